@@ -0,0 +1,142 @@
+// src-tauri/src/atomic.rs
+// Écritures atomiques des fichiers de l'application
+// Le contenu est écrit dans un fichier temporaire du même dossier, synchronisé
+// sur le disque, puis renommé par-dessus l'ancien : en cas de coupure (plantage,
+// clé USB retirée), l'ancien fichier reste intact
+
+use rand::RngCore;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Suffixe des fichiers temporaires (permet de retrouver ceux laissés par une coupure)
+pub const TEMP_SUFFIX: &str = ".cockpit-tmp";
+
+/// Remplace le contenu de `path` de façon atomique
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// Remplace `path` par ce que `write` écrit dans le fichier temporaire
+///
+/// Si `write` échoue, le fichier temporaire est supprimé et `path` n'est pas modifié.
+fn write_atomic_with<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    let temp_path = temp_path_for(path)?;
+
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        });
+
+    if let Err(e) = written.and_then(|_| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    sync_dir(path);
+    Ok(())
+}
+
+/// Chemin temporaire unique à côté de `path` (ex: ".sites.encrypted.3f2a9c1b.cockpit-tmp")
+fn temp_path_for(path: &Path) -> io::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nom de fichier invalide"))?;
+
+    let suffix = rand::thread_rng().next_u32();
+    Ok(path.with_file_name(format!(".{}.{:08x}{}", file_name, suffix, TEMP_SUFFIX)))
+}
+
+/// Synchronise le dossier pour que le renommage lui-même survive à une coupure
+///
+/// Au mieux : certains systèmes de fichiers (FAT des clés USB) refusent
+/// l'opération, le fichier est alors déjà écrit et renommé.
+#[cfg(unix)]
+fn sync_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) {}
+
+/// Supprime les fichiers temporaires laissés dans `dir` par une écriture interrompue
+///
+/// # Returns
+/// Les fichiers supprimés
+pub fn cleanup_temp_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    if !dir.exists() {
+        return Ok(removed);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_temp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.') && name.ends_with(TEMP_SUFFIX));
+
+        if is_temp && path.is_file() {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_interrupted_write_keeps_previous_file() {
+        let temp_dir = env::temp_dir().join("cockpit_atomic_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("sites.encrypted");
+
+        write_atomic(&path, b"version 1").unwrap();
+        write_atomic(&path, b"version 2").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"version 2");
+
+        // Écriture interrompue au milieu : l'ancien contenu reste en place
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"vers")?;
+            Err(io::Error::new(io::ErrorKind::WriteZero, "disque retiré"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"version 2");
+        assert!(cleanup_temp_files(&temp_dir).unwrap().is_empty());
+
+        // Processus tué pendant l'écriture : le fichier temporaire est retrouvé
+        // et supprimé au démarrage suivant, les autres fichiers sont conservés
+        let leftover = temp_path_for(&path).unwrap();
+        fs::write(&leftover, b"vers").unwrap();
+        fs::write(temp_dir.join("notes.tmp"), b"autre").unwrap();
+
+        assert_eq!(
+            cleanup_temp_files(&temp_dir).unwrap(),
+            vec![leftover.clone()]
+        );
+        assert!(!leftover.exists());
+        assert!(temp_dir.join("notes.tmp").exists());
+        assert_eq!(fs::read(&path).unwrap(), b"version 2");
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
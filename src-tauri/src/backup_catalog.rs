@@ -0,0 +1,532 @@
+// src-tauri/src/backup_catalog.rs
+// Catalogue des backups
+// Chaque backup du dossier backups/ est décrit dans index.json : date précise,
+// taille, empreinte SHA-256, version du format, raison de sa création et
+// nombre de sites quand il est connu. Une copie (index.copy.json) permet de
+// retrouver les raisons si le catalogue devient illisible. Le frontend
+// désigne un backup par son identifiant opaque, jamais par un chemin
+
+use crate::atomic;
+use crate::crypto::EncryptedData;
+use crate::error::{AppError, AppResult};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nom du fichier catalogue, dans le dossier des backups
+pub const CATALOG_FILE_NAME: &str = "index.json";
+
+/// Copie du catalogue, réécrite à chaque enregistrement
+pub const CATALOG_COPY_FILE_NAME: &str = "index.copy.json";
+
+/// Préfixe et suffixe des fichiers de backup
+const BACKUP_PREFIX: &str = "sites-";
+const BACKUP_SUFFIX: &str = ".encrypted";
+
+/// Au-delà, le nombre du nom de fichier est en millisecondes (en secondes
+/// pour les backups antérieurs au catalogue)
+const MILLIS_THRESHOLD: u64 = 100_000_000_000;
+
+/// Longueur d'un identifiant de backup (octets aléatoires en hexadécimal)
+const ID_LEN: usize = 16;
+
+/// Raison de la création d'un backup
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    /// Avant l'écrasement du fichier par une sauvegarde
+    Save,
+
+    /// Avant la restauration d'un autre backup
+    Restore,
+
+    /// Avant la migration du fichier vers un format ou un schéma plus récent
+    Migration,
+
+    /// Demandé par l'utilisateur (jamais supprimé par la rétention)
+    Manual,
+}
+
+/// Entrée du catalogue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupEntry {
+    /// Identifiant opaque, utilisé par les commandes
+    #[serde(default)]
+    pub id: String,
+
+    /// Nom du fichier dans le dossier des backups
+    pub name: String,
+
+    /// Date de création (timestamp Unix en millisecondes)
+    pub created_at_ms: u64,
+
+    /// Taille en octets
+    pub size: u64,
+
+    /// Empreinte SHA-256 du fichier (hexadécimal)
+    pub sha256: String,
+
+    /// Version du format de l'enveloppe (`None` si elle est illisible)
+    pub format_version: Option<String>,
+
+    /// Raison de la création (`None` pour un backup antérieur au catalogue)
+    pub reason: Option<BackupReason>,
+
+    /// Nombre de sites, s'il était connu à la création
+    pub site_count: Option<usize>,
+}
+
+/// Catalogue d'un dossier de backups
+pub struct BackupCatalog {
+    dir: PathBuf,
+    entries: Vec<BackupEntry>,
+}
+
+impl BackupCatalog {
+    /// Charge le catalogue de `dir` et le met en cohérence avec les fichiers
+    ///
+    /// Les entrées dont le fichier a disparu sont retirées ; les backups
+    /// sans entrée (antérieurs au catalogue, copiés à la main) sont décrits
+    /// à partir du fichier. Un catalogue absent ou illisible est repris de sa
+    /// copie, pour ne pas perdre les raisons (un backup manuel n'est jamais
+    /// supprimé par la rétention).
+    ///
+    /// # Errors
+    /// `CorruptVault` si le catalogue est illisible et sa copie illisible ou
+    /// absente (supprimer les deux fichiers reconstruit le catalogue sans les
+    /// raisons)
+    pub fn load(dir: &Path) -> AppResult<Self> {
+        let mut entries = match read_entries(&dir.join(CATALOG_FILE_NAME)) {
+            Ok(Some(entries)) => entries,
+            catalog => match read_entries(&dir.join(CATALOG_COPY_FILE_NAME))? {
+                Some(entries) => entries,
+                None => catalog?.unwrap_or_default(),
+            },
+        };
+        // Un catalogue modifié à la main ne doit pas désigner de fichier hors du dossier
+        entries.retain(|entry| is_safe_file_name(&entry.name) && dir.join(&entry.name).is_file());
+        for entry in entries.iter_mut().filter(|entry| entry.id.is_empty()) {
+            entry.id = id_for_name(&entry.name);
+        }
+
+        if dir.exists() {
+            for dir_entry in fs::read_dir(dir)? {
+                let dir_entry = dir_entry?;
+                let Some(name) = dir_entry.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                if !is_safe_file_name(&name) || entries.iter().any(|e| e.name == name) {
+                    continue;
+                }
+                entries.push(describe_unindexed(&dir_entry.path(), name)?);
+            }
+        }
+
+        let mut catalog = Self {
+            dir: dir.to_path_buf(),
+            entries,
+        };
+        catalog.sort();
+        Ok(catalog)
+    }
+
+    /// Entrées, de la plus récente à la plus ancienne
+    pub fn entries(&self) -> &[BackupEntry] {
+        &self.entries
+    }
+
+    /// Entrée d'un backup
+    ///
+    /// # Errors
+    /// `InvalidInput` si `id` n'a pas la forme d'un identifiant (chemin, `..`),
+    /// `BackupNotFound` s'il n'est pas au catalogue
+    pub fn get(&self, id: &str) -> AppResult<&BackupEntry> {
+        check_id(id)?;
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| AppError::BackupNotFound(id.to_string()))
+    }
+
+    /// Contenu d'un backup, vérifié avec l'empreinte du catalogue
+    ///
+    /// # Errors
+    /// `CorruptVault` si le fichier n'est pas un fichier ordinaire (lien
+    /// symbolique…) ou a été modifié depuis sa création
+    pub fn read(&self, id: &str) -> AppResult<Vec<u8>> {
+        let entry = self.get(id)?;
+        let path = self.dir.join(&entry.name);
+
+        if !fs::symlink_metadata(&path)?.file_type().is_file() {
+            return Err(AppError::CorruptVault(format!(
+                "{} n'est pas un fichier ordinaire",
+                entry.name
+            )));
+        }
+        let content = fs::read(&path)?;
+        if to_hex(&Sha256::digest(&content)) != entry.sha256 {
+            return Err(AppError::CorruptVault(format!(
+                "{} a été modifié depuis sa création",
+                entry.name
+            )));
+        }
+        Ok(content)
+    }
+
+    /// Copie `source` dans un nouveau backup et l'ajoute au catalogue
+    ///
+    /// Le nom est unique même pour plusieurs backups dans la même milliseconde.
+    pub fn add(
+        &mut self,
+        source: &Path,
+        reason: BackupReason,
+        site_count: Option<usize>,
+    ) -> AppResult<BackupEntry> {
+        let content = fs::read(source)?;
+        let created_at_ms = unix_millis();
+
+        let mut name = format!("{}{}{}", BACKUP_PREFIX, created_at_ms, BACKUP_SUFFIX);
+        let mut suffix = 1;
+        while self.dir.join(&name).exists() {
+            name = format!(
+                "{}{}-{}{}",
+                BACKUP_PREFIX, created_at_ms, suffix, BACKUP_SUFFIX
+            );
+            suffix += 1;
+        }
+        atomic::write_atomic(&self.dir.join(&name), &content)?;
+
+        let mut id = [0u8; ID_LEN / 2];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        let entry = BackupEntry {
+            id: to_hex(&id),
+            created_at_ms,
+            reason: Some(reason),
+            site_count,
+            ..describe(name, &content)
+        };
+        // En tête : le plus récent, y compris dans la même milliseconde
+        self.entries.insert(0, entry.clone());
+        self.sort();
+        self.save()?;
+        Ok(entry)
+    }
+
+    /// Supprime des backups et leurs entrées
+    pub fn remove(&mut self, names: &[&str]) -> AppResult<()> {
+        for name in names {
+            match fs::remove_file(self.dir.join(name)) {
+                // Déjà supprimé par une autre instance
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            self.entries.retain(|entry| entry.name != *name);
+        }
+        self.save()
+    }
+
+    /// Écrit le catalogue et sa copie
+    pub fn save(&self) -> AppResult<()> {
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        atomic::write_atomic(&self.dir.join(CATALOG_FILE_NAME), json.as_bytes())?;
+        atomic::write_atomic(&self.dir.join(CATALOG_COPY_FILE_NAME), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Du plus récent au plus ancien (tri stable : l'ordre d'ajout départage)
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.created_at_ms));
+    }
+}
+
+/// Entrées d'un fichier catalogue (`None` s'il est absent)
+///
+/// # Errors
+/// `CorruptVault` si le fichier n'est pas un catalogue
+fn read_entries(path: &Path) -> AppResult<Option<Vec<BackupEntry>>> {
+    match fs::read(path) {
+        Ok(content) => serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|e| AppError::CorruptVault(format!("{} illisible : {}", path.display(), e))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Le nom est-il celui d'un backup (`sites-*.encrypted`) situé directement
+/// dans le dossier, sans composant de chemin ?
+fn is_safe_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(file)), None) if file == name
+    ) && !name.contains(['/', '\\', ':'])
+        && name.starts_with(BACKUP_PREFIX)
+        && name.ends_with(BACKUP_SUFFIX)
+}
+
+/// Refuse un identifiant qui n'en est pas un (chemin, nom de fichier…)
+fn check_id(id: &str) -> AppResult<()> {
+    if id.contains(['/', '\\']) || id.contains("..") || Path::new(id).is_absolute() {
+        return Err(AppError::InvalidInput(
+            "Identifiant de backup invalide : chemin refusé".to_string(),
+        ));
+    }
+    if id.len() != ID_LEN || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidInput(format!(
+            "Identifiant de backup invalide : {}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Identifiant stable d'un backup absent du catalogue, dérivé de son nom
+fn id_for_name(name: &str) -> String {
+    to_hex(&Sha256::digest(name.as_bytes()))[..ID_LEN].to_string()
+}
+
+/// Description d'un fichier d'après son contenu
+fn describe(name: String, content: &[u8]) -> BackupEntry {
+    let format_version = serde_json::from_slice::<EncryptedData>(content)
+        .ok()
+        .map(|encrypted| encrypted.version);
+    BackupEntry {
+        id: String::new(),
+        name,
+        created_at_ms: 0,
+        size: content.len() as u64,
+        sha256: to_hex(&Sha256::digest(content)),
+        format_version,
+        reason: None,
+        site_count: None,
+    }
+}
+
+/// Description d'un backup absent du catalogue
+///
+/// La date vient du nom (`sites-{timestamp}.encrypted`) ; un fichier renommé à
+/// la main est daté de sa modification et traité comme un backup manuel.
+fn describe_unindexed(path: &Path, name: String) -> AppResult<BackupEntry> {
+    let content = fs::read(path)?;
+    let timestamp = name
+        .strip_prefix(BACKUP_PREFIX)
+        .and_then(|rest| rest.strip_suffix(BACKUP_SUFFIX))
+        .and_then(|stem| stem.split('-').next())
+        .and_then(|number| number.parse::<u64>().ok());
+
+    let (created_at_ms, reason) = match timestamp {
+        Some(ms) if ms >= MILLIS_THRESHOLD => (ms, None),
+        Some(secs) => (secs * 1000, None),
+        None => {
+            let modified = fs::metadata(path)?
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            (modified, Some(BackupReason::Manual))
+        }
+    };
+
+    Ok(BackupEntry {
+        id: id_for_name(&name),
+        created_at_ms,
+        reason,
+        ..describe(name, &content)
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Millisecondes écoulées depuis l'epoch Unix
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn by_name<'a>(catalog: &'a BackupCatalog, name: &str) -> Option<&'a BackupEntry> {
+        catalog.entries().iter().find(|entry| entry.name == name)
+    }
+
+    #[test]
+    fn test_catalog_entries_and_reconciliation() {
+        let temp_dir = env::temp_dir().join("cockpit_backup_catalog_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let source = temp_dir.join("sites.encrypted");
+        fs::write(
+            &source,
+            r#"{"version":"1.2","algorithm":"AES-256-GCM","kdf":"Argon2id",
+                "nonce":"","ciphertext":"","auth_tag":""}"#,
+        )
+        .unwrap();
+        let backups = temp_dir.join("backups");
+        fs::create_dir_all(&backups).unwrap();
+
+        // Plusieurs backups dans la même milliseconde : noms distincts
+        let mut catalog = BackupCatalog::load(&backups).unwrap();
+        let first = catalog.add(&source, BackupReason::Save, Some(3)).unwrap();
+        let second = catalog.add(&source, BackupReason::Save, None).unwrap();
+        let third = catalog.add(&source, BackupReason::Restore, None).unwrap();
+        assert_ne!(first.name, second.name);
+        assert_ne!(second.name, third.name);
+        assert_ne!(first.id, second.id);
+        assert_eq!(catalog.entries()[0], third);
+
+        assert_eq!(first.format_version.as_deref(), Some("1.2"));
+        assert_eq!(first.site_count, Some(3));
+        assert_eq!(first.size, fs::metadata(&source).unwrap().len());
+        assert_eq!(
+            first.sha256,
+            to_hex(&Sha256::digest(fs::read(&source).unwrap()))
+        );
+
+        // Backup d'avant le catalogue, fichier renommé à la main, fichier supprimé
+        fs::write(backups.join("sites-1712345678.encrypted"), b"ancien").unwrap();
+        fs::write(backups.join("sites-avant-import.encrypted"), b"copie").unwrap();
+        fs::remove_file(backups.join(&second.name)).unwrap();
+
+        let catalog = BackupCatalog::load(&backups).unwrap();
+        assert_eq!(catalog.entries().len(), 4);
+        assert!(matches!(
+            catalog.get(&second.id),
+            Err(AppError::BackupNotFound(_))
+        ));
+        assert_eq!(
+            catalog.get(&third.id).unwrap().reason,
+            Some(BackupReason::Restore)
+        );
+
+        let legacy = by_name(&catalog, "sites-1712345678.encrypted").unwrap();
+        assert_eq!(legacy.created_at_ms, 1_712_345_678_000);
+        assert_eq!(legacy.reason, None);
+        assert_eq!(legacy.format_version, None);
+        let manual = by_name(&catalog, "sites-avant-import.encrypted").unwrap();
+        assert_eq!(manual.reason, Some(BackupReason::Manual));
+
+        // Identifiant stable tant que le catalogue n'est pas réécrit
+        let reloaded = BackupCatalog::load(&backups).unwrap();
+        assert_eq!(
+            catalog.get(&legacy.id).unwrap(),
+            reloaded.get(&legacy.id).unwrap()
+        );
+
+        // Le plus récent en premier ; le plus ancien est celui d'avant le catalogue
+        assert!(catalog
+            .entries()
+            .windows(2)
+            .all(|pair| pair[0].created_at_ms >= pair[1].created_at_ms));
+        assert_eq!(catalog.entries()[3].name, "sites-1712345678.encrypted");
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_catalog_recovers_reasons_from_copy() {
+        let temp_dir = env::temp_dir().join("cockpit_backup_catalog_copy_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let backups = temp_dir.join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        let source = temp_dir.join("sites.encrypted");
+        fs::write(&source, b"coffre").unwrap();
+
+        let mut catalog = BackupCatalog::load(&backups).unwrap();
+        let manual = catalog.add(&source, BackupReason::Manual, None).unwrap();
+
+        // Catalogue endommagé : les raisons sont reprises de la copie
+        fs::write(backups.join(CATALOG_FILE_NAME), b"{tronqu").unwrap();
+        let catalog = BackupCatalog::load(&backups).unwrap();
+        assert_eq!(catalog.get(&manual.id).unwrap(), &manual);
+
+        // Catalogue et copie endommagés : erreur plutôt que raisons perdues
+        fs::write(backups.join(CATALOG_COPY_FILE_NAME), b"").unwrap();
+        assert!(matches!(
+            BackupCatalog::load(&backups),
+            Err(AppError::CorruptVault(_))
+        ));
+
+        // Copie seule endommagée : le catalogue fait foi
+        catalog.save().unwrap();
+        fs::write(backups.join(CATALOG_COPY_FILE_NAME), b"").unwrap();
+        assert_eq!(BackupCatalog::load(&backups).unwrap().entries(), &[manual]);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_catalog_rejects_paths() {
+        let temp_dir = env::temp_dir().join("cockpit_backup_catalog_paths_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let backups = temp_dir.join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        fs::write(temp_dir.join("sites.encrypted"), b"coffre").unwrap();
+
+        // Catalogue modifié pour désigner un fichier hors du dossier
+        let forged = serde_json::json!([{
+            "id": "0123456789abcdef",
+            "name": "../sites.encrypted",
+            "created_at_ms": 0,
+            "size": 6,
+            "sha256": to_hex(&Sha256::digest(b"coffre")),
+            "format_version": null,
+            "reason": "manual",
+            "site_count": null
+        }]);
+        fs::write(backups.join(CATALOG_FILE_NAME), forged.to_string()).unwrap();
+
+        let catalog = BackupCatalog::load(&backups).unwrap();
+        assert!(catalog.entries().is_empty());
+        assert!(matches!(
+            catalog.read("0123456789abcdef"),
+            Err(AppError::BackupNotFound(_))
+        ));
+
+        // Chemins à la place d'un identifiant
+        for id in [
+            "../sites.encrypted",
+            "..\\sites.encrypted",
+            "/etc/passwd",
+            "C:\\Windows\\win.ini",
+            "sites-1712345678.encrypted",
+            "backups/../../x",
+            "..",
+            "",
+        ] {
+            assert!(
+                matches!(catalog.get(id), Err(AppError::InvalidInput(_))),
+                "{id}"
+            );
+        }
+
+        assert!(is_safe_file_name("sites-1712345678.encrypted"));
+        for name in [
+            "../sites-1.encrypted",
+            "sites-1/../../sites.encrypted",
+            "sites-1:flux.encrypted",
+            "/tmp/sites-1.encrypted",
+        ] {
+            assert!(!is_safe_file_name(name), "{name}");
+        }
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,26 +1,19 @@
 // src-tauri/src/config.rs
 // Module de gestion de la configuration persistante de l'application
 
+use crate::atomic;
+use crate::error::AppResult;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Configuration de l'application
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct AppConfig {
     /// Emplacement personnalisé des données (si défini)
     pub custom_data_location: Option<String>,
 }
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            custom_data_location: None,
-        }
-    }
-}
-
 /// Gestionnaire de configuration
 pub struct ConfigManager {
     config_path: PathBuf,
@@ -30,7 +23,7 @@ impl ConfigManager {
     /// Crée un nouveau gestionnaire de configuration
     /// Le fichier config.json est stocké à côté de l'exécutable (mode portable)
     /// ou dans le dossier système approprié (mode classique)
-    pub fn new(config_dir: &Path) -> Result<Self, Box<dyn Error>> {
+    pub fn new(config_dir: &Path) -> AppResult<Self> {
         let config_path = config_dir.join("config.json");
 
         // Créer le dossier parent si nécessaire
@@ -44,7 +37,7 @@ impl ConfigManager {
     }
 
     /// Charge la configuration depuis le fichier
-    pub fn load(&self) -> Result<AppConfig, Box<dyn Error>> {
+    pub fn load(&self) -> AppResult<AppConfig> {
         if !self.config_path.exists() {
             return Ok(AppConfig::default());
         }
@@ -56,14 +49,14 @@ impl ConfigManager {
     }
 
     /// Sauvegarde la configuration dans le fichier
-    pub fn save(&self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
+    pub fn save(&self, config: &AppConfig) -> AppResult<()> {
         let json = serde_json::to_string_pretty(config)?;
-        fs::write(&self.config_path, json)?;
+        atomic::write_atomic(&self.config_path, json.as_bytes())?;
         Ok(())
     }
 
     /// Met à jour l'emplacement des données personnalisé
-    pub fn set_custom_data_location(&self, location: Option<String>) -> Result<(), Box<dyn Error>> {
+    pub fn set_custom_data_location(&self, location: Option<String>) -> AppResult<()> {
         let mut config = self.load()?;
         config.custom_data_location = location;
         self.save(&config)?;
@@ -71,7 +64,7 @@ impl ConfigManager {
     }
 
     /// Récupère l'emplacement des données personnalisé
-    pub fn get_custom_data_location(&self) -> Result<Option<String>, Box<dyn Error>> {
+    pub fn get_custom_data_location(&self) -> AppResult<Option<String>> {
         let config = self.load()?;
         Ok(config.custom_data_location)
     }
@@ -1,48 +1,778 @@
 // src-tauri/src/crypto.rs
 // Module de chiffrement sécurisé pour Cockpit CFDT
-// Utilise AES-256-GCM (authentifié) + Argon2id (dérivation de clé)
+// Utilise AES-256-GCM ou XChaCha20-Poly1305 (authentifiés) + Argon2id (dérivation de clé)
+// Les données sont chiffrées avec une clé aléatoire, elle-même protégée
+// par un ou plusieurs emplacements de clé (mot de passe, clé de secours)
 
+use crate::error::{AppError, AppResult};
+use crate::memory::LockedKey;
 use aes_gcm::{
-    aead::{Aead, KeyInit},
-    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit, Nonce, Payload},
+    Aes256Gcm,
 };
-use argon2::Argon2;
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{engine::general_purpose, Engine as _};
-use rand::RngCore;
+use chacha20poly1305::XChaCha20Poly1305;
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 /// Résultat d'une opération crypto
-pub type CryptoResult<T> = Result<T, Box<dyn Error>>;
+pub type CryptoResult<T> = AppResult<T>;
+
+/// Version du format historique : `kdf_params` y était écrit mais ignoré,
+/// la clé étant toujours dérivée avec `Argon2::default()`
+pub const LEGACY_FORMAT_VERSION: &str = "1.0";
+
+/// Version du format 1.1 : la clé est dérivée du mot de passe avec les
+/// `kdf_params` stockés
+pub const PASSWORD_FORMAT_VERSION: &str = "1.1";
+
+/// Version du format 1.2 : clé de données aléatoire protégée par des
+/// emplacements de clé (`key_slots`)
+pub const KEY_SLOTS_FORMAT_VERSION: &str = "1.2";
+
+/// Version du format 1.3 : comme 1.2, avec l'en-tête (version, algorithme,
+/// KDF) authentifié comme données associées AEAD
+pub const HEADER_FORMAT_VERSION: &str = "1.3";
+
+/// Version du format courant : comme 1.3, avec un tag propre à l'en-tête qui
+/// authentifie aussi les paramètres des emplacements de clé, et distingue un
+/// en-tête modifié de données endommagées
+pub const FORMAT_VERSION: &str = "1.4";
+
+/// Libellé de l'emplacement créé avec le mot de passe initial
+pub const MASTER_SLOT_LABEL: &str = "Mot de passe maître";
+
+/// Alphabet des clés de secours (sans caractères ambigus : 0/O, 1/I/L, U)
+const RECOVERY_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTVWXYZ23456789";
+
+/// Nombre de caractères d'une clé de secours (~118 bits d'entropie)
+const RECOVERY_KEY_LEN: usize = 24;
+
+/// Taille d'un fichier clé généré
+const KEYFILE_LEN: usize = 64;
+
+/// Taille maximale acceptée pour un fichier clé
+const KEYFILE_MAX_LEN: u64 = 10 * 1024 * 1024;
+
+/// Décompose une version de format "majeure.mineure"
+fn parse_format_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Mot de passe reçu du frontend, effacé de la mémoire à la libération
+///
+/// Le contenu n'apparaît jamais dans `Debug` (logs, messages d'erreur).
+#[derive(Deserialize, Zeroize, ZeroizeOnDrop)]
+#[serde(transparent)]
+pub struct Password(String);
+
+impl Password {
+    /// Accès au mot de passe en clair
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Password {
+    fn from(password: &str) -> Self {
+        Self(password.to_string())
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
 
 /// Structure contenant un secret qui sera effacé de la mémoire
+///
+/// La clé est gardée dans une page verrouillée en mémoire vive quand le
+/// système l'autorise (voir `memory.rs`).
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct SecretKey {
-    key: [u8; 32], // 256 bits
+    key: LockedKey, // 256 bits
 }
 
 impl SecretKey {
-    /// Crée une clé depuis un mot de passe en utilisant Argon2
-    pub fn from_password(password: &str, salt: &[u8]) -> CryptoResult<Self> {
+    /// Crée une clé depuis un mot de passe en utilisant Argon2id
+    /// avec les paramètres fournis
+    ///
+    /// Si un fichier clé est fourni, son empreinte est utilisée comme
+    /// secret Argon2 : le mot de passe seul ne suffit plus.
+    pub fn from_password(
+        password: &str,
+        salt: &[u8],
+        params: &KdfParams,
+        keyfile: Option<&Keyfile>,
+    ) -> CryptoResult<Self> {
         // Configuration Argon2id (résistant aux attaques GPU et side-channel)
-        let argon2 = Argon2::default();
+        let argon2 = params.to_argon2(keyfile.map(|k| k.digest.as_slice()))?;
 
-        // Dérivation de la clé (cela prend du temps intentionnellement)
-        let mut key = [0u8; 32];
+        // Dérivation de la clé (cela prend du temps intentionnellement),
+        // directement dans la mémoire protégée
+        let mut key = LockedKey::zeroed();
         argon2
-            .hash_password_into(password.as_bytes(), salt, &mut key)
-            .map_err(|e| format!("Erreur dérivation clé: {}", e))?;
+            .hash_password_into(password.as_bytes(), salt, &mut key[..])
+            .map_err(|e| AppError::Crypto(format!("dérivation clé: {}", e)))?;
 
         Ok(SecretKey { key })
     }
 
+    /// Génère une clé aléatoire
+    fn random() -> Self {
+        let mut key = LockedKey::zeroed();
+        rand::thread_rng().fill_bytes(&mut key[..]);
+        SecretKey { key }
+    }
+
     /// Retourne la clé (usage interne uniquement)
     fn as_bytes(&self) -> &[u8; 32] {
         &self.key
     }
 }
 
+/// Fichier clé : second facteur mélangé à la dérivation Argon2
+///
+/// Seule l'empreinte SHA-256 du contenu est conservée en mémoire, et elle
+/// n'est jamais écrite dans le coffre.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Keyfile {
+    digest: LockedKey,
+}
+
+impl Keyfile {
+    /// Calcule l'empreinte d'un contenu de fichier clé
+    pub fn from_bytes(content: &[u8]) -> Self {
+        let mut digest = LockedKey::zeroed();
+        let mut hash: [u8; 32] = Sha256::digest(content).into();
+        digest.copy_from_slice(&hash);
+        hash.zeroize();
+        Self { digest }
+    }
+
+    /// Lit un fichier clé
+    pub fn from_file(path: &Path) -> CryptoResult<Self> {
+        let size = fs::metadata(path)
+            .map_err(|e| AppError::InvalidInput(format!("Fichier clé illisible: {}", e)))?
+            .len();
+        if size == 0 || size > KEYFILE_MAX_LEN {
+            return Err(AppError::InvalidInput(
+                "Fichier clé vide ou trop volumineux (10 MB maximum)".to_string(),
+            ));
+        }
+
+        let mut content = fs::read(path)
+            .map_err(|e| AppError::InvalidInput(format!("Fichier clé illisible: {}", e)))?;
+        let keyfile = Self::from_bytes(&content);
+        content.zeroize();
+        Ok(keyfile)
+    }
+
+    /// Génère un nouveau fichier clé aléatoire (refuse d'écraser un fichier existant)
+    pub fn generate(path: &Path) -> CryptoResult<Self> {
+        // Création exclusive : un fichier apparu entre-temps (un autre fichier
+        // clé, par exemple) n'est jamais écrasé
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                return Err(AppError::InvalidInput(
+                    "Un fichier existe déjà à cet emplacement".to_string(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut content = [0u8; KEYFILE_LEN];
+        rand::thread_rng().fill_bytes(&mut content);
+        // Le coffre dépendra de ce fichier : il doit être sur le disque
+        if let Err(e) = file.write_all(&content).and_then(|_| file.sync_all()) {
+            content.zeroize();
+            drop(file);
+            let _ = fs::remove_file(path);
+            return Err(e.into());
+        }
+
+        let keyfile = Self::from_bytes(&content);
+        content.zeroize();
+        Ok(keyfile)
+    }
+}
+
+/// Suite de chiffrement authentifié des données, enregistrée dans `algorithm`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CipherSuite {
+    /// AES-256-GCM, nonce aléatoire de 96 bits
+    #[default]
+    #[serde(rename = "AES-256-GCM")]
+    Aes256Gcm,
+
+    /// XChaCha20-Poly1305, nonce aléatoire de 192 bits (pas de risque de
+    /// collision, rapide sans accélération matérielle AES)
+    #[serde(rename = "XChaCha20-Poly1305")]
+    XChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// Nom enregistré dans le champ `algorithm`
+    pub fn name(self) -> &'static str {
+        match self {
+            CipherSuite::Aes256Gcm => "AES-256-GCM",
+            CipherSuite::XChaCha20Poly1305 => "XChaCha20-Poly1305",
+        }
+    }
+
+    /// Retrouve la suite d'après le champ `algorithm` d'un fichier
+    pub fn from_name(name: &str) -> CryptoResult<Self> {
+        [CipherSuite::Aes256Gcm, CipherSuite::XChaCha20Poly1305]
+            .into_iter()
+            .find(|suite| suite.name() == name)
+            .ok_or_else(|| AppError::UnsupportedAlgorithm(name.to_string()))
+    }
+
+    /// Chiffre avec un nonce aléatoire, en authentifiant `aad`
+    ///
+    /// # Returns
+    /// (nonce, ciphertext, auth_tag)
+    fn seal(
+        self,
+        key: &SecretKey,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> CryptoResult<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        match self {
+            CipherSuite::Aes256Gcm => seal_with::<Aes256Gcm>(key, plaintext, aad),
+            CipherSuite::XChaCha20Poly1305 => seal_with::<XChaCha20Poly1305>(key, plaintext, aad),
+        }
+    }
+
+    /// Déchiffre (échoue si la clé, les données ou `aad` ne correspondent pas)
+    fn open(
+        self,
+        key: &SecretKey,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        auth_tag: &[u8],
+        aad: &[u8],
+    ) -> Option<Zeroizing<Vec<u8>>> {
+        match self {
+            CipherSuite::Aes256Gcm => open_with::<Aes256Gcm>(key, nonce, ciphertext, auth_tag, aad),
+            CipherSuite::XChaCha20Poly1305 => {
+                open_with::<XChaCha20Poly1305>(key, nonce, ciphertext, auth_tag, aad)
+            }
+        }
+    }
+}
+
+/// Chiffre avec l'AEAD `C` et un nonce aléatoire, en authentifiant `aad`
+fn seal_with<C: Aead + KeyInit>(
+    key: &SecretKey,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> CryptoResult<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let cipher = C::new_from_slice(key.as_bytes())
+        .map_err(|e| AppError::Crypto(format!("création cipher: {}", e)))?;
+
+    // Nonce aléatoire (taille imposée par l'algorithme)
+    let mut nonce = Nonce::<C>::default();
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|e| AppError::Crypto(format!("chiffrement: {}", e)))?;
+
+    // Le tag d'authentification occupe les 16 derniers octets
+    let auth_tag = ciphertext.split_off(ciphertext.len() - 16);
+    Ok((nonce.to_vec(), ciphertext, auth_tag))
+}
+
+/// Déchiffre avec l'AEAD `C` (échoue si `aad` diffère de celui du chiffrement)
+fn open_with<C: Aead + KeyInit>(
+    key: &SecretKey,
+    nonce: &[u8],
+    ciphertext: &[u8],
+    auth_tag: &[u8],
+    aad: &[u8],
+) -> Option<Zeroizing<Vec<u8>>> {
+    let cipher = C::new_from_slice(key.as_bytes()).ok()?;
+    if nonce.len() != Nonce::<C>::default().len() {
+        return None;
+    }
+
+    let mut combined = ciphertext.to_vec();
+    combined.extend_from_slice(auth_tag);
+    cipher
+        .decrypt(
+            Nonce::<C>::from_slice(nonce),
+            Payload {
+                msg: &combined,
+                aad,
+            },
+        )
+        .ok()
+        .map(Zeroizing::new)
+}
+
+/// Décode un champ base64
+fn decode(value: &str, field: &str) -> CryptoResult<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| AppError::CorruptVault(format!("décodage {}: {}", field, e)))
+}
+
+/// Secondes écoulées depuis l'epoch Unix
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Génère une clé de secours imprimable (ex: "ABCD-EFGH-...")
+pub fn generate_recovery_key() -> String {
+    let mut rng = rand::thread_rng();
+    // Capacité exacte : la chaîne n'est jamais réallouée (ni copiée)
+    let mut key = String::with_capacity(RECOVERY_KEY_LEN + RECOVERY_KEY_LEN / 4 - 1);
+    for i in 0..RECOVERY_KEY_LEN {
+        if i > 0 && i % 4 == 0 {
+            key.push('-');
+        }
+        key.push(RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char);
+    }
+    key
+}
+
+/// Normalise une clé de secours saisie (majuscules, sans tirets ni espaces)
+fn normalize_recovery_key(input: &str) -> Option<Zeroizing<String>> {
+    let mut normalized = Zeroizing::new(String::with_capacity(input.len()));
+    normalized.extend(
+        input
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase()),
+    );
+
+    let valid = normalized.len() == RECOVERY_KEY_LEN
+        && normalized.bytes().all(|b| RECOVERY_ALPHABET.contains(&b));
+    valid.then_some(normalized)
+}
+
+/// Type de secret protégeant un emplacement de clé
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySlotKind {
+    /// Mot de passe (maître ou d'un collègue)
+    Password,
+    /// Clé de secours imprimable
+    Recovery,
+}
+
+/// Emplacement de clé : la clé de données chiffrée avec une clé dérivée d'un secret
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeySlot {
+    /// Identifiant de l'emplacement
+    pub id: String,
+
+    /// Libellé affiché (ex: "Mot de passe maître", "Clé de secours")
+    pub label: String,
+
+    /// Type de secret
+    pub kind: KeySlotKind,
+
+    /// Paramètres KDF de cet emplacement
+    pub kdf_params: KdfParams,
+
+    /// Salt pour la dérivation de clé (base64)
+    pub salt: String,
+
+    /// Nonce pour AES-GCM (base64)
+    pub nonce: String,
+
+    /// Clé de données chiffrée + tag GCM (base64)
+    pub wrapped_key: String,
+
+    /// Un fichier clé est requis en plus du mot de passe
+    /// (seule l'exigence est enregistrée, jamais l'empreinte du fichier)
+    #[serde(default)]
+    pub keyfile: bool,
+
+    /// Date de création (timestamp Unix)
+    pub created_at: u64,
+}
+
+/// Description publique d'un emplacement de clé (sans matériel chiffré)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeySlotInfo {
+    pub id: String,
+    pub label: String,
+    pub kind: KeySlotKind,
+    pub keyfile: bool,
+    pub created_at: u64,
+}
+
+impl KeySlot {
+    /// Protège `data_key` avec un secret (et un fichier clé pour un mot de passe)
+    fn wrap(
+        data_key: &SecretKey,
+        kind: KeySlotKind,
+        label: &str,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<Self> {
+        let secret = match kind {
+            KeySlotKind::Password => Zeroizing::new(secret.to_string()),
+            KeySlotKind::Recovery => normalize_recovery_key(secret)
+                .ok_or_else(|| AppError::InvalidInput("Clé de secours invalide".to_string()))?,
+        };
+
+        // La clé de secours sert justement quand le fichier clé est perdu
+        let keyfile = keyfile.filter(|_| kind == KeySlotKind::Password);
+
+        let mut salt = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let wrapping_key = SecretKey::from_password(&secret, &salt, params, keyfile)?;
+        let (nonce, mut wrapped, auth_tag) =
+            CipherSuite::Aes256Gcm.seal(&wrapping_key, data_key.as_bytes(), &[])?;
+        wrapped.extend_from_slice(&auth_tag);
+
+        let mut id = [0u8; 6];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        Ok(Self {
+            id: general_purpose::URL_SAFE_NO_PAD.encode(id),
+            label: label.to_string(),
+            kind,
+            kdf_params: params.clone(),
+            salt: general_purpose::STANDARD.encode(salt),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            wrapped_key: general_purpose::STANDARD.encode(wrapped),
+            keyfile: keyfile.is_some(),
+            created_at: unix_now(),
+        })
+    }
+
+    /// Tente de récupérer la clé de données avec un secret
+    ///
+    /// Le fichier clé n'est utilisé que si l'emplacement l'exige.
+    ///
+    /// # Returns
+    /// `None` si le secret (ou le fichier clé) ne correspond pas à cet emplacement
+    fn unwrap_key(
+        &self,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+    ) -> CryptoResult<Option<SecretKey>> {
+        let keyfile = match (self.keyfile, keyfile) {
+            (true, None) => return Ok(None),
+            (true, Some(keyfile)) => Some(keyfile),
+            (false, _) => None,
+        };
+
+        let secret = match self.kind {
+            KeySlotKind::Password => Zeroizing::new(secret.to_string()),
+            KeySlotKind::Recovery => match normalize_recovery_key(secret) {
+                Some(normalized) => normalized,
+                None => return Ok(None),
+            },
+        };
+
+        let salt = decode(&self.salt, "salt")?;
+        let nonce = decode(&self.nonce, "nonce")?;
+        let wrapped = decode(&self.wrapped_key, "wrapped_key")?;
+        if wrapped.len() != 48 {
+            return Err(AppError::CorruptVault("emplacement de clé".to_string()));
+        }
+
+        let wrapping_key = SecretKey::from_password(&secret, &salt, &self.kdf_params, keyfile)?;
+        let Some(bytes) =
+            CipherSuite::Aes256Gcm.open(&wrapping_key, &nonce, &wrapped[..32], &wrapped[32..], &[])
+        else {
+            return Ok(None);
+        };
+
+        let mut key = LockedKey::zeroed();
+        key.copy_from_slice(&bytes);
+        Ok(Some(SecretKey { key }))
+    }
+
+    /// Description publique de l'emplacement
+    pub fn info(&self) -> KeySlotInfo {
+        KeySlotInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            kind: self.kind,
+            keyfile: self.keyfile,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// Clé de données d'un coffre ouvert, conservée pour la durée d'une session
+///
+/// Garde aussi les emplacements de clé du coffre afin de pouvoir
+/// re-chiffrer sans redemander de secret. La clé elle-même est effacée
+/// de la mémoire à la destruction (voir `SecretKey`).
+pub struct SessionKey {
+    key: SecretKey,
+    key_slots: Vec<KeySlot>,
+    unlocked_slot: Option<String>,
+}
+
+impl SessionKey {
+    /// Crée une nouvelle clé de données protégée par un mot de passe
+    /// (et éventuellement un fichier clé)
+    pub fn generate(
+        label: &str,
+        password: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<Self> {
+        let key = SecretKey::random();
+        let slot = KeySlot::wrap(
+            &key,
+            KeySlotKind::Password,
+            label,
+            password,
+            keyfile,
+            params,
+        )?;
+        Ok(Self {
+            key,
+            unlocked_slot: Some(slot.id.clone()),
+            key_slots: vec![slot],
+        })
+    }
+
+    /// Ouvre un fichier chiffré avec un secret (mot de passe ou clé de secours)
+    /// et, si un emplacement l'exige, un fichier clé
+    ///
+    /// Pour les formats antérieurs à 1.2, la clé est dérivée directement du
+    /// mot de passe et la session ne contient aucun emplacement : elle ne
+    /// permet que le déchiffrement.
+    pub fn open(
+        encrypted: &EncryptedData,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+    ) -> CryptoResult<Self> {
+        CryptoEngine::check_format_version(&encrypted.version)?;
+
+        if !encrypted.uses_key_slots() {
+            // Un ancien format n'a pas d'emplacements : la version a été modifiée
+            if !encrypted.key_slots.is_empty() {
+                return Err(AppError::HeaderTampered);
+            }
+
+            let kdf_params = CryptoEngine::effective_kdf_params(encrypted)?;
+            let salt = decode(encrypted.salt.as_deref().unwrap_or_default(), "salt")?;
+            let key = SecretKey::from_password(secret, &salt, &kdf_params, None)?;
+            return Ok(Self {
+                key,
+                key_slots: Vec::new(),
+                unlocked_slot: None,
+            });
+        }
+
+        for slot in &encrypted.key_slots {
+            if let Some(key) = slot.unwrap_key(secret, keyfile)? {
+                return Ok(Self {
+                    key,
+                    key_slots: encrypted.key_slots.clone(),
+                    unlocked_slot: Some(slot.id.clone()),
+                });
+            }
+        }
+
+        if keyfile.is_none() && encrypted.requires_keyfile() {
+            return Err(AppError::KeyfileRequired);
+        }
+        Err(AppError::WrongPassword)
+    }
+
+    /// Chiffre un enregistrement indépendant du fichier de données (ex: entrée
+    /// du journal des modifications), en authentifiant `aad`
+    pub fn seal_record(
+        &self,
+        plaintext: &[u8],
+        aad: &[u8],
+        suite: CipherSuite,
+    ) -> CryptoResult<SealedRecord> {
+        let (nonce, ciphertext, auth_tag) = suite.seal(&self.key, plaintext, aad)?;
+        Ok(SealedRecord {
+            algorithm: suite.name().to_string(),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+            auth_tag: general_purpose::STANDARD.encode(auth_tag),
+        })
+    }
+
+    /// Déchiffre un enregistrement
+    ///
+    /// # Returns
+    /// `None` s'il a été chiffré avec une autre clé de données, ou si
+    /// l'enregistrement ou `aad` ont été modifiés
+    pub fn open_record(
+        &self,
+        record: &SealedRecord,
+        aad: &[u8],
+    ) -> CryptoResult<Option<Zeroizing<Vec<u8>>>> {
+        let suite = CipherSuite::from_name(&record.algorithm)?;
+        Ok(suite.open(
+            &self.key,
+            &decode(&record.nonce, "nonce")?,
+            &decode(&record.ciphertext, "ciphertext")?,
+            &decode(&record.auth_tag, "auth_tag")?,
+            aad,
+        ))
+    }
+
+    /// Emplacements de clé du coffre
+    pub fn key_slots(&self) -> &[KeySlot] {
+        &self.key_slots
+    }
+
+    /// Emplacement utilisé pour ouvrir la session
+    pub fn unlocked_slot(&self) -> Option<&KeySlot> {
+        let id = self.unlocked_slot.as_deref()?;
+        self.key_slots.iter().find(|slot| slot.id == id)
+    }
+
+    /// Ajoute un emplacement protégeant la clé de données avec un nouveau secret
+    pub fn add_slot(
+        &mut self,
+        kind: KeySlotKind,
+        label: &str,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<KeySlotInfo> {
+        let slot = KeySlot::wrap(&self.key, kind, label, secret, keyfile, params)?;
+        let info = slot.info();
+        self.key_slots.push(slot);
+        Ok(info)
+    }
+
+    /// Révoque un emplacement (le dernier emplacement ne peut pas être supprimé)
+    pub fn revoke_slot(&mut self, slot_id: &str) -> CryptoResult<()> {
+        let index = self
+            .key_slots
+            .iter()
+            .position(|slot| slot.id == slot_id)
+            .ok_or_else(|| AppError::InvalidInput("Emplacement de clé introuvable".to_string()))?;
+
+        if self.key_slots.len() == 1 {
+            return Err(AppError::InvalidInput(
+                "Impossible de supprimer le dernier emplacement de clé".to_string(),
+            ));
+        }
+
+        self.key_slots.remove(index);
+        if self.unlocked_slot.as_deref() == Some(slot_id) {
+            self.unlocked_slot = None;
+        }
+        Ok(())
+    }
+
+    /// Remplace le secret d'un emplacement mot de passe
+    ///
+    /// Seul l'emplacement ouvert par `old_secret` est modifié ; il conserve
+    /// son identifiant, son libellé et son exigence de fichier clé.
+    pub fn rewrap_slot(
+        &mut self,
+        old_secret: &str,
+        new_secret: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<()> {
+        let index = self.find_password_slot(old_secret, keyfile)?;
+        let keyfile = keyfile.filter(|_| self.key_slots[index].keyfile);
+        self.rewrap_at(index, new_secret, keyfile, params)
+    }
+
+    /// Ajoute ou retire l'exigence d'un fichier clé sur un emplacement mot de passe
+    ///
+    /// # Arguments
+    /// * `password` - Mot de passe de l'emplacement
+    /// * `current_keyfile` - Fichier clé actuellement requis, s'il y en a un
+    /// * `new_keyfile` - Fichier clé à exiger désormais (`None` pour le retirer)
+    pub fn set_slot_keyfile(
+        &mut self,
+        password: &str,
+        current_keyfile: Option<&Keyfile>,
+        new_keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<()> {
+        let index = self.find_password_slot(password, current_keyfile)?;
+        self.rewrap_at(index, password, new_keyfile, params)
+    }
+
+    /// Re-protège l'emplacement utilisé pour ouvrir la session
+    /// (même secret, nouveaux paramètres KDF)
+    pub fn rewrap_unlocked_slot(
+        &mut self,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<()> {
+        let id = self
+            .unlocked_slot
+            .as_deref()
+            .ok_or_else(|| AppError::Crypto("aucun emplacement de clé ouvert".to_string()))?;
+        let index = self
+            .key_slots
+            .iter()
+            .position(|slot| slot.id == id)
+            .ok_or_else(|| AppError::InvalidInput("Emplacement de clé introuvable".to_string()))?;
+
+        let keyfile = keyfile.filter(|_| self.key_slots[index].keyfile);
+        self.rewrap_at(index, secret, keyfile, params)
+    }
+
+    /// Index de l'emplacement mot de passe ouvert par ces secrets
+    fn find_password_slot(&self, password: &str, keyfile: Option<&Keyfile>) -> CryptoResult<usize> {
+        for (index, slot) in self.key_slots.iter().enumerate() {
+            if slot.kind == KeySlotKind::Password && slot.unwrap_key(password, keyfile)?.is_some() {
+                return Ok(index);
+            }
+        }
+
+        Err(AppError::WrongPassword)
+    }
+
+    /// Remplace l'emplacement `index` en conservant son identifiant, son type
+    /// et son libellé
+    fn rewrap_at(
+        &mut self,
+        index: usize,
+        secret: &str,
+        keyfile: Option<&Keyfile>,
+        params: &KdfParams,
+    ) -> CryptoResult<()> {
+        let slot = &self.key_slots[index];
+        let mut rewrapped =
+            KeySlot::wrap(&self.key, slot.kind, &slot.label, secret, keyfile, params)?;
+        rewrapped.id = slot.id.clone();
+        self.key_slots[index] = rewrapped;
+        Ok(())
+    }
+}
+
 /// Données chiffrées avec métadonnées
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EncryptedData {
@@ -55,24 +785,207 @@ pub struct EncryptedData {
     /// Fonction de dérivation de clé
     pub kdf: String,
 
-    /// Paramètres KDF
-    pub kdf_params: KdfParams,
+    /// Paramètres KDF (formats 1.0 et 1.1 uniquement)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf_params: Option<KdfParams>,
 
-    /// Salt pour la dérivation de clé (base64)
-    pub salt: String,
+    /// Salt pour la dérivation de clé, en base64 (formats 1.0 et 1.1 uniquement)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
 
-    /// Nonce pour AES-GCM (base64)
+    /// Emplacements protégeant la clé de données (format 1.2)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_slots: Vec<KeySlot>,
+
+    /// Nonce de l'algorithme (base64)
+    pub nonce: String,
+
+    /// Données chiffrées (base64)
+    pub ciphertext: String,
+
+    /// Tag d'authentification (base64)
+    pub auth_tag: String,
+
+    /// Nonce du tag de l'en-tête seul (base64, format 1.4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_nonce: Option<String>,
+
+    /// Tag de l'en-tête seul (base64, format 1.4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_tag: Option<String>,
+}
+
+/// Enregistrement chiffré avec la clé de données, hors du fichier principal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SealedRecord {
+    /// Algorithme de chiffrement utilisé
+    pub algorithm: String,
+
+    /// Nonce de l'algorithme (base64)
     pub nonce: String,
 
     /// Données chiffrées (base64)
     pub ciphertext: String,
 
-    /// Tag d'authentification GCM (base64)
+    /// Tag d'authentification (base64)
     pub auth_tag: String,
 }
 
+/// En-tête authentifié comme données associées (format 1.3 et suivants)
+///
+/// L'ordre des champs est fixe : la sérialisation JSON est canonique.
+#[derive(Serialize)]
+struct Header<'a> {
+    version: &'a str,
+    algorithm: &'a str,
+    kdf: &'a str,
+    kdf_params: Option<&'a KdfParams>,
+    salt: Option<&'a str>,
+    /// Seulement pour le tag de l'en-tête : les données chiffrées n'en
+    /// dépendent pas, pour que les emplacements puissent changer sans les
+    /// re-chiffrer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_slots: Option<Vec<SlotHeader<'a>>>,
+}
+
+/// Paramètres d'un emplacement de clé authentifiés par le tag de l'en-tête
+/// (format 1.4)
+///
+/// Le matériel chiffré (`nonce`, `wrapped_key`) est déjà protégé par son
+/// propre tag ; le libellé et la date sont purement informatifs.
+#[derive(Serialize)]
+struct SlotHeader<'a> {
+    id: &'a str,
+    kind: KeySlotKind,
+    kdf_params: &'a KdfParams,
+    salt: &'a str,
+    keyfile: bool,
+}
+
+impl EncryptedData {
+    /// Indique si la clé de données est protégée par des emplacements (format 1.2+)
+    pub fn uses_key_slots(&self) -> bool {
+        parse_format_version(&self.version) >= parse_format_version(KEY_SLOTS_FORMAT_VERSION)
+    }
+
+    /// Indique si l'en-tête est authentifié avec les données (format 1.3+)
+    fn authenticates_header(&self) -> bool {
+        parse_format_version(&self.version) >= parse_format_version(HEADER_FORMAT_VERSION)
+    }
+
+    /// Sérialisation canonique de l'en-tête, pour la version `version`
+    /// (données associées des données chiffrées)
+    fn header_aad(&self, version: &str) -> CryptoResult<Vec<u8>> {
+        self.canonical_header(version, false)
+    }
+
+    /// Sérialisation canonique de l'en-tête et des emplacements de clé
+    /// (données associées du tag de l'en-tête)
+    fn full_header_aad(&self) -> CryptoResult<Vec<u8>> {
+        self.canonical_header(&self.version, true)
+    }
+
+    fn canonical_header(&self, version: &str, with_slots: bool) -> CryptoResult<Vec<u8>> {
+        let header = Header {
+            version,
+            algorithm: &self.algorithm,
+            kdf: &self.kdf,
+            kdf_params: self.kdf_params.as_ref(),
+            salt: self.salt.as_deref(),
+            key_slots: with_slots.then(|| {
+                self.key_slots
+                    .iter()
+                    .map(|slot| SlotHeader {
+                        id: &slot.id,
+                        kind: slot.kind,
+                        kdf_params: &slot.kdf_params,
+                        salt: &slot.salt,
+                        keyfile: slot.keyfile,
+                    })
+                    .collect()
+            }),
+        };
+        Ok(serde_json::to_vec(&header)?)
+    }
+
+    /// Indique si l'en-tête a son propre tag (format 1.4+)
+    fn has_header_tag(&self) -> bool {
+        parse_format_version(&self.version) >= parse_format_version(FORMAT_VERSION)
+    }
+
+    /// Calcule le tag de l'en-tête (format 1.4+), après une modification de
+    /// l'en-tête ou des emplacements
+    fn seal_header(&mut self, key: &SecretKey, suite: CipherSuite) -> CryptoResult<()> {
+        if !self.has_header_tag() {
+            return Ok(());
+        }
+        let (nonce, _, tag) = suite.seal(key, &[], &self.full_header_aad()?)?;
+        self.header_nonce = Some(general_purpose::STANDARD.encode(nonce));
+        self.header_tag = Some(general_purpose::STANDARD.encode(tag));
+        Ok(())
+    }
+
+    /// Remplace les emplacements de clé par ceux de la session, sans
+    /// re-chiffrer les données
+    ///
+    /// # Errors
+    /// Comme `decrypt_with_key` si le fichier n'est pas chiffré avec la clé
+    /// de la session ou a été modifié
+    pub fn replace_key_slots(&mut self, session_key: &SessionKey) -> CryptoResult<()> {
+        CryptoEngine::decrypt_with_key(self, session_key)?;
+        let suite = CipherSuite::from_name(&self.algorithm)?;
+        self.key_slots = session_key.key_slots.clone();
+        self.seal_header(&session_key.key, suite)
+    }
+
+    /// Vérifie le tag de l'en-tête seul (format 1.4)
+    ///
+    /// # Returns
+    /// `false` si le tag est absent ou ne correspond pas à l'en-tête
+    fn header_tag_matches(&self, key: &SecretKey, suite: CipherSuite) -> bool {
+        let (Some(nonce), Some(tag)) = (&self.header_nonce, &self.header_tag) else {
+            return false;
+        };
+        let (Ok(nonce), Ok(tag)) = (decode(nonce, "header_nonce"), decode(tag, "header_tag"))
+        else {
+            return false;
+        };
+        self.full_header_aad()
+            .ok()
+            .and_then(|aad| suite.open(key, &nonce, &[], &tag, &aad))
+            .is_some()
+    }
+
+    /// Indique si au moins un emplacement mot de passe exige un fichier clé
+    pub fn requires_keyfile(&self) -> bool {
+        self.key_slots.iter().any(|slot| slot.keyfile)
+    }
+
+    /// Vérifie que l'enveloppe est complète et lisible par cette application,
+    /// sans la déchiffrer (ex: avant qu'un backup ne remplace le coffre)
+    pub fn check_well_formed(&self) -> CryptoResult<()> {
+        CryptoEngine::check_format_version(&self.version)?;
+        CipherSuite::from_name(&self.algorithm)?;
+        for (value, field) in [
+            (&self.nonce, "nonce"),
+            (&self.ciphertext, "ciphertext"),
+            (&self.auth_tag, "auth_tag"),
+        ] {
+            if decode(value, field)?.is_empty() {
+                return Err(AppError::CorruptVault(format!("{} vide", field)));
+            }
+        }
+        if self.uses_key_slots() && self.key_slots.is_empty() {
+            return Err(AppError::CorruptVault(
+                "aucun emplacement de clé".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Paramètres de dérivation de clé Argon2
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct KdfParams {
     pub memory: u32, // en KB
     pub iterations: u32,
@@ -81,19 +994,58 @@ pub struct KdfParams {
 
 impl Default for KdfParams {
     fn default() -> Self {
+        Self::recommended()
+    }
+}
+
+impl KdfParams {
+    /// Paramètres recommandés pour les nouveaux chiffrements
+    pub fn recommended() -> Self {
         Self {
             memory: 65536, // 64 MB
             iterations: 3,
             parallelism: 4,
         }
     }
+
+    /// Paramètres réellement utilisés par les fichiers au format 1.0
+    /// (valeurs par défaut de la crate argon2 : 19 MB, 2 itérations, 1 thread)
+    pub fn legacy() -> Self {
+        Self {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+
+    /// Indique si ces paramètres sont plus faibles que `other` sur au moins un critère
+    pub fn is_weaker_than(&self, other: &KdfParams) -> bool {
+        self.memory < other.memory
+            || self.iterations < other.iterations
+            || self.parallelism < other.parallelism
+    }
+
+    /// Construit une instance Argon2id à partir des paramètres,
+    /// avec un secret optionnel (empreinte du fichier clé)
+    fn to_argon2<'k>(&self, secret: Option<&'k [u8]>) -> CryptoResult<Argon2<'k>> {
+        let params = Params::new(self.memory, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| AppError::InvalidInput(format!("Paramètres KDF invalides: {}", e)))?;
+
+        match secret {
+            Some(secret) => {
+                Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params)
+                    .map_err(|e| AppError::Crypto(format!("secret Argon2: {}", e)))
+            }
+            None => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        }
+    }
 }
 
 /// Moteur de chiffrement principal
 pub struct CryptoEngine;
 
 impl CryptoEngine {
-    /// Chiffre des données avec AES-256-GCM
+    /// Chiffre des données avec AES-256-GCM et les paramètres KDF recommandés
     ///
     /// # Arguments
     /// * `plaintext` - Données en clair (JSON string)
@@ -102,91 +1054,252 @@ impl CryptoEngine {
     /// # Returns
     /// Structure EncryptedData contenant toutes les métadonnées
     pub fn encrypt(plaintext: &str, password: &str) -> CryptoResult<EncryptedData> {
-        // 1. Générer un salt aléatoire pour Argon2
-        let mut salt = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut salt);
+        Self::encrypt_with_params(plaintext, password, &KdfParams::recommended())
+    }
 
-        // 2. Dériver la clé depuis le mot de passe
-        let secret_key = SecretKey::from_password(password, &salt)?;
+    /// Chiffre des données avec une nouvelle clé de données protégée par
+    /// un mot de passe, avec des paramètres KDF choisis
+    ///
+    /// # Arguments
+    /// * `plaintext` - Données en clair (JSON string)
+    /// * `password` - Mot de passe maître
+    /// * `params` - Paramètres Argon2id, enregistrés dans l'emplacement de clé
+    pub fn encrypt_with_params(
+        plaintext: &str,
+        password: &str,
+        params: &KdfParams,
+    ) -> CryptoResult<EncryptedData> {
+        let session_key = SessionKey::generate(MASTER_SLOT_LABEL, password, None, params)?;
+        Self::encrypt_with_key(plaintext, &session_key, CipherSuite::default())
+    }
 
-        // 3. Créer le cipher AES-256-GCM
-        let cipher = Aes256Gcm::new_from_slice(secret_key.as_bytes())
-            .map_err(|e| format!("Erreur création cipher: {}", e))?;
+    /// Chiffre des données avec la clé de données d'une session
+    ///
+    /// Les emplacements de clé de la session sont enregistrés dans le résultat.
+    ///
+    /// # Arguments
+    /// * `plaintext` - Données à chiffrer (JSON string)
+    /// * `session_key` - Clé obtenue au déverrouillage
+    /// * `suite` - Algorithme de chiffrement des données
+    pub fn encrypt_with_key(
+        plaintext: &str,
+        session_key: &SessionKey,
+        suite: CipherSuite,
+    ) -> CryptoResult<EncryptedData> {
+        if session_key.key_slots.is_empty() {
+            return Err(AppError::Crypto(
+                "aucun emplacement de clé, impossible de chiffrer".to_string(),
+            ));
+        }
 
-        // 4. Générer un nonce aléatoire (96 bits pour GCM)
-        let mut nonce_bytes = [0u8; 12];
-        rand::thread_rng().fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+        let mut encrypted = EncryptedData {
+            version: FORMAT_VERSION.to_string(),
+            algorithm: suite.name().to_string(),
+            kdf: "Argon2id".to_string(),
+            kdf_params: None,
+            salt: None,
+            key_slots: session_key.key_slots.clone(),
+            nonce: String::new(),
+            ciphertext: String::new(),
+            auth_tag: String::new(),
+            header_nonce: None,
+            header_tag: None,
+        };
 
-        // 5. Chiffrer les données
-        let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
-            .map_err(|e| format!("Erreur chiffrement: {}", e))?;
+        // L'en-tête est lié au tag d'authentification : toute modification sera détectée
+        let aad = encrypted.header_aad(FORMAT_VERSION)?;
+        let (nonce, ciphertext, auth_tag) =
+            suite.seal(&session_key.key, plaintext.as_bytes(), &aad)?;
 
-        // 6. Extraire le tag d'authentification (les 16 derniers octets)
-        let auth_tag = &ciphertext[ciphertext.len() - 16..];
-        let actual_ciphertext = &ciphertext[..ciphertext.len() - 16];
+        // Encoder en base64 pour le stockage
+        encrypted.nonce = general_purpose::STANDARD.encode(nonce);
+        encrypted.ciphertext = general_purpose::STANDARD.encode(ciphertext);
+        encrypted.auth_tag = general_purpose::STANDARD.encode(auth_tag);
 
-        // 7. Encoder en base64 pour le stockage
-        Ok(EncryptedData {
-            version: "1.0".to_string(),
-            algorithm: "AES-256-GCM".to_string(),
-            kdf: "Argon2id".to_string(),
-            kdf_params: KdfParams::default(),
-            salt: general_purpose::STANDARD.encode(&salt),
-            nonce: general_purpose::STANDARD.encode(&nonce_bytes),
-            ciphertext: general_purpose::STANDARD.encode(actual_ciphertext),
-            auth_tag: general_purpose::STANDARD.encode(auth_tag),
-        })
+        // Tag de l'en-tête seul, emplacements compris
+        encrypted.seal_header(&session_key.key, suite)?;
+        Ok(encrypted)
     }
 
-    /// Déchiffre des données AES-256-GCM
+    /// Déchiffre des données (l'algorithme est lu dans `encrypted.algorithm`)
     ///
     /// # Arguments
     /// * `encrypted` - Structure EncryptedData
-    /// * `password` - Mot de passe maître
+    /// * `password` - Mot de passe maître ou clé de secours
     ///
     /// # Returns
-    /// Données déchiffrées (JSON string)
-    pub fn decrypt(encrypted: &EncryptedData, password: &str) -> CryptoResult<String> {
-        // 1. Vérifier la version
-        if encrypted.version != "1.0" {
-            return Err("Version de format non supportée".into());
+    /// Données déchiffrées (JSON string), effacées de la mémoire à la libération
+    pub fn decrypt(encrypted: &EncryptedData, password: &str) -> CryptoResult<Zeroizing<String>> {
+        let session_key = SessionKey::open(encrypted, password, None)?;
+        Self::decrypt_with_key(encrypted, &session_key)
+    }
+
+    /// Déchiffre des données avec la clé de données d'une session
+    ///
+    /// Échoue si le fichier a été chiffré avec une autre clé de données, si
+    /// son en-tête a été modifié (format 1.3+, emplacements compris en 1.4)
+    /// ou si les données chiffrées ont été modifiées.
+    pub fn decrypt_with_key(
+        encrypted: &EncryptedData,
+        session_key: &SessionKey,
+    ) -> CryptoResult<Zeroizing<String>> {
+        // 1. Décoder les données base64
+        let nonce = decode(&encrypted.nonce, "nonce")?;
+        let ciphertext = decode(&encrypted.ciphertext, "ciphertext")?;
+        let auth_tag = decode(&encrypted.auth_tag, "auth_tag")?;
+
+        // 2. Déchiffrer avec l'algorithme enregistré (le tag vérifie aussi
+        //    que la clé et l'en-tête sont les bons)
+        let suite = CipherSuite::from_name(&encrypted.algorithm)?;
+        let aad = if encrypted.authenticates_header() {
+            encrypted.header_aad(&encrypted.version)?
+        } else {
+            Vec::new()
+        };
+        let Some(mut plaintext_bytes) =
+            suite.open(&session_key.key, &nonce, &ciphertext, &auth_tag, &aad)
+        else {
+            return Err(Self::decryption_error(
+                encrypted,
+                session_key,
+                suite,
+                &nonce,
+                &ciphertext,
+                &auth_tag,
+            ));
+        };
+
+        // 3. Emplacements de clé et en-tête : vérifiés par leur propre tag
+        if encrypted.has_header_tag() && !encrypted.header_tag_matches(&session_key.key, suite) {
+            return Err(AppError::HeaderTampered);
         }
 
-        // 2. Décoder les données base64
-        let salt = general_purpose::STANDARD
-            .decode(&encrypted.salt)
-            .map_err(|e| format!("Erreur décodage salt: {}", e))?;
-        let nonce_bytes = general_purpose::STANDARD
-            .decode(&encrypted.nonce)
-            .map_err(|e| format!("Erreur décodage nonce: {}", e))?;
-        let ciphertext = general_purpose::STANDARD
-            .decode(&encrypted.ciphertext)
-            .map_err(|e| format!("Erreur décodage ciphertext: {}", e))?;
-        let auth_tag = general_purpose::STANDARD
-            .decode(&encrypted.auth_tag)
-            .map_err(|e| format!("Erreur décodage auth_tag: {}", e))?;
+        // 4. Convertir en String UTF-8 (le tampon est déplacé, pas copié)
+        match String::from_utf8(mem::take(&mut *plaintext_bytes)) {
+            Ok(plaintext) => Ok(Zeroizing::new(plaintext)),
+            Err(e) => {
+                let message = format!("UTF-8: {}", e.utf8_error());
+                e.into_bytes().zeroize();
+                Err(AppError::CorruptVault(message))
+            }
+        }
+    }
+
+    /// Explique l'échec du déchiffrement avec une clé de session
+    ///
+    /// Une clé ouverte par un emplacement de ce fichier a déjà été
+    /// authentifiée : si elle ne déchiffre pas un fichier 1.4, le tag de
+    /// l'en-tête seul indique si c'est l'en-tête ou les données qui ont été
+    /// modifiés. Un fichier 1.3 n'a pas ce tag : l'échec y est attribué à
+    /// l'en-tête. Une version
+    /// abaissée est détectée en recalculant l'en-tête dans les versions
+    /// authentifiées plus récentes.
+    ///
+    /// Les paramètres de l'emplacement qui a servi à ouvrir la session ne
+    /// peuvent pas être vérifiés sans la clé : les modifier donne
+    /// `WrongPassword`, comme un mauvais mot de passe.
+    fn decryption_error(
+        encrypted: &EncryptedData,
+        session_key: &SessionKey,
+        suite: CipherSuite,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        auth_tag: &[u8],
+    ) -> AppError {
+        let key_is_authentic = session_key
+            .unlocked_slot()
+            .is_some_and(|slot| encrypted.key_slots.iter().any(|s| s.id == slot.id));
+        let declared = parse_format_version(&encrypted.version);
+        let downgraded = [HEADER_FORMAT_VERSION, FORMAT_VERSION]
+            .into_iter()
+            .filter(|version| parse_format_version(version) > declared)
+            .filter_map(|version| encrypted.header_aad(version).ok())
+            .any(|aad| {
+                suite
+                    .open(&session_key.key, nonce, ciphertext, auth_tag, &aad)
+                    .is_some()
+            });
 
-        // 3. Dériver la clé depuis le mot de passe
-        let secret_key = SecretKey::from_password(password, &salt)?;
+        if downgraded {
+            return AppError::HeaderTampered;
+        }
+        if !key_is_authentic || !encrypted.authenticates_header() {
+            return AppError::WrongPassword;
+        }
+        if encrypted.has_header_tag() && encrypted.header_tag_matches(&session_key.key, suite) {
+            AppError::DataTampered
+        } else {
+            AppError::HeaderTampered
+        }
+    }
 
-        // 4. Créer le cipher
-        let cipher = Aes256Gcm::new_from_slice(secret_key.as_bytes())
-            .map_err(|e| format!("Erreur création cipher: {}", e))?;
+    /// Paramètres KDF réellement utilisés pour dériver la clé d'un fichier
+    /// au format 1.0 ou 1.1
+    ///
+    /// Les fichiers au format 1.0 annoncent des `kdf_params` qui n'ont jamais
+    /// été appliqués : leur clé a été dérivée avec les paramètres par défaut.
+    pub fn effective_kdf_params(encrypted: &EncryptedData) -> CryptoResult<KdfParams> {
+        Self::check_format_version(&encrypted.version)?;
 
-        // 5. Recombiner ciphertext + auth_tag
-        let mut combined = ciphertext;
-        combined.extend_from_slice(&auth_tag);
+        match encrypted.version.as_str() {
+            LEGACY_FORMAT_VERSION => Ok(KdfParams::legacy()),
+            PASSWORD_FORMAT_VERSION => encrypted
+                .kdf_params
+                .clone()
+                .ok_or_else(|| AppError::CorruptVault("paramètres KDF absents".to_string())),
+            _ => Err(AppError::Crypto(
+                "ce format n'utilise pas de paramètres KDF globaux".to_string(),
+            )),
+        }
+    }
 
-        // 6. Déchiffrer
-        let nonce = Nonce::from_slice(&nonce_bytes);
-        let plaintext_bytes = cipher
-            .decrypt(nonce, combined.as_ref())
-            .map_err(|_| "Mot de passe incorrect ou données corrompues")?;
+    /// Vérifie qu'une version de format peut être lue par cette application
+    ///
+    /// Un fichier écrit par une version plus récente est refusé explicitement
+    /// plutôt que d'être mal interprété.
+    pub fn check_format_version(version: &str) -> CryptoResult<()> {
+        let current = parse_format_version(FORMAT_VERSION).expect("FORMAT_VERSION invalide");
+        let legacy = parse_format_version(LEGACY_FORMAT_VERSION).expect("version historique");
 
-        // 7. Convertir en String UTF-8
-        String::from_utf8(plaintext_bytes).map_err(|e| format!("Erreur UTF-8: {}", e).into())
+        match parse_format_version(version) {
+            Some(found) if found > current => Err(AppError::UnsupportedVersion(format!(
+                "ce coffre utilise le format {} (plus récent que {}). Mettez à jour Cockpit CFDT.",
+                version, FORMAT_VERSION
+            ))),
+            Some(found) if found >= legacy => Ok(()),
+            _ => Err(AppError::UnsupportedVersion(format!("format {}", version))),
+        }
+    }
+}
+
+/// Chiffre au format 1.0/1.1 (clé dérivée directement du mot de passe),
+/// pour tester la lecture des anciens coffres
+#[cfg(test)]
+pub(crate) fn encrypt_password_format(
+    plaintext: &str,
+    password: &str,
+    params: &KdfParams,
+    version: &str,
+) -> EncryptedData {
+    let mut salt = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = SecretKey::from_password(password, &salt, params, None).unwrap();
+    let (nonce, ciphertext, auth_tag) = CipherSuite::Aes256Gcm
+        .seal(&key, plaintext.as_bytes(), &[])
+        .unwrap();
+
+    EncryptedData {
+        version: version.to_string(),
+        algorithm: "AES-256-GCM".to_string(),
+        kdf: "Argon2id".to_string(),
+        kdf_params: Some(params.clone()),
+        salt: Some(general_purpose::STANDARD.encode(salt)),
+        key_slots: Vec::new(),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        auth_tag: general_purpose::STANDARD.encode(auth_tag),
+        header_nonce: None,
+        header_tag: None,
     }
 }
 
@@ -203,13 +1316,13 @@ mod tests {
         let encrypted = CryptoEngine::encrypt(data, password).unwrap();
 
         // Vérifier les métadonnées
-        assert_eq!(encrypted.version, "1.0");
+        assert_eq!(encrypted.version, FORMAT_VERSION);
         assert_eq!(encrypted.algorithm, "AES-256-GCM");
         assert_eq!(encrypted.kdf, "Argon2id");
 
         // Déchiffrer
         let decrypted = CryptoEngine::decrypt(&encrypted, password).unwrap();
-        assert_eq!(decrypted, data);
+        assert_eq!(*decrypted, data);
     }
 
     #[test]
@@ -225,6 +1338,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_password_is_redacted() {
+        let password: Password = serde_json::from_str("\"mot_de_passe\"").unwrap();
+        assert_eq!(password.expose(), "mot_de_passe");
+        assert_eq!(format!("{:?}", password), "Password(***)");
+
+        // La clé de session reste utilisable depuis sa page protégée
+        let session_key =
+            SessionKey::generate("test", password.expose(), None, &fast_params()).unwrap();
+        let encrypted =
+            CryptoEngine::encrypt_with_key("data", &session_key, CipherSuite::default()).unwrap();
+        assert_eq!(
+            *CryptoEngine::decrypt_with_key(&encrypted, &session_key).unwrap(),
+            "data"
+        );
+    }
+
     #[test]
     fn test_tampered_data() {
         let password = "password";
@@ -237,7 +1367,367 @@ mod tests {
 
         let result = CryptoEngine::decrypt(&encrypted, password);
 
-        // Doit échouer grâce au tag d'authentification GCM
-        assert!(result.is_err());
+        // Doit échouer grâce au tag d'authentification GCM ; l'en-tête est intact
+        assert!(matches!(result, Err(AppError::DataTampered)));
+    }
+
+    /// Paramètres légers pour garder les tests rapides
+    fn fast_params() -> KdfParams {
+        KdfParams {
+            memory: 8192,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn test_custom_kdf_params_are_stored_and_used() {
+        let password = "password";
+        let params = fast_params();
+
+        let mut encrypted = CryptoEngine::encrypt_with_params("data", password, &params).unwrap();
+        assert_eq!(encrypted.key_slots[0].kdf_params, params);
+        assert_eq!(
+            *CryptoEngine::decrypt(&encrypted, password).unwrap(),
+            "data"
+        );
+
+        // Les paramètres stockés doivent réellement servir à la dérivation
+        encrypted.key_slots[0].kdf_params.iterations = 2;
+        assert!(CryptoEngine::decrypt(&encrypted, password).is_err());
+    }
+
+    #[test]
+    fn test_legacy_format_uses_default_argon2() {
+        let password = "password";
+
+        // Un fichier 1.0 annonce les paramètres recommandés mais a été
+        // chiffré avec Argon2::default()
+        let mut encrypted = encrypt_password_format(
+            "legacy",
+            password,
+            &KdfParams::legacy(),
+            LEGACY_FORMAT_VERSION,
+        );
+        encrypted.kdf_params = Some(KdfParams::recommended());
+
+        assert_eq!(
+            *CryptoEngine::decrypt(&encrypted, password).unwrap(),
+            "legacy"
+        );
+        assert_eq!(
+            CryptoEngine::effective_kdf_params(&encrypted).unwrap(),
+            KdfParams::legacy()
+        );
+    }
+
+    #[test]
+    fn test_password_format_is_readable_but_not_writable() {
+        let password = "password";
+        let encrypted =
+            encrypt_password_format("v1.1", password, &fast_params(), PASSWORD_FORMAT_VERSION);
+
+        let session_key = SessionKey::open(&encrypted, password, None).unwrap();
+        assert_eq!(
+            *CryptoEngine::decrypt_with_key(&encrypted, &session_key).unwrap(),
+            "v1.1"
+        );
+
+        // Sans emplacement de clé, la session ne peut pas écrire de coffre
+        assert!(session_key.key_slots().is_empty());
+        assert!(
+            CryptoEngine::encrypt_with_key("data", &session_key, CipherSuite::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_invalid_kdf_params_rejected() {
+        let params = KdfParams {
+            memory: 1,
+            iterations: 0,
+            parallelism: 0,
+        };
+        assert!(CryptoEngine::encrypt_with_params("data", "pw", &params).is_err());
+    }
+
+    #[test]
+    fn test_newer_format_version_refused() {
+        let mut encrypted =
+            CryptoEngine::encrypt_with_params("data", "pw", &fast_params()).unwrap();
+        encrypted.version = "2.0".to_string();
+
+        let err = CryptoEngine::decrypt(&encrypted, "pw").unwrap_err();
+        assert!(matches!(err, AppError::UnsupportedVersion(_)));
+        assert!(err.to_string().contains("plus récent"));
+
+        assert!(CryptoEngine::check_format_version("abc").is_err());
+        assert!(CryptoEngine::check_format_version("0.9").is_err());
+        assert!(CryptoEngine::check_format_version(LEGACY_FORMAT_VERSION).is_ok());
+    }
+
+    #[test]
+    fn test_session_key_reuse() {
+        let password = "password";
+        let session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, password, None, &fast_params()).unwrap();
+
+        // Plusieurs chiffrements avec la même clé, sans re-dérivation
+        let first =
+            CryptoEngine::encrypt_with_key("first", &session_key, CipherSuite::default()).unwrap();
+        let second =
+            CryptoEngine::encrypt_with_key("second", &session_key, CipherSuite::default()).unwrap();
+        assert_eq!(first.key_slots[0].salt, second.key_slots[0].salt);
+        assert_ne!(first.nonce, second.nonce);
+
+        // Lisibles avec la clé ou avec le mot de passe
+        assert_eq!(
+            *CryptoEngine::decrypt_with_key(&second, &session_key).unwrap(),
+            "second"
+        );
+        assert_eq!(*CryptoEngine::decrypt(&first, password).unwrap(), "first");
+
+        // Un coffre chiffré avec une autre clé de données est refusé
+        let other = CryptoEngine::encrypt_with_params("other", password, &fast_params()).unwrap();
+        assert!(CryptoEngine::decrypt_with_key(&other, &session_key).is_err());
+    }
+
+    #[test]
+    fn test_key_slots_open_same_data_key() {
+        let params = fast_params();
+        let mut session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, "maitre", None, &params).unwrap();
+
+        // Ajouter un mot de passe collègue et une clé de secours
+        session_key
+            .add_slot(KeySlotKind::Password, "Collègue", "collegue", None, &params)
+            .unwrap();
+        let recovery_key = generate_recovery_key();
+        let recovery = session_key
+            .add_slot(
+                KeySlotKind::Recovery,
+                "Clé de secours",
+                &recovery_key,
+                None,
+                &params,
+            )
+            .unwrap();
+        assert_eq!(
+            recovery_key.len(),
+            RECOVERY_KEY_LEN + RECOVERY_KEY_LEN / 4 - 1
+        );
+
+        let encrypted =
+            CryptoEngine::encrypt_with_key("inventaire", &session_key, CipherSuite::default())
+                .unwrap();
+        assert_eq!(encrypted.key_slots.len(), 3);
+
+        // Chaque secret ouvre le coffre, la clé de secours tolère la casse et les espaces
+        assert_eq!(
+            *CryptoEngine::decrypt(&encrypted, "maitre").unwrap(),
+            "inventaire"
+        );
+        assert_eq!(
+            *CryptoEngine::decrypt(&encrypted, "collegue").unwrap(),
+            "inventaire"
+        );
+        let typed = recovery_key.to_lowercase().replace('-', " ");
+        let opened = SessionKey::open(&encrypted, &typed, None).unwrap();
+        assert_eq!(opened.unlocked_slot().unwrap().id, recovery.id);
+
+        // Révoquer le mot de passe collègue
+        let colleague = encrypted.key_slots[1].id.clone();
+        session_key.revoke_slot(&colleague).unwrap();
+        let encrypted =
+            CryptoEngine::encrypt_with_key("inventaire", &session_key, CipherSuite::default())
+                .unwrap();
+        assert!(CryptoEngine::decrypt(&encrypted, "collegue").is_err());
+        assert!(CryptoEngine::decrypt(&encrypted, "maitre").is_ok());
+    }
+
+    #[test]
+    fn test_rewrap_slot_changes_only_one_slot() {
+        let params = fast_params();
+        let mut session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, "ancien", None, &params).unwrap();
+        session_key
+            .add_slot(KeySlotKind::Password, "Collègue", "collegue", None, &params)
+            .unwrap();
+        let before = session_key.key_slots().to_vec();
+
+        assert!(session_key
+            .rewrap_slot("inconnu", "nouveau", None, &params)
+            .is_err());
+        session_key
+            .rewrap_slot("ancien", "nouveau", None, &params)
+            .unwrap();
+
+        let after = session_key.key_slots();
+        assert_eq!(after[0].id, before[0].id);
+        assert_eq!(after[0].label, MASTER_SLOT_LABEL);
+        assert_ne!(after[0].wrapped_key, before[0].wrapped_key);
+        assert_eq!(after[1].wrapped_key, before[1].wrapped_key);
+
+        let encrypted =
+            CryptoEngine::encrypt_with_key("data", &session_key, CipherSuite::default()).unwrap();
+        assert!(CryptoEngine::decrypt(&encrypted, "ancien").is_err());
+        assert!(CryptoEngine::decrypt(&encrypted, "nouveau").is_ok());
+        assert!(CryptoEngine::decrypt(&encrypted, "collegue").is_ok());
+    }
+
+    #[test]
+    fn test_last_key_slot_cannot_be_revoked() {
+        let mut session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, "pw", None, &fast_params()).unwrap();
+        let id = session_key.key_slots()[0].id.clone();
+
+        assert!(session_key.revoke_slot(&id).is_err());
+        assert!(session_key.revoke_slot("inconnu").is_err());
+    }
+
+    #[test]
+    fn test_keyfile_second_factor() {
+        let params = fast_params();
+        let keyfile = Keyfile::from_bytes(b"contenu du fichier cle");
+        let mut session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, "maitre", Some(&keyfile), &params).unwrap();
+        let recovery_key = generate_recovery_key();
+        session_key
+            .add_slot(
+                KeySlotKind::Recovery,
+                "Clé de secours",
+                &recovery_key,
+                Some(&keyfile),
+                &params,
+            )
+            .unwrap();
+        let encrypted =
+            CryptoEngine::encrypt_with_key("secret", &session_key, CipherSuite::default()).unwrap();
+
+        // Le mot de passe seul ou un mauvais fichier clé ne suffisent pas
+        assert!(encrypted.requires_keyfile());
+        let err = SessionKey::open(&encrypted, "maitre", None).err().unwrap();
+        assert!(matches!(err, AppError::KeyfileRequired));
+        let wrong = Keyfile::from_bytes(b"autre fichier");
+        assert!(SessionKey::open(&encrypted, "maitre", Some(&wrong)).is_err());
+        assert!(SessionKey::open(&encrypted, "maitre", Some(&keyfile)).is_ok());
+
+        // La clé de secours reste utilisable sans le fichier clé
+        assert!(SessionKey::open(&encrypted, &recovery_key, None).is_ok());
+
+        // Seule l'exigence est enregistrée, pas l'empreinte du fichier
+        let json = serde_json::to_string(&encrypted).unwrap();
+        let digest = general_purpose::STANDARD.encode(*keyfile.digest);
+        assert!(json.contains("\"keyfile\":true"));
+        assert!(!json.contains(&digest));
+    }
+
+    #[test]
+    fn test_header_is_authenticated() {
+        let password = "mot_de_passe_test";
+        let session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, password, None, &fast_params()).unwrap();
+        let encrypted =
+            CryptoEngine::encrypt_with_key("données", &session_key, CipherSuite::default())
+                .unwrap();
+        assert_eq!(encrypted.version, FORMAT_VERSION);
+
+        let header_error =
+            |tampered: &EncryptedData| CryptoEngine::decrypt(tampered, password).unwrap_err();
+
+        // Chaque champ de l'en-tête est lié au tag GCM
+        let mut tampered = encrypted.clone();
+        tampered.algorithm = CipherSuite::XChaCha20Poly1305.name().to_string();
+        assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+
+        let mut tampered = encrypted.clone();
+        tampered.kdf = "scrypt".to_string();
+        assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+
+        let mut tampered = encrypted.clone();
+        tampered.kdf_params = Some(KdfParams::legacy());
+        assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+
+        let mut tampered = encrypted.clone();
+        tampered.salt = Some("c2Vs".to_string());
+        assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+
+        // Les paramètres des autres emplacements sont aussi authentifiés
+        let mut session_key = session_key;
+        let recovery_key = generate_recovery_key();
+        session_key
+            .add_slot(
+                KeySlotKind::Recovery,
+                "Clé de secours",
+                &recovery_key,
+                None,
+                &fast_params(),
+            )
+            .unwrap();
+        let encrypted =
+            CryptoEngine::encrypt_with_key("données", &session_key, CipherSuite::default())
+                .unwrap();
+        let tamper_slot: [fn(&mut KeySlot); 5] = [
+            |slot| slot.id = "autre".to_string(),
+            |slot| slot.kind = KeySlotKind::Password,
+            |slot| slot.kdf_params.iterations += 1,
+            |slot| slot.salt = "c2Vs".to_string(),
+            |slot| slot.keyfile = true,
+        ];
+        for tamper in tamper_slot {
+            let mut tampered = encrypted.clone();
+            tamper(&mut tampered.key_slots[1]);
+            assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+        }
+
+        // Tag de l'en-tête retiré : l'en-tête ne peut plus être vérifié
+        let mut tampered = encrypted.clone();
+        tampered.ciphertext = general_purpose::STANDARD.encode(b"abime");
+        assert!(matches!(header_error(&tampered), AppError::DataTampered));
+        tampered.header_tag = None;
+        assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+
+        // Abaisser la version ne permet pas de contourner la vérification
+        for version in [
+            HEADER_FORMAT_VERSION,
+            KEY_SLOTS_FORMAT_VERSION,
+            LEGACY_FORMAT_VERSION,
+        ] {
+            let mut tampered = encrypted.clone();
+            tampered.version = version.to_string();
+            assert!(matches!(header_error(&tampered), AppError::HeaderTampered));
+        }
+
+        // Un mauvais mot de passe garde son message habituel
+        let err = CryptoEngine::decrypt(&encrypted, "autre").unwrap_err();
+        assert!(matches!(err, AppError::WrongPassword));
+    }
+
+    #[test]
+    fn test_cipher_suites() {
+        let password = "mot_de_passe_test";
+        let session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, password, None, &fast_params()).unwrap();
+
+        // Chaque suite relit ses propres données, l'algorithme étant lu dans le fichier
+        for suite in [CipherSuite::Aes256Gcm, CipherSuite::XChaCha20Poly1305] {
+            let encrypted = CryptoEngine::encrypt_with_key("données", &session_key, suite).unwrap();
+            assert_eq!(encrypted.algorithm, suite.name());
+            assert_eq!(CipherSuite::from_name(&encrypted.algorithm).unwrap(), suite);
+            assert_eq!(
+                *CryptoEngine::decrypt(&encrypted, password).unwrap(),
+                "données"
+            );
+        }
+
+        let xchacha =
+            CryptoEngine::encrypt_with_key("données", &session_key, CipherSuite::XChaCha20Poly1305)
+                .unwrap();
+        assert_eq!(decode(&xchacha.nonce, "nonce").unwrap().len(), 24);
+
+        // Un algorithme inconnu est refusé explicitement
+        let mut unknown = xchacha.clone();
+        unknown.algorithm = "ROT13".to_string();
+        let err = CryptoEngine::decrypt(&unknown, password).unwrap_err();
+        assert!(matches!(err, AppError::UnsupportedAlgorithm(_)));
     }
 }
@@ -0,0 +1,400 @@
+// src-tauri/src/diff.rs
+// Comparaison de deux versions des données (backups ou coffre actuel)
+// Permet de voir ce qu'un backup contient avant de le restaurer : sites
+// ajoutés, supprimés et modifiés, champ par champ, y compris dans les listes
+// (interventions, extensions, checklist, contacts…)
+
+use crate::error::AppResult;
+use crate::merge::sites_by_id;
+use crate::storage::{zeroize_value, AppData};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Modification d'un champ entre deux versions
+///
+/// `old` est `None` pour un élément ajouté, `new` pour un élément supprimé.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    /// Chemin du champ (ex: "tech.php_version", "extensions[JCE].version",
+    /// "interventions[2024-03-12 Mise à jour]")
+    pub path: String,
+
+    /// Valeur dans la version de départ
+    pub old: Option<Value>,
+
+    /// Valeur dans la version d'arrivée
+    pub new: Option<Value>,
+
+    /// Position d'un élément de liste ajouté (dans la version d'arrivée) ou
+    /// supprimé (dans la version de départ), pour le journal
+    #[serde(skip)]
+    pub index: Option<usize>,
+}
+
+impl Drop for FieldChange {
+    fn drop(&mut self) {
+        // Les valeurs sont des données déchiffrées
+        for value in [&mut self.old, &mut self.new].into_iter().flatten() {
+            zeroize_value(value);
+        }
+    }
+}
+
+/// Site ajouté ou supprimé
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SiteRef {
+    pub id: String,
+    pub name: String,
+}
+
+/// Site présent dans les deux versions, avec ses champs modifiés
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SiteDiff {
+    pub id: String,
+
+    /// Nom dans la version d'arrivée
+    pub name: String,
+
+    pub changes: Vec<FieldChange>,
+}
+
+/// Différences entre deux versions des données
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct VaultDiff {
+    /// Sites absents de la version de départ
+    pub added: Vec<SiteRef>,
+
+    /// Sites absents de la version d'arrivée
+    pub removed: Vec<SiteRef>,
+
+    /// Sites modifiés
+    pub modified: Vec<SiteDiff>,
+
+    /// Paramètres modifiés (chemins relatifs à `settings`)
+    pub settings: Vec<FieldChange>,
+}
+
+impl VaultDiff {
+    /// Les deux versions sont-elles identiques ?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.settings.is_empty()
+    }
+}
+
+/// Compare deux versions des données
+///
+/// Les sites sont associés par `id`. Les éléments des listes sont associés
+/// par leur champ distinctif (nom d'une extension, tâche de la checklist,
+/// date et type d'une intervention…) : une extension mise à jour apparaît
+/// comme un changement de `version`, pas comme une suppression suivie d'un ajout.
+///
+/// # Arguments
+/// * `from` - Version de départ (ex: un backup)
+/// * `to` - Version d'arrivée (ex: le coffre actuel)
+pub fn diff(from: &AppData, to: &AppData) -> AppResult<VaultDiff> {
+    let mut values = [serde_json::to_value(from)?, serde_json::to_value(to)?];
+    let result = diff_values(&values[0], &values[1]);
+
+    // Les représentations intermédiaires contiennent les données en clair
+    values.iter_mut().for_each(zeroize_value);
+    Ok(result)
+}
+
+fn diff_values(from: &Value, to: &Value) -> VaultDiff {
+    let mut result = VaultDiff::default();
+    let from_sites = sites_by_id(from);
+    let to_sites = sites_by_id(to);
+
+    for (id, site) in &from_sites {
+        match to_sites.iter().find(|(other, _)| other == id) {
+            Some((_, other)) => {
+                let mut changes = Vec::new();
+                diff_field("", Some(site), Some(other), &mut changes);
+                if !changes.is_empty() {
+                    result.modified.push(SiteDiff {
+                        id: id.to_string(),
+                        name: site_name(other),
+                        changes,
+                    });
+                }
+            }
+            None => result.removed.push(site_ref(id, site)),
+        }
+    }
+    for (id, site) in &to_sites {
+        if !from_sites.iter().any(|(other, _)| other == id) {
+            result.added.push(site_ref(id, site));
+        }
+    }
+
+    diff_field(
+        "",
+        from.get("settings"),
+        to.get("settings"),
+        &mut result.settings,
+    );
+    result
+}
+
+/// Modifications champ par champ entre deux valeurs (`None` = absente), avec
+/// les mêmes règles d'association des listes que `diff`
+pub(crate) fn field_changes(from: Option<&Value>, to: Option<&Value>) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_field("", from, to, &mut changes);
+    changes
+}
+
+fn site_name(site: &Value) -> String {
+    site.get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn site_ref(id: &str, site: &Value) -> SiteRef {
+    SiteRef {
+        id: id.to_string(),
+        name: site_name(site),
+    }
+}
+
+/// Compare un champ (`None` = absent)
+fn diff_field(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    if old == new {
+        return;
+    }
+
+    match (old, new) {
+        (Some(Value::Object(old_map)), Some(Value::Object(new_map))) => {
+            diff_objects(path, old_map, new_map, changes);
+        }
+        (Some(Value::Array(old_items)), Some(Value::Array(new_items))) => {
+            diff_lists(path, old_items, new_items, changes);
+        }
+        _ => changes.push(FieldChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+            index: None,
+        }),
+    }
+}
+
+fn diff_objects(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    let mut keys: Vec<&String> = old.keys().collect();
+    keys.extend(new.keys().filter(|key| !old.contains_key(*key)));
+
+    for key in keys {
+        let field_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        diff_field(&field_path, old.get(key), new.get(key), changes);
+    }
+}
+
+/// Compare deux listes en associant leurs éléments par leur clé
+///
+/// Les éléments de même clé sont associés dans l'ordre (le 2e avec le 2e) ;
+/// les autres sont des ajouts ou des suppressions.
+fn diff_lists(path: &str, old: &[Value], new: &[Value], changes: &mut Vec<FieldChange>) {
+    let old_keys = item_keys(path, old);
+    let new_keys = item_keys(path, new);
+    let mut matched = vec![false; new.len()];
+
+    for (old_index, (old_item, key)) in old.iter().zip(&old_keys).enumerate() {
+        let item_path = format!("{}[{}]", path, key);
+        match new_keys.iter().position(|other| other == key) {
+            Some(index) => {
+                matched[index] = true;
+                diff_field(&item_path, Some(old_item), Some(&new[index]), changes);
+            }
+            None => changes.push(FieldChange {
+                path: item_path,
+                old: Some(old_item.clone()),
+                new: None,
+                index: Some(old_index),
+            }),
+        }
+    }
+    for (new_index, (new_item, key)) in new.iter().zip(&new_keys).enumerate() {
+        if !matched[new_index] {
+            changes.push(FieldChange {
+                path: format!("{}[{}]", path, key),
+                old: None,
+                new: Some(new_item.clone()),
+                index: Some(new_index),
+            });
+        }
+    }
+}
+
+/// Clé de chaque élément d'une liste, rendue unique par un numéro d'occurrence
+pub(crate) fn item_keys(path: &str, items: &[Value]) -> Vec<String> {
+    let field = path.rsplit('.').next().unwrap_or(path);
+    let bases: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| item_key(field, item).unwrap_or_else(|| index.to_string()))
+        .collect();
+
+    bases
+        .iter()
+        .enumerate()
+        .map(
+            |(index, key)| match bases[..index].iter().filter(|b| *b == key).count() {
+                0 => key.clone(),
+                previous => format!("{} #{}", key, previous + 1),
+            },
+        )
+        .collect()
+}
+
+/// Champ distinctif d'un élément selon la liste à laquelle il appartient
+fn item_key(field: &str, item: &Value) -> Option<String> {
+    let text = |name: &str| item.get(name).and_then(Value::as_str);
+    match (field, item) {
+        ("extensions" | "contacts", _) => text("name").map(str::to_string),
+        ("checklist", _) => text("task").map(str::to_string),
+        ("joomla_accounts", _) => text("username").map(str::to_string),
+        ("interventions", _) => Some(format!("{} {}", text("date")?, text("type_intervention")?)),
+        // Liste de textes (ex: éditeurs Dashlane) : la valeur elle-même
+        (_, Value::String(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChecklistItem, Contact, Extension, Intervention, Site};
+    use serde_json::json;
+
+    fn data(sites: Vec<Site>) -> AppData {
+        let mut data = AppData::default();
+        data.sites = sites;
+        data
+    }
+
+    fn extension(name: &str, version: &str) -> Extension {
+        Extension {
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            critical: false,
+        }
+    }
+
+    fn intervention(date: &str, description: &str) -> Intervention {
+        Intervention {
+            date: date.to_string(),
+            type_intervention: "Mise à jour".to_string(),
+            description: description.to_string(),
+            duration: "1h".to_string(),
+            result: "OK".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_sites_and_nested_lists() {
+        let mut site = Site::sample("a", "Site A");
+        site.extensions = vec![extension("JCE", "2.9"), extension("Akeeba", "9.0")];
+        site.checklist = vec![ChecklistItem {
+            task: "Sauvegarde".to_string(),
+            done: false,
+            date: None,
+        }];
+        site.interventions = vec![
+            intervention("2024-03-12", "Joomla 4.4"),
+            intervention("2024-04-02", "PHP 8.2"),
+        ];
+        let from = data(vec![site.clone(), Site::sample("b", "Site B")]);
+
+        site.name = "Site A (renommé)".to_string();
+        site.extensions[0].version = Some("2.10".to_string());
+        site.extensions.remove(1);
+        site.checklist[0].done = true;
+        site.interventions.remove(0);
+        site.contacts.push(Contact {
+            name: "Marie".to_string(),
+            role: "Webmaster".to_string(),
+            email: None,
+            phone: None,
+        });
+        site.dashlane_refs
+            .editors
+            .push("Éditeur Site A".to_string());
+        let mut to = data(vec![site, Site::sample("c", "Site C")]);
+        to.settings.backup_keep_days = 60;
+
+        let result = diff(&from, &to).unwrap();
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].id, "c");
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.removed[0].name, "Site B");
+
+        assert_eq!(result.modified.len(), 1);
+        let modified = &result.modified[0];
+        assert_eq!(modified.name, "Site A (renommé)");
+
+        // Champs dans l'ordre alphabétique
+        let paths: Vec<&str> = modified.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "checklist[Sauvegarde].done",
+                "contacts[Marie]",
+                "dashlane_refs.editors[Éditeur Site A]",
+                "extensions[JCE].version",
+                "extensions[Akeeba]",
+                "interventions[2024-03-12 Mise à jour]",
+                "name",
+            ]
+        );
+
+        let version = &modified.changes[3];
+        assert_eq!(version.old, Some(json!("2.9")));
+        assert_eq!(version.new, Some(json!("2.10")));
+        // Ajout et suppression d'éléments
+        assert!(modified.changes[1].old.is_none());
+        assert!(modified.changes[4].new.is_none());
+
+        assert_eq!(result.settings.len(), 1);
+        assert_eq!(result.settings[0].path, "backup_keep_days");
+
+        assert!(diff(&to, &to).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_list_keys_are_paired_in_order() {
+        let mut site = Site::sample("a", "Site A");
+        site.interventions = vec![
+            intervention("2024-03-12", "Matin"),
+            intervention("2024-03-12", "Après-midi"),
+        ];
+        let from = data(vec![site.clone()]);
+        site.interventions[1].result = "Échec".to_string();
+        let to = data(vec![site]);
+
+        let result = diff(&from, &to).unwrap();
+        let changes = &result.modified[0].changes;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].path,
+            "interventions[2024-03-12 Mise à jour #2].result"
+        );
+    }
+}
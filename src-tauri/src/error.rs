@@ -0,0 +1,218 @@
+// src-tauri/src/error.rs
+// Erreurs typées partagées par les modules crypto, storage et config
+// Transmises au frontend sous la forme { code, message, details }
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::io;
+use thiserror::Error;
+
+/// Résultat d'une opération de l'application
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Champ refusé par la validation
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Chemin du champ (ex: "urls.frontend", "dashlane_refs.joomla_admin")
+    pub field: String,
+
+    /// Raison du refus, pour l'utilisateur
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Erreur de l'application
+///
+/// Le `code` est stable et destiné au frontend ; le message est en français
+/// et peut évoluer.
+#[derive(Debug, Error)]
+pub enum AppError {
+    /// Aucun emplacement de clé ne s'ouvre avec ce mot de passe
+    #[error("Mot de passe incorrect")]
+    WrongPassword,
+
+    /// Le mot de passe est peut-être correct mais un fichier clé est exigé
+    #[error("Mot de passe incorrect ou fichier clé requis")]
+    KeyfileRequired,
+
+    /// La clé est correcte mais l'en-tête ne correspond plus aux données
+    #[error("En-tête du coffre altéré : version, algorithme ou paramètres modifiés hors de l'application")]
+    HeaderTampered,
+
+    /// La clé et l'en-tête sont corrects mais les données chiffrées ont été
+    /// modifiées ou endommagées
+    #[error("Données du coffre altérées : le contenu chiffré a été modifié ou endommagé")]
+    DataTampered,
+
+    /// Fichier chiffré illisible ou incohérent
+    #[error("Fichier chiffré corrompu : {0}")]
+    CorruptVault(String),
+
+    /// Format ou schéma que cette version de l'application ne sait pas lire
+    #[error("Version non supportée : {0}")]
+    UnsupportedVersion(String),
+
+    /// Algorithme de chiffrement inconnu
+    #[error("Algorithme de chiffrement non supporté : {0}")]
+    UnsupportedAlgorithm(String),
+
+    /// Backup absent du dossier des backups
+    #[error("Backup introuvable : {0}")]
+    BackupNotFound(String),
+
+    /// Backup chiffré avec une autre clé de données
+    #[error("Ce backup a été chiffré avec une autre clé : mot de passe requis")]
+    BackupPasswordRequired,
+
+    /// Trop d'échecs de déverrouillage : attendre le nombre de secondes indiqué
+    #[error("Trop de tentatives : réessayez dans {0} s")]
+    Throttled(u64),
+
+    /// Coffre ouvert en écriture par une autre instance (machine et PID indiqués)
+    #[error("Coffre déjà ouvert sur {0}")]
+    VaultInUse(String),
+
+    /// Le fichier sur le disque a été remplacé par un coffre chiffré avec une
+    /// autre clé de données : impossible de fusionner
+    #[error(
+        "Le coffre a été remplacé sur le disque par un autre fichier : déverrouillez-le à nouveau"
+    )]
+    VaultReplaced,
+
+    /// Coffre ouvert en lecture seule : aucune écriture possible
+    #[error("Coffre ouvert en lecture seule")]
+    ReadOnly,
+
+    /// Commande nécessitant un coffre déverrouillé
+    #[error("Application verrouillée")]
+    Locked,
+
+    /// `initialize_storage` n'a pas encore été appelé
+    #[error("Storage non initialisé")]
+    NotInitialized,
+
+    /// Paramètre refusé (message destiné à l'utilisateur)
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// Données refusées, avec la raison pour chaque champ
+    #[error("Données invalides : {}", describe_fields(.0))]
+    Validation(Vec<FieldError>),
+
+    /// Échec interne d'une primitive cryptographique
+    #[error("Erreur de chiffrement : {0}")]
+    Crypto(String),
+
+    /// Erreur d'accès au système de fichiers
+    #[error("Erreur d'accès au fichier : {0}")]
+    Io(#[from] io::Error),
+
+    /// JSON invalide (configuration, enveloppe ou données)
+    #[error("Données JSON invalides : {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl AppError {
+    /// Code stable transmis au frontend
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::WrongPassword => "WRONG_PASSWORD",
+            AppError::KeyfileRequired => "KEYFILE_REQUIRED",
+            AppError::HeaderTampered => "HEADER_TAMPERED",
+            AppError::DataTampered => "DATA_TAMPERED",
+            AppError::CorruptVault(_) => "CORRUPT_VAULT",
+            AppError::UnsupportedVersion(_) => "UNSUPPORTED_VERSION",
+            AppError::UnsupportedAlgorithm(_) => "UNSUPPORTED_ALGORITHM",
+            AppError::BackupNotFound(_) => "BACKUP_NOT_FOUND",
+            AppError::BackupPasswordRequired => "BACKUP_PASSWORD_REQUIRED",
+            AppError::Throttled(_) => "THROTTLED",
+            AppError::VaultInUse(_) => "VAULT_IN_USE",
+            AppError::VaultReplaced => "VAULT_REPLACED",
+            AppError::ReadOnly => "READ_ONLY",
+            AppError::Locked => "LOCKED",
+            AppError::NotInitialized => "NOT_INITIALIZED",
+            AppError::InvalidInput(_) => "INVALID_INPUT",
+            AppError::Validation(_) => "VALIDATION",
+            AppError::Crypto(_) => "CRYPTO",
+            AppError::Io(_) => "IO",
+            AppError::Serialization(_) => "SERIALIZATION",
+        }
+    }
+
+    /// Champs refusés (erreur de validation uniquement)
+    pub fn fields(&self) -> Option<&[FieldError]> {
+        match self {
+            AppError::Validation(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Précisions techniques (ex: type d'erreur système), si utiles
+    pub fn details(&self) -> Option<String> {
+        match self {
+            AppError::Io(e) => Some(format!("{:?}", e.kind())),
+            AppError::Serialization(e) => {
+                Some(format!("ligne {}, colonne {}", e.line(), e.column()))
+            }
+            AppError::BackupNotFound(name) => Some(name.clone()),
+            AppError::Throttled(seconds) => Some(seconds.to_string()),
+            AppError::VaultInUse(holder) => Some(holder.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.serialize_field("fields", &self.fields())?;
+        state.end()
+    }
+}
+
+fn describe_fields(fields: &[FieldError]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{} ({})", f.field, f.message))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_serialization() {
+        let json = serde_json::to_value(AppError::WrongPassword).unwrap();
+        assert_eq!(json["code"], "WRONG_PASSWORD");
+        assert_eq!(json["message"], "Mot de passe incorrect");
+        assert!(json["details"].is_null());
+
+        let io = io::Error::new(io::ErrorKind::PermissionDenied, "accès refusé");
+        let json = serde_json::to_value(AppError::from(io)).unwrap();
+        assert_eq!(json["code"], "IO");
+        assert_eq!(json["details"], "PermissionDenied");
+        assert!(json["fields"].is_null());
+
+        let error = AppError::Validation(vec![FieldError::new("urls.frontend", "URL invalide")]);
+        let json = serde_json::to_value(error).unwrap();
+        assert_eq!(json["code"], "VALIDATION");
+        assert_eq!(
+            json["message"],
+            "Données invalides : urls.frontend (URL invalide)"
+        );
+        assert_eq!(json["fields"][0]["field"], "urls.frontend");
+    }
+}
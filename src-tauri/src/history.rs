@@ -0,0 +1,491 @@
+// src-tauri/src/history.rs
+// Reconstitution du coffre à une date donnée
+// On part du point de départ le plus proche (un backup, ou le coffre actuel)
+// et on rejoue le journal des modifications : en avant depuis un backup plus
+// ancien, à rebours depuis un état plus récent
+
+use crate::diff;
+use crate::error::AppResult;
+use crate::journal::{JournalEvent, REDACTED};
+use crate::storage::{zeroize_value, AppData};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Modification du journal qui n'a pas pu être rejouée exactement
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedChange {
+    /// Site concerné (`None` pour les paramètres)
+    pub site_id: Option<String>,
+
+    /// Chemin du champ, comme dans le journal
+    pub path: String,
+}
+
+/// État du coffre (ou d'un site) reconstitué à une date
+#[derive(Serialize, Debug)]
+pub struct PointInTime<T> {
+    /// Date demandée (millisecondes depuis l'epoch Unix)
+    pub at_ms: u64,
+
+    /// Backup de départ (`None` : le coffre actuel)
+    pub snapshot_id: Option<String>,
+
+    /// Nombre de modifications rejouées depuis le point de départ
+    pub replayed: usize,
+
+    /// Modifications introuvables dans les données (journal incomplet) ou
+    /// dont la valeur est masquée : le champ garde la valeur du point de
+    /// départ. Dans un site ou un élément de liste reconstitué, un champ
+    /// masqué reprend la valeur de l'élément qu'il remplace ; sans elle (site
+    /// supprimé depuis…), il est vide. Les données ne contiennent jamais
+    /// `REDACTED`.
+    pub unresolved: Vec<UnresolvedChange>,
+
+    /// Date de la première modification du journal : avant, seul un backup
+    /// antérieur donne un état exact
+    pub journal_start_ms: Option<u64>,
+
+    /// Lignes du journal illisibles (autre clé de données), donc non rejouées
+    pub unreadable: usize,
+
+    pub data: T,
+}
+
+/// Résultat d'un rejeu
+#[derive(Debug)]
+pub struct Replay {
+    pub data: AppData,
+    pub replayed: usize,
+    pub unresolved: Vec<UnresolvedChange>,
+}
+
+/// Modifications à rejouer pour passer d'un point de départ à `at_ms`
+///
+/// Un point de départ daté de `snapshot_ms` contient les modifications
+/// antérieures à cette date ; `None` (coffre actuel) les contient toutes.
+///
+/// # Returns
+/// Les modifications dans l'ordre où les appliquer, et `true` pour un rejeu
+/// en avant (valeurs `new`), `false` à rebours (valeurs `old`)
+pub fn events_to_replay(
+    events: &[JournalEvent],
+    snapshot_ms: Option<u64>,
+    at_ms: u64,
+) -> (Vec<&JournalEvent>, bool) {
+    if let Some(start) = snapshot_ms.filter(|start| *start <= at_ms) {
+        let selected = events
+            .iter()
+            .filter(|e| e.at_ms >= start && e.at_ms <= at_ms)
+            .collect();
+        return (selected, true);
+    }
+
+    let selected: Vec<&JournalEvent> = events
+        .iter()
+        .filter(|e| e.at_ms > at_ms && snapshot_ms.is_none_or(|end| e.at_ms < end))
+        .collect();
+    // Écritures de la plus récente à la plus ancienne ; dans chacune, les
+    // éléments ajoutés sont retirés avant de remettre les éléments supprimés
+    // à leur place, par position croissante
+    let mut ordered = Vec::with_capacity(selected.len());
+    for batch in selected.chunk_by(|a, b| a.at_ms == b.at_ms).rev() {
+        let (reinserted, others): (Vec<&JournalEvent>, Vec<_>) = batch
+            .iter()
+            .partition(|e| e.old.is_some() && e.new.is_none());
+        ordered.extend(others);
+        ordered.extend(reinserted);
+    }
+    (ordered, false)
+}
+
+/// Reconstitue les données à `at_ms` à partir d'un point de départ
+///
+/// # Arguments
+/// * `snapshot` - Données du point de départ
+/// * `snapshot_ms` - Date du point de départ (`None` : coffre actuel)
+/// * `events` - Journal, du plus ancien au plus récent
+/// * `at_ms` - Date à reconstituer
+pub fn replay(
+    snapshot: &AppData,
+    snapshot_ms: Option<u64>,
+    events: &[JournalEvent],
+    at_ms: u64,
+) -> AppResult<Replay> {
+    let mut root = serde_json::to_value(snapshot)?;
+    let (selected, forward) = events_to_replay(events, snapshot_ms, at_ms);
+    let mut unresolved = Vec::new();
+
+    for event in &selected {
+        let value = if forward { &event.new } else { &event.old };
+        let masked = value.as_ref().is_some_and(contains_redacted);
+        // Valeur inconnue : le champ garde celle du point de départ
+        let applied = value.as_ref() != Some(&Value::from(REDACTED))
+            && apply(&mut root, event, value.clone());
+        if masked || !applied {
+            unresolved.push(UnresolvedChange {
+                site_id: event.site_id.clone(),
+                path: event.path.clone(),
+            });
+        }
+    }
+
+    let data = AppData::deserialize(&root);
+    // La représentation intermédiaire contient les données en clair
+    zeroize_value(&mut root);
+    Ok(Replay {
+        data: data?,
+        replayed: selected.len(),
+        unresolved,
+    })
+}
+
+/// Applique une modification aux données sérialisées
+///
+/// # Returns
+/// `false` si le site ou le champ n'existe pas
+fn apply(root: &mut Value, event: &JournalEvent, value: Option<Value>) -> bool {
+    let Some(site_id) = &event.site_id else {
+        return match root.get_mut("settings") {
+            Some(settings) => set_path(settings, "settings", &event.path, value, None),
+            None => false,
+        };
+    };
+
+    let Some(Value::Array(sites)) = root.get_mut("sites") else {
+        return false;
+    };
+    let index = sites
+        .iter()
+        .position(|site| site.get("id").and_then(Value::as_str) == Some(site_id.as_str()));
+    match (index, event.path.is_empty(), value) {
+        // Site entier ajouté ou supprimé
+        (Some(index), true, Some(mut site)) => {
+            unmask(&mut site, Some(&sites[index]));
+            zeroize_value(&mut sites[index]);
+            sites[index] = site;
+            true
+        }
+        (Some(index), true, None) => {
+            zeroize_value(&mut sites.remove(index));
+            true
+        }
+        (None, true, Some(mut site)) => {
+            unmask(&mut site, None);
+            let index = event.index.unwrap_or(sites.len()).min(sites.len());
+            sites.insert(index, site);
+            true
+        }
+        (Some(index), false, value) => {
+            set_path(&mut sites[index], "", &event.path, value, event.index)
+        }
+        _ => false,
+    }
+}
+
+/// Écrit `value` au chemin `path` (syntaxe de `diff`) ; `None` retire le champ
+/// ou l'élément de liste
+///
+/// Le chemin est découpé en suivant les données : un nom d'extension ou de
+/// tâche peut contenir '.' ou '['. Un élément de liste absent est inséré à
+/// la position `index` (en fin de liste si elle est inconnue).
+fn set_path(
+    target: &mut Value,
+    list_field: &str,
+    path: &str,
+    value: Option<Value>,
+    index: Option<usize>,
+) -> bool {
+    match target {
+        Value::Object(map) => {
+            let key = map
+                .keys()
+                .filter(|key| starts_segment(path, key))
+                .max_by_key(|key| key.len())
+                .cloned()
+                .or_else(|| (!path.contains(['.', '['])).then(|| path.to_string()));
+            let Some(key) = key else {
+                return false;
+            };
+            let rest = &path[key.len()..];
+            let rest = rest.strip_prefix('.').unwrap_or(rest);
+            if !rest.is_empty() {
+                return match map.get_mut(&key) {
+                    Some(child) => set_path(child, &key, rest, value, index),
+                    None => false,
+                };
+            }
+
+            let value = value.map(|mut value| {
+                unmask(&mut value, map.get(&key));
+                value
+            });
+            if let Some(old) = map.get_mut(&key) {
+                zeroize_value(old);
+            }
+            match value {
+                Some(value) => {
+                    map.insert(key, value);
+                }
+                None => {
+                    map.remove(&key);
+                }
+            }
+            true
+        }
+        Value::Array(items) => {
+            let keys = diff::item_keys(list_field, items);
+            let found = keys
+                .iter()
+                .enumerate()
+                .filter(|(_, key)| starts_item(path, key))
+                .max_by_key(|(_, key)| key.len())
+                .map(|(index, key)| (index, key.len() + 2))
+                // Élément d'une liste secrète, désigné par sa position : seule
+                // sa suppression est rejouée (sa valeur est masquée)
+                .or_else(|| {
+                    item_position(path)
+                        .filter(|(position, _)| value.is_none() && *position < items.len())
+                });
+            let Some((position, len)) = found else {
+                return match value {
+                    Some(mut value) if path.starts_with('[') && path.ends_with(']') => {
+                        unmask(&mut value, None);
+                        let position = index.unwrap_or(items.len()).min(items.len());
+                        items.insert(position, value);
+                        true
+                    }
+                    _ => false,
+                };
+            };
+
+            let rest = &path[len..];
+            let rest = rest.strip_prefix('.').unwrap_or(rest);
+            if !rest.is_empty() {
+                return set_path(&mut items[position], list_field, rest, value, index);
+            }
+            let value = value.map(|mut value| {
+                unmask(&mut value, Some(&items[position]));
+                value
+            });
+            zeroize_value(&mut items[position]);
+            match value {
+                Some(value) => items[position] = value,
+                None => {
+                    items.remove(position);
+                }
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Le chemin commence-t-il par le champ `key` ?
+fn starts_segment(path: &str, key: &str) -> bool {
+    path.strip_prefix(key)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+/// Le chemin commence-t-il par l'élément de liste `[key]` ?
+fn starts_item(path: &str, key: &str) -> bool {
+    path.strip_prefix('[')
+        .and_then(|rest| rest.strip_prefix(key))
+        .and_then(|rest| rest.strip_prefix(']'))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+/// Position de l'élément `[#position]` en tête du chemin, et longueur de
+/// ce segment
+fn item_position(path: &str) -> Option<(usize, usize)> {
+    let rest = path.strip_prefix("[#")?;
+    let end = rest.find(']')?;
+    let position = rest[..end].parse().ok()?;
+    Some((position, end + 3))
+}
+
+/// Remplace les valeurs masquées de `value` par celles de `known`, l'élément
+/// qu'elle remplace ; sans équivalent connu, un texte masqué devient vide et
+/// un élément de liste masqué (éditeur Dashlane) est retiré
+fn unmask(value: &mut Value, known: Option<&Value>) {
+    match value {
+        Value::String(text) if text == REDACTED => {
+            *value = known
+                .filter(|known| known.is_string() || known.is_null())
+                .cloned()
+                .unwrap_or_else(|| Value::from(""));
+        }
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                unmask(field, known.and_then(|known| known.get(key)));
+            }
+        }
+        Value::Array(items) => {
+            let mut position = 0;
+            items.retain_mut(|item| {
+                let known_item = known.and_then(|known| known.get(position));
+                position += 1;
+                if known_item.is_none() && *item == REDACTED {
+                    return false;
+                }
+                unmask(item, known_item);
+                true
+            });
+        }
+        _ => {}
+    }
+}
+
+fn contains_redacted(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s == REDACTED,
+        Value::Array(items) => items.iter().any(contains_redacted),
+        Value::Object(map) => map.values().any(contains_redacted),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use crate::storage::{Extension, Site};
+
+    fn json(data: &AppData) -> Value {
+        serde_json::to_value(data).unwrap()
+    }
+
+    /// Site sans références Dashlane (masquées dans le journal)
+    fn plain_site(id: &str, name: &str) -> Site {
+        let mut site = Site::sample(id, name);
+        site.dashlane_refs.joomla_admin.clear();
+        site.dashlane_refs.mysql_su.clear();
+        site
+    }
+
+    fn extension(name: &str, version: &str) -> Extension {
+        Extension {
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            critical: false,
+        }
+    }
+
+    #[test]
+    fn test_replay_forward_and_backward() {
+        // Trois versions successives, journalisées à 10 et 20
+        let v0 = AppData::default();
+        let mut v1 = v0.clone();
+        let mut site = plain_site("a", "Site A");
+        site.extensions = vec![extension("JCE", "2.9"), extension("Akeeba v.1 [pro]", "9")];
+        v1.sites.push(site);
+        let mut v2 = v1.clone();
+        v2.sites[0].extensions[1].version = Some("10".to_string());
+        v2.sites[0].extensions.remove(0);
+        v2.sites[0].tech.php_version = "8.2".to_string();
+        v2.sites.push(plain_site("b", "Site B"));
+        v2.settings.auto_lock_minutes = 3;
+
+        let mut events = journal::changes(&v0, &v1, 10, "marie@PC").unwrap();
+        events.extend(journal::changes(&v1, &v2, 20, "marie@PC").unwrap());
+
+        // En avant depuis un backup pris avant la première écriture
+        let result = replay(&v0, Some(5), &events, 15).unwrap();
+        assert_eq!(json(&result.data), json(&v1));
+        assert_eq!(result.replayed, 1);
+        assert!(result.unresolved.is_empty());
+        assert_eq!(
+            json(&replay(&v0, Some(5), &events, 20).unwrap().data),
+            json(&v2)
+        );
+
+        // À rebours depuis le coffre actuel
+        // (l'extension supprimée reprend sa place en tête de liste)
+        assert_eq!(
+            json(&replay(&v2, None, &events, 15).unwrap().data),
+            json(&v1)
+        );
+        assert_eq!(
+            json(&replay(&v2, None, &events, 0).unwrap().data),
+            json(&v0)
+        );
+
+        // Point de départ le plus proche
+        assert_eq!(
+            events_to_replay(&events, None, 15).0.len(),
+            events.len() - 1
+        );
+        assert_eq!(events_to_replay(&events, Some(20), 15).0.len(), 0);
+    }
+
+    #[test]
+    fn test_replay_removes_secret_list_item_by_position() {
+        let mut v0 = AppData::default();
+        let mut site = plain_site("a", "Site A");
+        site.dashlane_refs.editors = vec!["Rédaction".to_string(), "Communication".to_string()];
+        v0.sites.push(site);
+        let mut v1 = v0.clone();
+        v1.sites[0].dashlane_refs.editors.remove(0);
+
+        let events = journal::changes(&v0, &v1, 10, "marie@PC").unwrap();
+        assert_eq!(events[0].path, "dashlane_refs.editors[#0]");
+        let result = replay(&v0, Some(5), &events, 10).unwrap();
+        assert_eq!(json(&result.data), json(&v1));
+        assert!(result.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_replay_never_returns_masked_values() {
+        let mut v0 = AppData::default();
+        let mut site = Site::sample("a", "Site A");
+        site.admintools_login = Some("admin-cfdt".to_string());
+        site.dashlane_refs.editors = vec!["Rédaction".to_string()];
+        v0.sites.push(site);
+        let v1 = AppData::default();
+        let events = journal::changes(&v0, &v1, 10, "marie@PC").unwrap();
+
+        // Site supprimé, rejoué à rebours : ses secrets sont inconnus
+        let result = replay(&v1, None, &events, 5).unwrap();
+        let site = &result.data.sites[0];
+        assert_eq!(site.name, "Site A");
+        assert_eq!(site.dashlane_refs.joomla_admin, "");
+        assert_eq!(site.admintools_login.as_deref(), Some(""));
+        assert!(site.dashlane_refs.editors.is_empty());
+        assert!(!serde_json::to_string(&result.data)
+            .unwrap()
+            .contains(REDACTED));
+        assert_eq!(result.unresolved[0].path, "");
+
+        // Site déjà présent au point de départ : ses secrets sont repris
+        let added = journal::changes(&v1, &v0, 10, "marie@PC").unwrap();
+        let result = replay(&v0, Some(5), &added, 10).unwrap();
+        assert_eq!(json(&result.data), json(&v0));
+    }
+
+    #[test]
+    fn test_unresolved_changes_keep_snapshot_value() {
+        let v0 = AppData::default();
+        let event = |path: &str, new: Value| JournalEvent {
+            at_ms: 10,
+            author: String::new(),
+            site_id: None,
+            path: path.to_string(),
+            old: None,
+            new: Some(new),
+            index: None,
+        };
+        let events = vec![
+            event("auto_lock_minutes", Value::from(REDACTED)),
+            event("missing.field", Value::from(1)),
+            event("backup_keep_days", Value::from(7)),
+        ];
+
+        let result = replay(&v0, Some(0), &events, 10).unwrap();
+        assert_eq!(
+            result.data.settings.auto_lock_minutes,
+            v0.settings.auto_lock_minutes
+        );
+        assert_eq!(result.data.settings.backup_keep_days, 7);
+        let paths: Vec<_> = result.unresolved.iter().map(|u| u.path.as_str()).collect();
+        assert_eq!(paths, vec!["auto_lock_minutes", "missing.field"]);
+    }
+}
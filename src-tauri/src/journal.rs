@@ -0,0 +1,512 @@
+// src-tauri/src/journal.rs
+// Journal des modifications du coffre
+// Chaque écriture ajoute une ligne chiffrée à journal.encrypted : qui a
+// modifié quel champ de quel site, et quand. Les lignes sont chaînées par
+// leur empreinte : une ligne modifiée ou retirée est détectée à la lecture
+
+use crate::crypto::{CipherSuite, SealedRecord, SessionKey};
+use crate::diff;
+use crate::error::{AppError, AppResult};
+use crate::merge::sites_by_id;
+use crate::storage::{zeroize_value, AppData};
+use crate::vault_lock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Nom du journal, à côté de sites.encrypted
+pub const JOURNAL_FILE_NAME: &str = "journal.encrypted";
+
+/// Valeur enregistrée à la place d'un secret
+pub const REDACTED: &str = "[masqué]";
+
+/// Champs jamais journalisés en clair : identifiant de la protection
+/// AdminTools et noms des entrées Dashlane (ils désignent les comptes
+/// d'administration). Les mots de passe eux-mêmes ne sont pas dans le coffre.
+const SECRET_FIELDS: [&str; 3] = ["admintools_login", "dashlane_refs", "dashlane_ref"];
+
+/// Modification d'un champ, telle qu'enregistrée dans le journal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalEvent {
+    /// Date de l'écriture (millisecondes depuis l'epoch Unix)
+    pub at_ms: u64,
+
+    /// Auteur (ex: "marie@PC-ACCUEIL")
+    pub author: String,
+
+    /// Site modifié (`None` : paramètres de l'application)
+    pub site_id: Option<String>,
+
+    /// Chemin du champ, comme dans `diff` ("" : site entier ajouté ou supprimé) ;
+    /// sous un champ secret, un élément de liste est désigné par sa position
+    /// (ex: "dashlane_refs.editors[#0]") et non par son nom
+    pub path: String,
+
+    /// Valeur avant la modification (`None` : élément ajouté)
+    pub old: Option<Value>,
+
+    /// Valeur après la modification (`None` : élément supprimé)
+    pub new: Option<Value>,
+
+    /// Position de l'élément (site ou élément de liste) ajouté ou supprimé,
+    /// pour le reconstituer à sa place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+}
+
+impl Drop for JournalEvent {
+    fn drop(&mut self) {
+        // Les valeurs sont des données déchiffrées
+        for value in [&mut self.old, &mut self.new].into_iter().flatten() {
+            zeroize_value(value);
+        }
+    }
+}
+
+/// Filtre de lecture du journal (tous les critères sont optionnels)
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalQuery {
+    /// Seulement les événements de ce site
+    #[serde(default)]
+    pub site_id: Option<String>,
+
+    /// Événements à partir de cette date (ms, incluse)
+    #[serde(default)]
+    pub from_ms: Option<u64>,
+
+    /// Événements avant cette date (ms, exclue)
+    #[serde(default)]
+    pub to_ms: Option<u64>,
+}
+
+impl JournalQuery {
+    fn matches(&self, event: &JournalEvent) -> bool {
+        self.site_id
+            .as_ref()
+            .is_none_or(|id| event.site_id.as_ref() == Some(id))
+            && self.from_ms.is_none_or(|from| event.at_ms >= from)
+            && self.to_ms.is_none_or(|to| event.at_ms < to)
+    }
+}
+
+/// Résultat d'une lecture du journal
+#[derive(Serialize, Debug, Default)]
+pub struct JournalPage {
+    /// Événements retenus, du plus ancien au plus récent
+    pub events: Vec<JournalEvent>,
+
+    /// Lignes chiffrées avec une autre clé de données (coffre recréé ou
+    /// restauré depuis un autre coffre), donc illisibles
+    pub unreadable: usize,
+}
+
+/// Ligne du journal : un lot d'événements chiffré, chaîné à la ligne précédente
+#[derive(Serialize, Deserialize)]
+struct JournalLine {
+    /// Numéro de la ligne (à partir de 0)
+    seq: u64,
+
+    /// Empreinte SHA-256 (hex) de la ligne précédente ("" pour la première)
+    prev: String,
+
+    #[serde(flatten)]
+    record: SealedRecord,
+}
+
+impl JournalLine {
+    /// Données authentifiées avec le lot : sa place dans la chaîne
+    fn aad(&self) -> Vec<u8> {
+        line_aad(self.seq, &self.prev)
+    }
+}
+
+fn line_aad(seq: u64, prev: &str) -> Vec<u8> {
+    format!("{}:{}", seq, prev).into_bytes()
+}
+
+/// Journal chiffré d'un coffre
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Journal du dossier de données `data_dir`
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(JOURNAL_FILE_NAME),
+        }
+    }
+
+    /// Ajoute un lot d'événements (une écriture du coffre) à la fin du journal
+    pub fn append(
+        &self,
+        events: &[JournalEvent],
+        session_key: &SessionKey,
+        suite: CipherSuite,
+    ) -> AppResult<()> {
+        let content = if self.path.exists() {
+            fs::read(&self.path)?
+        } else {
+            Vec::new()
+        };
+        let lines = complete_lines(&content);
+        // Dernière ligne incomplète (écriture interrompue) : elle est retirée
+        let complete_len: usize = lines.iter().map(|l| l.len() + 1).sum();
+        if complete_len < content.len() {
+            OpenOptions::new()
+                .write(true)
+                .open(&self.path)?
+                .set_len(complete_len as u64)?;
+        }
+
+        let seq = lines.len() as u64;
+        let prev = lines
+            .last()
+            .map(|l| to_hex(&Sha256::digest(l)))
+            .unwrap_or_default();
+        let json = Zeroizing::new(serde_json::to_vec(events)?);
+        let record = session_key.seal_record(&json, &line_aad(seq, &prev), suite)?;
+        let line = JournalLine { seq, prev, record };
+
+        let mut serialized = serde_json::to_vec(&line)?;
+        serialized.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&serialized)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Lit les événements correspondant au filtre
+    ///
+    /// # Errors
+    /// `CorruptVault` si une ligne a été modifiée, retirée ou déplacée
+    pub fn query(&self, session_key: &SessionKey, query: &JournalQuery) -> AppResult<JournalPage> {
+        let mut page = JournalPage::default();
+        if !self.path.exists() {
+            return Ok(page);
+        }
+
+        let content = fs::read(&self.path)?;
+        let mut prev = String::new();
+        for (seq, raw) in complete_lines(&content).into_iter().enumerate() {
+            let corrupt = || AppError::CorruptVault(format!("journal modifié (ligne {})", seq + 1));
+            let line: JournalLine = serde_json::from_slice(raw).map_err(|_| corrupt())?;
+            if line.seq != seq as u64 || line.prev != prev {
+                return Err(corrupt());
+            }
+            prev = to_hex(&Sha256::digest(raw));
+
+            match session_key.open_record(&line.record, &line.aad())? {
+                Some(json) => {
+                    let events: Vec<JournalEvent> = serde_json::from_slice(&json)?;
+                    page.events
+                        .extend(events.into_iter().filter(|e| query.matches(e)));
+                }
+                None => page.unreadable += 1,
+            }
+        }
+        Ok(page)
+    }
+}
+
+/// Lignes complètes du journal ; une dernière ligne sans fin de ligne vient
+/// d'une écriture interrompue et est ignorée
+fn complete_lines(content: &[u8]) -> Vec<&[u8]> {
+    let mut lines: Vec<&[u8]> = content.split(|&b| b == b'\n').collect();
+    // Après le dernier '\n' : vide, ou ligne incomplète
+    lines.pop();
+    lines
+}
+
+/// Événements décrivant le passage de `old` à `new`
+///
+/// Un site ajouté ou supprimé donne un seul événement (chemin "") avec le
+/// site entier. Les secrets sont remplacés par `REDACTED`.
+pub fn changes(
+    old: &AppData,
+    new: &AppData,
+    at_ms: u64,
+    author: &str,
+) -> AppResult<Vec<JournalEvent>> {
+    let mut values = [serde_json::to_value(old)?, serde_json::to_value(new)?];
+    let mut events = Vec::new();
+    let mut push = |site_id: Option<&str>, site_index: usize, mut change: diff::FieldChange| {
+        let mut event = JournalEvent {
+            at_ms,
+            author: author.to_string(),
+            site_id: site_id.map(str::to_string),
+            path: change.path.clone(),
+            old: change.old.take(),
+            new: change.new.take(),
+            // Site entier : sa position dans la liste des sites
+            index: if change.path.is_empty() && site_id.is_some() {
+                Some(site_index)
+            } else {
+                change.index
+            },
+        };
+        redact(&mut event);
+        events.push(event);
+    };
+
+    let old_sites = sites_by_id(&values[0]);
+    let new_sites = sites_by_id(&values[1]);
+    for (index, (id, site)) in old_sites.iter().enumerate() {
+        let other = new_sites
+            .iter()
+            .find(|(other, _)| other == id)
+            .map(|(_, s)| *s);
+        for change in diff::field_changes(Some(site), other) {
+            push(Some(id), index, change);
+        }
+    }
+    for (index, (id, site)) in new_sites.iter().enumerate() {
+        if !old_sites.iter().any(|(other, _)| other == id) {
+            for change in diff::field_changes(None, Some(site)) {
+                push(Some(id), index, change);
+            }
+        }
+    }
+    for change in diff::field_changes(values[0].get("settings"), values[1].get("settings")) {
+        push(None, 0, change);
+    }
+
+    // Les représentations intermédiaires contiennent les données en clair
+    values.iter_mut().for_each(zeroize_value);
+    Ok(events)
+}
+
+/// Auteur des modifications faites sur ce poste (ex: "marie@PC-ACCUEIL")
+pub fn author() -> String {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "inconnu".to_string());
+    format!("{}@{}", user, vault_lock::hostname())
+}
+
+/// Masque les secrets d'un événement : le champ lui-même, ou les champs
+/// secrets d'un objet (site entier, élément de liste). Dans un objet, seules
+/// les valeurs sont masquées : sa structure reste celle d'un `Site`.
+fn redact(event: &mut JournalEvent) {
+    let secret_path = event.path.split(['.', '[']).any(is_secret);
+    if secret_path {
+        event.path = redact_path(&event.path, event.index);
+    }
+    for value in [&mut event.old, &mut event.new].into_iter().flatten() {
+        if secret_path {
+            zeroize_value(value);
+            *value = Value::from(REDACTED);
+        } else {
+            redact_value(value);
+        }
+    }
+}
+
+/// Remplace la clé d'un élément de liste situé sous un champ secret (le nom
+/// d'une entrée Dashlane, pour les éditeurs) par sa position `#index`
+fn redact_path(path: &str, index: Option<usize>) -> String {
+    let mut start = 0;
+    for segment in path.split(['.', '[']) {
+        let end = start + segment.len();
+        if is_secret(segment) {
+            return match path[end..].find('[') {
+                Some(bracket) => {
+                    let item = index.map_or_else(|| REDACTED.to_string(), |i| format!("#{}", i));
+                    format!("{}[{}]", &path[..end + bracket], item)
+                }
+                None => path.to_string(),
+            };
+        }
+        start = end + 1;
+    }
+    path.to_string()
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_secret(key) {
+                    mask_strings(field);
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Remplace chaque texte non vide de `value` par `REDACTED`
+fn mask_strings(value: &mut Value) {
+    match value {
+        Value::String(text) if !text.is_empty() => {
+            zeroize_value(value);
+            *value = Value::from(REDACTED);
+        }
+        Value::Object(map) => map.values_mut().for_each(mask_strings),
+        Value::Array(items) => items.iter_mut().for_each(mask_strings),
+        _ => {}
+    }
+}
+
+fn is_secret(name: &str) -> bool {
+    SECRET_FIELDS.contains(&name)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KdfParams, MASTER_SLOT_LABEL};
+    use crate::storage::{JoomlaAccount, Site};
+    use serde_json::json;
+
+    fn session_key() -> SessionKey {
+        let params = KdfParams {
+            memory: 8192,
+            iterations: 1,
+            parallelism: 1,
+        };
+        SessionKey::generate(MASTER_SLOT_LABEL, "test_password_123", None, &params).unwrap()
+    }
+
+    #[test]
+    fn test_changes_are_redacted() {
+        let old = AppData::default();
+        let mut new = AppData::default();
+        new.sites.push(Site::sample("a", "Site A"));
+        new.settings.backup_keep_days = 60;
+
+        let events = changes(&old, &new, 1, "marie@PC").unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].site_id.as_deref(), Some("a"));
+        assert_eq!(events[0].path, "");
+        assert!(events[0].old.is_none());
+        assert_eq!(events[1].site_id, None);
+        assert_eq!(events[1].path, "backup_keep_days");
+
+        // Site entier : références masquées, structure conservée
+        let site = events[0].new.as_ref().unwrap();
+        assert_eq!(site["dashlane_refs"]["joomla_admin"], json!(REDACTED));
+        assert_eq!(site["dashlane_refs"]["backend_protection"], Value::Null);
+        assert_eq!(site["name"], json!("Site A"));
+        let masked: Site = serde_json::from_value(site.clone()).unwrap();
+        assert_eq!(masked.dashlane_refs.mysql_su, REDACTED);
+
+        // Champs modifiés un par un
+        let mut newer = new.clone();
+        newer.sites[0].admintools_login = Some("admin-cfdt".to_string());
+        newer.sites[0].dashlane_refs.editors = vec!["Rédaction".to_string()];
+        newer.sites[0].joomla_accounts = vec![JoomlaAccount {
+            username: "redac".to_string(),
+            role: "Éditeur".to_string(),
+            dashlane_ref: Some("[Site A] Rédaction".to_string()),
+        }];
+        newer.sites[0].tech.php_version = "8.3".to_string();
+        let events = changes(&new, &newer, 2, "marie@PC").unwrap();
+        let value = |path: &str| {
+            events
+                .iter()
+                .find(|e| e.path == path)
+                .and_then(|e| e.new.clone())
+                .unwrap()
+        };
+        assert_eq!(value("admintools_login"), json!(REDACTED));
+        assert_eq!(value("dashlane_refs.editors[#0]"), json!(REDACTED));
+        assert!(events.iter().all(|e| !e.path.contains("Rédaction")));
+        assert_eq!(value("tech.php_version"), json!("8.3"));
+        let account = value("joomla_accounts[redac]");
+        assert_eq!(account["dashlane_ref"], json!(REDACTED));
+        assert_eq!(account["username"], json!("redac"));
+    }
+
+    #[test]
+    fn test_journal_chain_detects_tampering() {
+        let temp_dir = env::temp_dir().join("cockpit_journal_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let journal = Journal::new(&temp_dir);
+        let key = session_key();
+
+        let event = |at_ms: u64, site: &str| JournalEvent {
+            at_ms,
+            author: "marie@PC".to_string(),
+            site_id: Some(site.to_string()),
+            path: "notes".to_string(),
+            old: None,
+            new: Some(json!("texte")),
+            index: None,
+        };
+        for (at_ms, site) in [(10, "a"), (20, "b"), (30, "a")] {
+            journal
+                .append(&[event(at_ms, site)], &key, CipherSuite::default())
+                .unwrap();
+        }
+
+        let all = journal.query(&key, &JournalQuery::default()).unwrap();
+        assert_eq!(all.events.len(), 3);
+        let query = JournalQuery {
+            site_id: Some("a".to_string()),
+            from_ms: Some(20),
+            to_ms: None,
+        };
+        let page = journal.query(&key, &query).unwrap();
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.events[0].at_ms, 30);
+
+        // Une autre clé ne lit rien, mais la chaîne reste vérifiable
+        let page = journal
+            .query(&session_key(), &JournalQuery::default())
+            .unwrap();
+        assert_eq!(page.unreadable, 3);
+
+        // Écriture interrompue : la ligne incomplète est ignorée puis remplacée
+        let path = temp_dir.join(JOURNAL_FILE_NAME);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"seq\":3,").unwrap();
+        assert_eq!(
+            journal
+                .query(&key, &JournalQuery::default())
+                .unwrap()
+                .events
+                .len(),
+            3
+        );
+        journal
+            .append(&[event(40, "b")], &key, CipherSuite::default())
+            .unwrap();
+        assert_eq!(
+            journal
+                .query(&key, &JournalQuery::default())
+                .unwrap()
+                .events
+                .len(),
+            4
+        );
+
+        // Ligne retirée : détecté
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(matches!(
+            journal.query(&key, &JournalQuery::default()),
+            Err(AppError::CorruptVault(_))
+        ));
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,9 +1,26 @@
 // Cockpit CFDT - Bibliothèque principale
 
+pub mod atomic;
+pub mod backup_catalog;
 pub mod config;
 pub mod crypto;
+pub mod diff;
+pub mod error;
+pub mod history;
+pub mod journal;
+mod memory;
+pub mod merge;
+pub mod relocation;
+pub mod restore;
+pub mod retention;
+pub mod site_edit;
 pub mod storage;
+pub mod throttle;
+pub mod trash;
+pub mod undo;
+pub mod vault_lock;
 
 // Réexporter les types nécessaires
 pub use config::ConfigManager;
+pub use error::{AppError, AppResult};
 pub use storage::{AppData, StorageManager};
@@ -3,16 +3,47 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use cockpit_cfdt::{AppData, ConfigManager, StorageManager};
-use std::path::PathBuf;
+use cockpit_cfdt::backup_catalog::{BackupEntry, BackupReason};
+use cockpit_cfdt::crypto::{KeySlotInfo, Keyfile, Password, SessionKey};
+use cockpit_cfdt::diff::VaultDiff;
+use cockpit_cfdt::history::PointInTime;
+use cockpit_cfdt::journal::{JournalPage, JournalQuery};
+use cockpit_cfdt::relocation::RelocationReport;
+use cockpit_cfdt::restore::{
+    self, BackupSiteSummary, ConflictStrategy, RestoreReport, SiteSelection,
+};
+use cockpit_cfdt::retention::RetentionPlan;
+use cockpit_cfdt::site_edit;
+use cockpit_cfdt::storage::{Intervention, RecoveryKey, SaveOutcome, Site};
+use cockpit_cfdt::trash::{self, TrashItem};
+use cockpit_cfdt::undo::{UndoHistory, UndoSummary};
+use cockpit_cfdt::{AppData, AppError, AppResult, ConfigManager, StorageManager};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::State;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{Manager, State};
+
+/// Événement émis vers le frontend quand le backend verrouille l'application
+const AUTO_LOCK_EVENT: &str = "auto-locked";
+
+/// Événement émis quand une autre instance a repris le verrou d'écriture
+const READ_ONLY_EVENT: &str = "read-only";
+
+/// Intervalle de vérification de l'inactivité
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct AppState {
     storage_manager: Mutex<Option<StorageManager>>,
     app_data: Mutex<Option<AppData>>,
+    /// Clé dérivée au déverrouillage, effacée de la mémoire au verrouillage
+    session_key: Mutex<Option<SessionKey>>,
     is_locked: Mutex<bool>,
     config_manager: Mutex<Option<ConfigManager>>,
+    /// Dernière commande de données, pour le verrouillage automatique
+    last_activity: Mutex<Instant>,
+    /// Modifications de la session, effacées au verrouillage
+    undo_history: Mutex<UndoHistory>,
 }
 
 impl AppState {
@@ -20,215 +51,744 @@ impl AppState {
         Self {
             storage_manager: Mutex::new(None),
             app_data: Mutex::new(None),
+            session_key: Mutex::new(None),
             is_locked: Mutex::new(true),
             config_manager: Mutex::new(None),
+            last_activity: Mutex::new(Instant::now()),
+            undo_history: Mutex::new(UndoHistory::default()),
+        }
+    }
+
+    /// Efface les données déchiffrées et la clé de session, et libère le
+    /// verrou d'écriture du coffre
+    fn lock_session(&self) {
+        if let Some(storage) = self.storage_manager.lock().unwrap().as_mut() {
+            storage.release_lock();
         }
+        *self.app_data.lock().unwrap() = None;
+        // La clé est effacée de la mémoire à sa destruction
+        *self.session_key.lock().unwrap() = None;
+        *self.is_locked.lock().unwrap() = true;
+        self.undo_history.lock().unwrap().clear();
+    }
+
+    /// Rafraîchit le verrou d'écriture tant que le coffre est ouvert
+    ///
+    /// Renvoie `true` si le coffre vient de passer en lecture seule
+    fn keep_vault_lock_alive(&self) -> AppResult<bool> {
+        match self.storage_manager.lock().unwrap().as_mut() {
+            Some(storage) => storage.keep_lock_alive(),
+            None => Ok(false),
+        }
+    }
+
+    /// Applique une modification ciblée aux données du coffre, l'enregistre
+    /// et la mémorise sous `description` pour pouvoir l'annuler
+    ///
+    /// # Returns
+    /// Les données après modification
+    fn edit_data<T>(
+        &self,
+        description: String,
+        edit: impl FnOnce(&mut AppData) -> AppResult<T>,
+    ) -> AppResult<AppData> {
+        let (_, before, after) = self.write_edit(BackupReason::Save, edit)?;
+        self.record_step(description, &before, &after);
+        Ok(after)
+    }
+
+    /// Applique une modification ciblée et l'enregistre (après un backup
+    /// pour la raison `reason`), sans la mémoriser
+    ///
+    /// # Returns
+    /// Le résultat de `edit`, et les données avant et après modification
+    fn write_edit<T>(
+        &self,
+        reason: BackupReason,
+        edit: impl FnOnce(&mut AppData) -> AppResult<T>,
+    ) -> AppResult<(T, AppData, AppData)> {
+        self.touch();
+        let storage_guard = self.storage_manager.lock().unwrap();
+        let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+        let key_guard = self.session_key.lock().unwrap();
+        let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+        let mut data_guard = self.app_data.lock().unwrap();
+        let current = data_guard.as_mut().ok_or(AppError::Locked)?;
+        // Version effectivement modifiée (celle du disque si elle a changé)
+        let (value, before) =
+            storage.apply_edit_with_backup(current, session_key, reason, |data| {
+                let before = data.clone();
+                Ok((edit(data)?, before))
+            })?;
+        Ok((value, before, current.clone()))
+    }
+
+    /// Ajoute une étape à l'historique d'annulation
+    ///
+    /// Appelée une fois les autres verrous relâchés : l'historique n'est
+    /// jamais verrouillé en même temps que le stockage ou les données.
+    fn record_step(&self, description: String, before: &AppData, after: &AppData) {
+        self.undo_history
+            .lock()
+            .unwrap()
+            .record(description, before, after);
+    }
+
+    /// Nom du site `site_id` pour les descriptions de l'historique
+    fn site_label(&self, site_id: &str) -> String {
+        self.app_data
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|data| data.sites.iter().find(|s| s.id == site_id))
+            .map_or_else(|| site_id.to_string(), |site| site.name.clone())
+    }
+
+    /// Repousse le verrouillage automatique
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Verrouille si aucune commande de données n'a été reçue depuis
+    /// `auto_lock_minutes` (0 = jamais)
+    ///
+    /// # Returns
+    /// `true` si l'application vient d'être verrouillée
+    fn lock_if_idle(&self) -> bool {
+        if *self.is_locked.lock().unwrap() {
+            return false;
+        }
+
+        let auto_lock_minutes = match self.app_data.lock().unwrap().as_ref() {
+            Some(data) => data.settings.auto_lock_minutes,
+            None => return false,
+        };
+        if auto_lock_minutes == 0 {
+            return false;
+        }
+
+        let timeout = Duration::from_secs(u64::from(auto_lock_minutes) * 60);
+        if self.last_activity.lock().unwrap().elapsed() < timeout {
+            return false;
+        }
+
+        self.lock_session();
+        true
     }
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lit le fichier clé éventuellement choisi par l'utilisateur
+fn read_keyfile(keyfile_path: Option<&str>) -> AppResult<Option<Keyfile>> {
+    keyfile_path
+        .map(|path| Keyfile::from_file(Path::new(path)))
+        .transpose()
+}
+
 #[tauri::command]
-fn initialize_storage(app_dir: String, state: State<AppState>) -> Result<bool, String> {
+fn initialize_storage(app_dir: String, state: State<AppState>) -> AppResult<bool> {
     let path = PathBuf::from(&app_dir);
-    let storage = StorageManager::new(&path).map_err(|e| format!("Erreur: {}", e))?;
+    let storage = StorageManager::new(&path)?;
+    // Restes d'une sauvegarde interrompue (plantage, clé USB retirée)
+    storage.cleanup_temp_files()?;
     let exists = storage.exists();
     *state.storage_manager.lock().unwrap() = Some(storage);
 
     // Initialiser le ConfigManager avec le même répertoire
-    let config = ConfigManager::new(&path).map_err(|e| format!("Erreur config: {}", e))?;
+    let config = ConfigManager::new(&path)?;
     *state.config_manager.lock().unwrap() = Some(config);
 
     Ok(exists)
 }
 
 #[tauri::command]
-fn create_initial_data(password: String, state: State<AppState>) -> Result<(), String> {
+fn create_initial_data(password: Password, state: State<AppState>) -> AppResult<()> {
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    storage
-        .initialize(&password)
-        .map_err(|e| format!("Erreur: {}", e))?;
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    storage.initialize(password.expose())?;
     Ok(())
 }
 
 #[tauri::command]
-fn unlock(password: String, state: State<AppState>) -> Result<AppData, String> {
-    let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    let data = storage
-        .load(&password)
-        .map_err(|e| format!("Erreur: {}", e))?;
+fn unlock(
+    password: Password,
+    keyfile_path: Option<String>,
+    read_only: Option<bool>,
+    state: State<AppState>,
+) -> AppResult<AppData> {
+    let keyfile = read_keyfile(keyfile_path.as_deref())?;
+    let mut storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_mut().ok_or(AppError::NotInitialized)?;
+
+    // Verrou d'écriture (VAULT_IN_USE si une autre instance a ouvert le coffre)
+    storage.acquire_lock(read_only.unwrap_or(false))?;
+    let (data, session_key) = match storage.unlock(password.expose(), keyfile.as_ref()) {
+        Ok(unlocked) => unlocked,
+        Err(e) => {
+            storage.release_lock();
+            return Err(e);
+        }
+    };
     *state.app_data.lock().unwrap() = Some(data.clone());
+    *state.session_key.lock().unwrap() = Some(session_key);
     *state.is_locked.lock().unwrap() = false;
+    state.touch();
     Ok(data)
 }
 
 #[tauri::command]
-fn lock(state: State<AppState>) -> Result<(), String> {
-    *state.app_data.lock().unwrap() = None;
-    *state.is_locked.lock().unwrap() = true;
+fn get_unlock_wait(state: State<AppState>) -> AppResult<u64> {
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    Ok(storage.unlock_wait())
+}
+
+#[tauri::command]
+fn vault_requires_keyfile(state: State<AppState>) -> AppResult<bool> {
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    storage.requires_keyfile()
+}
+
+#[tauri::command]
+fn lock(state: State<AppState>) -> AppResult<()> {
+    state.lock_session();
     Ok(())
 }
 
 #[tauri::command]
-fn is_locked(state: State<AppState>) -> Result<bool, String> {
+fn is_locked(state: State<AppState>) -> AppResult<bool> {
     Ok(*state.is_locked.lock().unwrap())
 }
 
 #[tauri::command]
-fn save_data(password: String, data: AppData, state: State<AppState>) -> Result<(), String> {
+fn is_read_only(state: State<AppState>) -> AppResult<bool> {
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    storage
-        .save(&data, &password, true)
-        .map_err(|e| format!("Erreur: {}", e))?;
-    *state.app_data.lock().unwrap() = Some(data);
-    Ok(())
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    Ok(storage.is_read_only())
+}
+
+/// Sauvegarde ; si le fichier a été modifié ailleurs depuis le chargement,
+/// retourne la fusion écrite ou les conflits à arbitrer
+#[tauri::command]
+fn save_data(data: AppData, state: State<AppState>) -> AppResult<SaveOutcome> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    // Les données du backend sont la base de la fusion
+    let mut data_guard = state.app_data.lock().unwrap();
+    let base = data_guard.as_mut().ok_or(AppError::Locked)?;
+    // Mêmes règles que les commandes ciblées pour les sites ajoutés ou modifiés
+    site_edit::validate_changed_sites(base, &data)?;
+    let before = base.clone();
+    let outcome = storage.save_checked(base, &data, session_key, true)?;
+    drop((data_guard, key_guard, storage_guard));
+
+    // L'étape va de la base aux données de l'application : en cas de fusion,
+    // les modifications faites ailleurs ne sont pas annulées avec elle
+    if !matches!(outcome, SaveOutcome::Conflicts { .. }) {
+        state.record_step(
+            "Enregistrement des modifications".to_string(),
+            &before,
+            &data,
+        );
+    }
+    Ok(outcome)
 }
 
 #[tauri::command]
-fn get_data(state: State<AppState>) -> Result<AppData, String> {
+fn get_data(state: State<AppState>) -> AppResult<AppData> {
+    state.touch();
     let data_guard = state.app_data.lock().unwrap();
-    data_guard
-        .as_ref()
-        .ok_or("Application verrouillée".to_string())
+    data_guard.as_ref().cloned().ok_or(AppError::Locked)
+}
+
+#[tauri::command]
+fn add_site(site: Site, state: State<AppState>) -> AppResult<AppData> {
+    let description = format!("Ajout du site {}", site.name);
+    state.edit_data(description, |data| site_edit::add_site(data, site))
+}
+
+#[tauri::command]
+fn update_site(site_id: String, site: Site, state: State<AppState>) -> AppResult<AppData> {
+    let description = format!("Modification du site {}", site.name);
+    state.edit_data(description, |data| {
+        site_edit::update_site(data, &site_id, site)
+    })
+}
+
+/// Met un site à la corbeille
+#[tauri::command]
+fn delete_site(site_id: String, state: State<AppState>) -> AppResult<AppData> {
+    let description = format!("Suppression du site {}", state.site_label(&site_id));
+    state.edit_data(description, |data| trash::trash_site(data, &site_id))
+}
+
+#[tauri::command]
+fn add_intervention(
+    site_id: String,
+    intervention: Intervention,
+    state: State<AppState>,
+) -> AppResult<AppData> {
+    let description = format!("Ajout d'une intervention ({})", state.site_label(&site_id));
+    state.edit_data(description, |data| {
+        site_edit::add_intervention(data, &site_id, intervention)
+    })
+}
+
+#[tauri::command]
+fn delete_intervention(
+    site_id: String,
+    index: usize,
+    state: State<AppState>,
+) -> AppResult<AppData> {
+    let description = format!(
+        "Suppression d'une intervention ({})",
+        state.site_label(&site_id)
+    );
+    state.edit_data(description, |data| {
+        trash::trash_intervention(data, &site_id, index)
+    })
+}
+
+#[tauri::command]
+fn delete_contact(site_id: String, index: usize, state: State<AppState>) -> AppResult<AppData> {
+    let description = format!("Suppression d'un contact ({})", state.site_label(&site_id));
+    state.edit_data(description, |data| {
+        trash::trash_contact(data, &site_id, index)
+    })
+}
+
+#[tauri::command]
+fn list_trash(state: State<AppState>) -> AppResult<Vec<TrashItem>> {
+    state.touch();
+    let data_guard = state.app_data.lock().unwrap();
+    let data = data_guard.as_ref().ok_or(AppError::Locked)?;
+    Ok(data.trash.clone())
+}
+
+/// Remet un élément de la corbeille à sa place
+#[tauri::command]
+fn restore_from_trash(item_id: String, state: State<AppState>) -> AppResult<AppData> {
+    let description = "Restauration depuis la corbeille".to_string();
+    state.edit_data(description, |data| trash::restore(data, &item_id))
+}
+
+#[tauri::command]
+fn purge_trash_item(item_id: String, state: State<AppState>) -> AppResult<AppData> {
+    let description = "Suppression définitive d'un élément de la corbeille".to_string();
+    state.edit_data(description, |data| trash::purge(data, &item_id))
+}
+
+#[tauri::command]
+fn empty_trash(state: State<AppState>) -> AppResult<AppData> {
+    let description = "Vidage de la corbeille".to_string();
+    state.edit_data(description, |data| Ok(trash::empty(data)))
+}
+
+#[tauri::command]
+fn toggle_checklist_item(
+    site_id: String,
+    index: usize,
+    state: State<AppState>,
+) -> AppResult<AppData> {
+    let description = format!("Checklist de {}", state.site_label(&site_id));
+    state.edit_data(description, |data| {
+        site_edit::toggle_checklist_item(data, &site_id, index)
+    })
+}
+
+/// Annule la dernière modification de la session
+///
+/// Les modifications faites depuis (ailleurs ou sur d'autres champs) sont
+/// conservées ; si l'une d'elles touche un champ de l'étape, rien n'est écrit.
+#[tauri::command]
+fn undo(state: State<AppState>) -> AppResult<AppData> {
+    let step = state
+        .undo_history
+        .lock()
+        .unwrap()
+        .next_undo()
+        .cloned()
+        .ok_or_else(|| AppError::InvalidInput("Aucune modification à annuler".to_string()))?;
+    let (_, _, data) = state.write_edit(BackupReason::Save, |data| step.revert(data))?;
+    state.undo_history.lock().unwrap().undone(&step);
+    Ok(data)
+}
+
+/// Rétablit la dernière modification annulée
+#[tauri::command]
+fn redo(state: State<AppState>) -> AppResult<AppData> {
+    let step = state
+        .undo_history
+        .lock()
+        .unwrap()
+        .next_redo()
         .cloned()
+        .ok_or_else(|| AppError::InvalidInput("Aucune modification à rétablir".to_string()))?;
+    let (_, _, data) = state.write_edit(BackupReason::Save, |data| step.reapply(data))?;
+    state.undo_history.lock().unwrap().redone(&step);
+    Ok(data)
+}
+
+/// Descriptions des étapes annulables et rétablissables
+#[tauri::command]
+fn get_undo_history(state: State<AppState>) -> AppResult<UndoSummary> {
+    state.touch();
+    if *state.is_locked.lock().unwrap() {
+        return Err(AppError::Locked);
+    }
+    Ok(state.undo_history.lock().unwrap().summary())
 }
 
 #[tauri::command]
 fn change_password(
-    old_password: String,
-    new_password: String,
+    old_password: Password,
+    new_password: Password,
+    keyfile_path: Option<String>,
     state: State<AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
+    state.touch();
+    let keyfile = read_keyfile(keyfile_path.as_deref())?;
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+
+    // Seul l'emplacement ouvert par l'ancien mot de passe est re-protégé
+    storage.change_password(
+        session_key,
+        old_password.expose(),
+        new_password.expose(),
+        keyfile.as_ref(),
+    )?;
 
-    // Vérifier l'ancien mot de passe en chargeant les données
-    let data = storage
-        .load(&old_password)
-        .map_err(|_| "Ancien mot de passe incorrect")?;
+    Ok(())
+}
 
-    // Sauvegarder avec le nouveau mot de passe
-    storage
-        .save(&data, &new_password, true)
-        .map_err(|e| format!("Erreur: {}", e))?;
+#[tauri::command]
+fn generate_keyfile(path: String) -> AppResult<()> {
+    Keyfile::generate(Path::new(&path)).map(|_| ())
+}
 
-    Ok(())
+#[tauri::command]
+fn attach_keyfile(
+    password: Password,
+    keyfile_path: String,
+    current_keyfile_path: Option<String>,
+    state: State<AppState>,
+) -> AppResult<()> {
+    state.touch();
+    let keyfile = Keyfile::from_file(Path::new(&keyfile_path))?;
+    let current_keyfile = read_keyfile(current_keyfile_path.as_deref())?;
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+    storage.attach_keyfile(
+        session_key,
+        password.expose(),
+        current_keyfile.as_ref(),
+        &keyfile,
+    )
 }
 
 #[tauri::command]
-fn get_data_location(state: State<AppState>) -> Result<String, String> {
+fn remove_keyfile(
+    password: Password,
+    keyfile_path: String,
+    state: State<AppState>,
+) -> AppResult<()> {
+    state.touch();
+    let keyfile = Keyfile::from_file(Path::new(&keyfile_path))?;
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+    storage.remove_keyfile(session_key, password.expose(), &keyfile)
+}
+
+#[tauri::command]
+fn list_key_slots(state: State<AppState>) -> AppResult<Vec<KeySlotInfo>> {
+    state.touch();
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    Ok(session_key
+        .key_slots()
+        .iter()
+        .map(|slot| slot.info())
+        .collect())
+}
+
+#[tauri::command]
+fn add_password_slot(
+    label: String,
+    password: Password,
+    state: State<AppState>,
+) -> AppResult<KeySlotInfo> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+    storage.add_password_slot(session_key, &label, password.expose())
+}
+
+#[tauri::command]
+fn add_recovery_key(label: String, state: State<AppState>) -> AppResult<RecoveryKey> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+    storage.add_recovery_key(session_key, &label)
+}
+
+#[tauri::command]
+fn revoke_key_slot(slot_id: String, state: State<AppState>) -> AppResult<()> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let mut key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_mut().ok_or(AppError::Locked)?;
+    storage.revoke_key_slot(session_key, &slot_id)
+}
+
+#[tauri::command]
+fn get_data_location(state: State<AppState>) -> AppResult<String> {
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
 
     // Récupérer le chemin actuel depuis le storage manager
     let path = storage.get_data_dir();
     Ok(path.to_string_lossy().to_string())
 }
 
+/// Déplace les données vers `new_path` et y fait pointer le coffre ouvert
+///
+/// Les copies sont vérifiées avant d'enregistrer le nouvel emplacement ;
+/// en cas d'échec, rien ne change. `remove_source` efface l'ancien emplacement.
 #[tauri::command]
-fn set_data_location(new_path: String, state: State<AppState>) -> Result<(), String> {
-    use std::fs;
-
-    let new_dir = PathBuf::from(&new_path);
+fn set_data_location(
+    new_path: String,
+    remove_source: Option<bool>,
+    state: State<AppState>,
+) -> AppResult<RelocationReport> {
+    state.touch();
+    let mut storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_mut().ok_or(AppError::NotInitialized)?;
+    let config_guard = state.config_manager.lock().unwrap();
+    let config = config_guard.as_ref().ok_or(AppError::NotInitialized)?;
 
-    // Vérifier que le dossier existe
-    if !new_dir.exists() {
-        return Err(format!("Le dossier '{}' n'existe pas", new_path));
-    }
+    storage.relocate(Path::new(&new_path), remove_source.unwrap_or(false), || {
+        config.set_custom_data_location(Some(new_path.clone()))
+    })
+}
 
-    // Récupérer l'ancien emplacement
+#[tauri::command]
+fn list_backups(state: State<AppState>) -> AppResult<Vec<BackupEntry>> {
+    state.touch();
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    let old_dir = storage.get_data_dir().to_path_buf();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    storage.list_backups()
+}
 
-    // Vérifier que ce n'est pas le même dossier
-    if old_dir == new_dir {
-        return Err("Le nouvel emplacement est identique à l'ancien".to_string());
+/// Backup manuel du coffre, conservé quelle que soit la rétention
+#[tauri::command]
+fn create_backup(state: State<AppState>) -> AppResult<BackupEntry> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    if state.session_key.lock().unwrap().is_none() {
+        return Err(AppError::Locked);
     }
+    storage.create_manual_backup()
+}
 
-    drop(storage_guard); // Libérer le lock
+#[tauri::command]
+fn restore_backup(
+    backup_id: String,
+    backup_password: Option<Password>,
+    state: State<AppState>,
+) -> AppResult<AppData> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    let data = storage.restore_backup(
+        &backup_id,
+        session_key,
+        backup_password.as_ref().map(Password::expose),
+    )?;
+    let before = state.app_data.lock().unwrap().replace(data.clone());
+    drop((key_guard, storage_guard));
+    if let Some(before) = before {
+        state.record_step("Restauration d'un backup".to_string(), &before, &data);
+    }
+    Ok(data)
+}
 
-    // Déplacer sites.encrypted s'il existe
-    let old_data_file = old_dir.join("sites.encrypted");
-    let new_data_file = new_dir.join("sites.encrypted");
+/// Réglage `backup_keep_days` des données déverrouillées
+fn backup_keep_days(state: &AppState) -> AppResult<u32> {
+    let data_guard = state.app_data.lock().unwrap();
+    let data = data_guard.as_ref().ok_or(AppError::Locked)?;
+    Ok(data.settings.backup_keep_days)
+}
 
-    if old_data_file.exists() {
-        fs::copy(&old_data_file, &new_data_file)
-            .map_err(|e| format!("Erreur lors de la copie de sites.encrypted: {}", e))?;
-    }
+/// Backups que la politique de rétention supprimerait, sans rien supprimer
+#[tauri::command]
+fn preview_backup_retention(state: State<AppState>) -> AppResult<RetentionPlan> {
+    state.touch();
+    let keep_days = backup_keep_days(&state)?;
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    storage.retention_preview(keep_days)
+}
 
-    // Déplacer le dossier backups s'il existe
-    let old_backup_dir = old_dir.join("backups");
-    let new_backup_dir = new_dir.join("backups");
+#[tauri::command]
+fn apply_backup_retention(state: State<AppState>) -> AppResult<RetentionPlan> {
+    state.touch();
+    let keep_days = backup_keep_days(&state)?;
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    storage.apply_retention(keep_days)
+}
 
-    if old_backup_dir.exists() {
-        // Créer le dossier backups dans le nouveau dossier
-        if !new_backup_dir.exists() {
-            fs::create_dir_all(&new_backup_dir)
-                .map_err(|e| format!("Erreur lors de la création du dossier backups: {}", e))?;
-        }
+/// Sites d'un backup, pour choisir ceux à restaurer
+#[tauri::command]
+fn list_backup_sites(
+    backup_id: String,
+    backup_password: Option<Password>,
+    state: State<AppState>,
+) -> AppResult<Vec<BackupSiteSummary>> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    let backup = storage.open_backup(
+        &backup_id,
+        session_key,
+        backup_password.as_ref().map(Password::expose),
+    )?;
+    let data_guard = state.app_data.lock().unwrap();
+    let current = data_guard.as_ref().ok_or(AppError::Locked)?;
+    Ok(restore::summarize(&backup, current))
+}
 
-        // Copier tous les fichiers de backup
-        for entry in fs::read_dir(&old_backup_dir)
-            .map_err(|e| format!("Erreur lors de la lecture des backups: {}", e))?
-        {
-            let entry = entry.map_err(|e| format!("Erreur: {}", e))?;
-            let file_name = entry.file_name();
-            let old_file = old_backup_dir.join(&file_name);
-            let new_file = new_backup_dir.join(&file_name);
-
-            if old_file.is_file() {
-                fs::copy(&old_file, &new_file).map_err(|e| {
-                    format!(
-                        "Erreur lors de la copie de {}: {}",
-                        file_name.to_string_lossy(),
-                        e
-                    )
-                })?;
-            }
-        }
-    }
+/// Restaure des sites d'un backup (ou certaines de leurs listes) dans le
+/// coffre actuel, sans perdre les modifications des autres sites
+#[tauri::command]
+fn restore_sites(
+    backup_id: String,
+    selections: Vec<SiteSelection>,
+    on_conflict: ConflictStrategy,
+    backup_password: Option<Password>,
+    state: State<AppState>,
+) -> AppResult<RestoreReport> {
+    state.touch();
+    let backup = {
+        let storage_guard = state.storage_manager.lock().unwrap();
+        let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+        let key_guard = state.session_key.lock().unwrap();
+        let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+        storage.open_backup(
+            &backup_id,
+            session_key,
+            backup_password.as_ref().map(Password::expose),
+        )?
+    };
+
+    // Appliquée à la version du disque si elle a changé (voir `apply_edit`) :
+    // l'étape d'annulation part de cette version
+    let (report, before, after) = state.write_edit(BackupReason::Restore, |data| {
+        let report = restore::restore_sites(data, &backup, &selections, on_conflict)?;
+        *data = report.data.clone();
+        Ok(report)
+    })?;
+    state.record_step(
+        "Restauration de sites d'un backup".to_string(),
+        &before,
+        &after,
+    );
+    Ok(report)
+}
 
-    // Sauvegarder le nouvel emplacement dans la config
-    let config_guard = state.config_manager.lock().unwrap();
-    if let Some(config) = config_guard.as_ref() {
-        config
-            .set_custom_data_location(Some(new_path))
-            .map_err(|e| format!("Erreur lors de la sauvegarde de la config: {}", e))?;
-    }
+/// Différences entre deux backups, ou entre un backup et le coffre actuel
+/// (`to` absent), pour voir ce qu'une restauration changerait
+#[tauri::command]
+fn diff_backups(
+    from: String,
+    to: Option<String>,
+    from_password: Option<Password>,
+    to_password: Option<Password>,
+    state: State<AppState>,
+) -> AppResult<VaultDiff> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    storage.diff_backups(
+        &from,
+        to.as_deref(),
+        session_key,
+        from_password.as_ref().map(Password::expose),
+        to_password.as_ref().map(Password::expose),
+    )
+}
 
-    Ok(())
+/// Historique des modifications, par site et/ou par période
+#[tauri::command]
+fn query_journal(query: JournalQuery, state: State<AppState>) -> AppResult<JournalPage> {
+    state.touch();
+    let storage_guard = state.storage_manager.lock().unwrap();
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    storage.query_journal(session_key, &query)
 }
 
+/// Coffre tel qu'il était à `at_ms`, reconstitué depuis le journal
 #[tauri::command]
-fn list_backups(state: State<AppState>) -> Result<Vec<String>, String> {
+fn get_vault_at(at_ms: u64, state: State<AppState>) -> AppResult<PointInTime<AppData>> {
+    state.touch();
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    storage.list_backups().map_err(|e| format!("Erreur: {}", e))
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    storage.state_at(at_ms, None, session_key)
 }
 
+/// Site tel qu'il était à `at_ms` (`null` s'il n'existait pas encore)
 #[tauri::command]
-fn restore_backup(backup_name: String, state: State<AppState>) -> Result<(), String> {
+fn get_site_at(
+    site_id: String,
+    at_ms: u64,
+    state: State<AppState>,
+) -> AppResult<PointInTime<Option<Site>>> {
+    state.touch();
     let storage_guard = state.storage_manager.lock().unwrap();
-    let storage = storage_guard.as_ref().ok_or("Storage non initialisé")?;
-    storage
-        .restore_backup(&backup_name)
-        .map_err(|e| format!("Erreur: {}", e))?;
-    *state.app_data.lock().unwrap() = None;
-    *state.is_locked.lock().unwrap() = true;
-    Ok(())
+    let storage = storage_guard.as_ref().ok_or(AppError::NotInitialized)?;
+    let key_guard = state.session_key.lock().unwrap();
+    let session_key = key_guard.as_ref().ok_or(AppError::Locked)?;
+    storage.site_at(&site_id, at_ms, session_key)
 }
 
 #[tauri::command]
-fn get_custom_data_location(config_dir: String) -> Result<Option<String>, String> {
+fn get_custom_data_location(config_dir: String) -> AppResult<Option<String>> {
     let path = PathBuf::from(&config_dir);
-    let config = ConfigManager::new(&path)
-        .map_err(|e| format!("Erreur lors de l'initialisation de la config: {}", e))?;
+    let config = ConfigManager::new(&path)?;
 
-    config
-        .get_custom_data_location()
-        .map_err(|e| format!("Erreur lors de la lecture de la config: {}", e))
+    config.get_custom_data_location()
 }
 
 fn main() {
@@ -238,17 +798,105 @@ fn main() {
             initialize_storage,
             create_initial_data,
             unlock,
+            get_unlock_wait,
+            vault_requires_keyfile,
             lock,
             is_locked,
+            is_read_only,
             save_data,
             get_data,
+            add_site,
+            update_site,
+            delete_site,
+            add_intervention,
+            delete_intervention,
+            delete_contact,
+            toggle_checklist_item,
+            undo,
+            redo,
+            get_undo_history,
+            list_trash,
+            restore_from_trash,
+            purge_trash_item,
+            empty_trash,
             list_backups,
+            create_backup,
             restore_backup,
+            list_backup_sites,
+            restore_sites,
+            diff_backups,
+            query_journal,
+            get_vault_at,
+            get_site_at,
+            preview_backup_retention,
+            apply_backup_retention,
             change_password,
+            generate_keyfile,
+            attach_keyfile,
+            remove_keyfile,
+            list_key_slots,
+            add_password_slot,
+            add_recovery_key,
+            revoke_key_slot,
             get_data_location,
             set_data_location,
             get_custom_data_location,
         ])
-        .run(tauri::generate_context!())
-        .expect("Erreur lors du lancement de l'application");
+        .setup(|app| {
+            // Verrouillage automatique côté backend, même si le frontend ne répond plus
+            let handle = app.handle();
+            thread::spawn(move || loop {
+                thread::sleep(AUTO_LOCK_CHECK_INTERVAL);
+                let state = handle.state::<AppState>();
+                if state.lock_if_idle() {
+                    let _ = handle.emit_all(AUTO_LOCK_EVENT, ());
+                }
+                // Lecteur réseau momentanément indisponible : nouvel essai au prochain tour
+                if let Ok(true) = state.keep_vault_lock_alive() {
+                    let _ = handle.emit_all(READ_ONLY_EVENT, ());
+                }
+            });
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("Erreur lors du lancement de l'application")
+        .run(|handle, event| {
+            // Libérer le verrou du coffre à la fermeture
+            if let tauri::RunEvent::Exit = event {
+                handle.state::<AppState>().lock_session();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_if_idle() {
+        let state = AppState::new();
+        assert!(!state.lock_if_idle());
+
+        let mut data = AppData::default();
+        data.settings.auto_lock_minutes = 5;
+        let before = data.clone();
+        data.settings.auto_lock_minutes = 10;
+        state.record_step("Réglages".to_string(), &before, &data);
+        *state.app_data.lock().unwrap() = Some(data);
+        *state.is_locked.lock().unwrap() = false;
+
+        // Activité récente : pas de verrouillage
+        state.touch();
+        assert!(!state.lock_if_idle());
+
+        // Inactif depuis plus de 10 minutes : données et historique effacés
+        *state.last_activity.lock().unwrap() = Instant::now() - Duration::from_secs(11 * 60);
+        assert!(state.lock_if_idle());
+        assert!(state.app_data.lock().unwrap().is_none());
+        assert!(*state.is_locked.lock().unwrap());
+        assert_eq!(
+            state.undo_history.lock().unwrap().summary(),
+            UndoSummary::default()
+        );
+    }
 }
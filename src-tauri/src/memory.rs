@@ -0,0 +1,155 @@
+// src-tauri/src/memory.rs
+// Mémoire protégée pour les clés
+// Les clés sont placées dans une page qui leur est propre, verrouillée en
+// mémoire vive (mlock / VirtualLock) pour ne pas être écrite dans le swap
+
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+use std::sync::OnceLock;
+use zeroize::Zeroize;
+
+/// Taille de page retenue si le système ne la donne pas (multiple des
+/// tailles de page courantes)
+const FALLBACK_PAGE_SIZE: usize = 16 * 1024;
+
+/// Taille d'une page mémoire du système (4 Ko en général, 16 Ko sur les Mac
+/// Apple Silicon), lue une fois
+fn page_size() -> usize {
+    static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+    *PAGE_SIZE.get_or_init(|| {
+        system_page_size()
+            .filter(|size| size.is_power_of_two())
+            .unwrap_or(FALLBACK_PAGE_SIZE)
+    })
+}
+
+#[cfg(unix)]
+fn system_page_size() -> Option<usize> {
+    // SAFETY: sysconf ne fait que lire une valeur du système
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    usize::try_from(size).ok()
+}
+
+#[cfg(windows)]
+fn system_page_size() -> Option<usize> {
+    use windows_sys::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+
+    // SAFETY: SYSTEM_INFO ne contient que des entiers et des pointeurs bruts,
+    // et GetSystemInfo la remplit entièrement
+    let info = unsafe {
+        let mut info: SYSTEM_INFO = std::mem::zeroed();
+        GetSystemInfo(&mut info);
+        info
+    };
+    usize::try_from(info.dwPageSize).ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_page_size() -> Option<usize> {
+    None
+}
+
+/// Clé de 256 bits stockée hors du tas ordinaire et effacée à la libération
+///
+/// Le verrouillage est fait au mieux : s'il est refusé (limite `RLIMIT_MEMLOCK`,
+/// plateforme non gérée), la clé reste utilisable et est toujours effacée.
+pub(crate) struct LockedKey {
+    ptr: NonNull<[u8; 32]>,
+    locked: bool,
+}
+
+// La page n'est accessible qu'à travers `LockedKey`, comme un `Box`
+unsafe impl Send for LockedKey {}
+unsafe impl Sync for LockedKey {}
+
+impl LockedKey {
+    /// Alloue une clé à zéro dans sa propre page
+    pub(crate) fn zeroed() -> Self {
+        let layout = Self::layout();
+        // SAFETY: la taille de `layout` est non nulle
+        let raw = unsafe { alloc::alloc_zeroed(layout) };
+        let Some(ptr) = NonNull::new(raw) else {
+            alloc::handle_alloc_error(layout);
+        };
+        let locked = lock_page(raw);
+        Self {
+            ptr: ptr.cast(),
+            locked,
+        }
+    }
+
+    fn layout() -> Layout {
+        let page_size = page_size();
+        Layout::from_size_align(page_size, page_size).expect("taille de page invalide")
+    }
+}
+
+impl std::ops::Deref for LockedKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &[u8; 32] {
+        // SAFETY: pointeur valide et aligné jusqu'à la libération
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl std::ops::DerefMut for LockedKey {
+    fn deref_mut(&mut self) -> &mut [u8; 32] {
+        // SAFETY: accès exclusif garanti par `&mut self`
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl Zeroize for LockedKey {
+    fn zeroize(&mut self) {
+        (**self).zeroize();
+    }
+}
+
+impl Drop for LockedKey {
+    fn drop(&mut self) {
+        self.zeroize();
+        let raw = self.ptr.as_ptr().cast::<u8>();
+        if self.locked {
+            unlock_page(raw);
+        }
+        // SAFETY: alloué par `zeroed` avec le même layout
+        unsafe { alloc::dealloc(raw, Self::layout()) };
+    }
+}
+
+#[cfg(unix)]
+fn lock_page(page: *mut u8) -> bool {
+    // SAFETY: `page` pointe sur une page allouée
+    unsafe { libc::mlock(page.cast(), page_size()) == 0 }
+}
+
+#[cfg(unix)]
+fn unlock_page(page: *mut u8) {
+    // SAFETY: page verrouillée par `lock_page`
+    unsafe {
+        libc::munlock(page.cast(), page_size());
+    }
+}
+
+#[cfg(windows)]
+fn lock_page(page: *mut u8) -> bool {
+    // SAFETY: `page` pointe sur une page allouée
+    unsafe { windows_sys::Win32::System::Memory::VirtualLock(page.cast(), page_size()) != 0 }
+}
+
+#[cfg(windows)]
+fn unlock_page(page: *mut u8) {
+    // SAFETY: page verrouillée par `lock_page`
+    unsafe {
+        windows_sys::Win32::System::Memory::VirtualUnlock(page.cast(), page_size());
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn lock_page(_page: *mut u8) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+fn unlock_page(_page: *mut u8) {}
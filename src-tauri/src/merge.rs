@@ -0,0 +1,320 @@
+// src-tauri/src/merge.rs
+// Fusion à trois voies des données de l'application
+// Utilisée quand sites.encrypted a été modifié sur le disque (client de
+// synchronisation, autre poste) depuis son chargement : les modifications
+// des deux côtés sont combinées site par site et champ par champ
+
+use crate::error::AppResult;
+use crate::storage::{zeroize_value, AppData};
+use crate::trash::TrashItem;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Champ modifié différemment sur le disque et dans l'application
+///
+/// Les valeurs sont `None` quand le champ (ou le site) est absent de la version
+/// concernée : un site supprimé d'un côté et modifié de l'autre est un conflit
+/// sur le champ `""` (site entier).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldConflict {
+    /// Site concerné (`None` pour les paramètres)
+    pub site_id: Option<String>,
+
+    /// Chemin du champ (ex: "server.mysql_host", "settings.auto_lock_minutes")
+    pub field: String,
+
+    /// Valeur au chargement
+    pub base: Option<Value>,
+
+    /// Valeur actuellement sur le disque
+    pub disk: Option<Value>,
+
+    /// Valeur dans l'application (retenue dans la proposition de fusion)
+    pub local: Option<Value>,
+}
+
+impl Drop for FieldConflict {
+    fn drop(&mut self) {
+        // Les valeurs sont des données déchiffrées
+        for value in [&mut self.base, &mut self.disk, &mut self.local]
+            .into_iter()
+            .flatten()
+        {
+            zeroize_value(value);
+        }
+    }
+}
+
+/// Résultat d'une fusion à trois voies
+#[derive(Debug)]
+pub struct MergeResult {
+    /// Données fusionnées ; pour chaque conflit, la valeur de l'application est retenue
+    pub merged: AppData,
+
+    /// Champs à arbitrer par l'utilisateur
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// Fusionne les modifications du disque et de l'application depuis `base`
+///
+/// Les sites sont associés par `id`. Un champ modifié d'un seul côté prend la
+/// nouvelle valeur ; un champ modifié des deux côtés avec des valeurs différentes
+/// est un conflit. Les listes (checklist, interventions…) sont comparées en bloc.
+///
+/// # Arguments
+/// * `base` - Données telles qu'elles ont été chargées
+/// * `disk` - Données actuellement sur le disque
+/// * `local` - Données modifiées dans l'application
+pub fn three_way_merge(base: &AppData, disk: &AppData, local: &AppData) -> AppResult<MergeResult> {
+    let mut values = [
+        serde_json::to_value(base)?,
+        serde_json::to_value(disk)?,
+        serde_json::to_value(local)?,
+    ];
+    let mut result = merge_values(&values, local.schema_version);
+    if let Ok(result) = result.as_mut() {
+        result.merged.trash = merge_trash(base, disk, local);
+    }
+
+    // Les représentations intermédiaires contiennent les données en clair
+    values.iter_mut().for_each(zeroize_value);
+    result
+}
+
+/// Corbeille fusionnée : les éléments présents des deux côtés ou ajoutés d'un
+/// seul côté ; un élément restauré ou purgé d'un côté en disparaît
+fn merge_trash(base: &AppData, disk: &AppData, local: &AppData) -> Vec<TrashItem> {
+    let contains = |items: &[TrashItem], id: &str| items.iter().any(|item| item.id == id);
+    let mut trash: Vec<TrashItem> = local
+        .trash
+        .iter()
+        .filter(|item| contains(&disk.trash, &item.id) || !contains(&base.trash, &item.id))
+        .cloned()
+        .collect();
+    trash.extend(
+        disk.trash
+            .iter()
+            .filter(|item| !contains(&local.trash, &item.id) && !contains(&base.trash, &item.id))
+            .cloned(),
+    );
+    trash
+}
+
+fn merge_values(values: &[Value; 3], schema_version: u32) -> AppResult<MergeResult> {
+    let [base, disk, local] = values;
+    let mut conflicts = Vec::new();
+
+    let base_sites = sites_by_id(base);
+    let disk_sites = sites_by_id(disk);
+    let local_sites = sites_by_id(local);
+
+    // Ordre de l'application, puis sites ajoutés sur le disque
+    let mut ids: Vec<&str> = local_sites.iter().map(|(id, _)| *id).collect();
+    for (id, _) in &disk_sites {
+        if !ids.contains(id) {
+            ids.push(id);
+        }
+    }
+    for (id, _) in &base_sites {
+        if !ids.contains(id) {
+            ids.push(id);
+        }
+    }
+
+    let mut sites = Vec::new();
+    for id in ids {
+        let merged = merge_field(
+            Some(id),
+            "",
+            find(&base_sites, id),
+            find(&disk_sites, id),
+            find(&local_sites, id),
+            &mut conflicts,
+        );
+        if let Some(site) = merged {
+            sites.push(serde_json::from_value(site)?);
+        }
+    }
+
+    let settings = merge_field(
+        None,
+        "settings",
+        base.get("settings"),
+        disk.get("settings"),
+        local.get("settings"),
+        &mut conflicts,
+    )
+    .unwrap_or(Value::Null);
+
+    Ok(MergeResult {
+        merged: AppData {
+            schema_version,
+            sites,
+            settings: serde_json::from_value(settings)?,
+            trash: Vec::new(),
+        },
+        conflicts,
+    })
+}
+
+/// Sites d'une version, indexés par identifiant
+pub(crate) fn sites_by_id(data: &Value) -> Vec<(&str, &Value)> {
+    data.get("sites")
+        .and_then(Value::as_array)
+        .map(|sites| {
+            sites
+                .iter()
+                .filter_map(|site| Some((site.get("id")?.as_str()?, site)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn find<'a>(sites: &[(&str, &'a Value)], id: &str) -> Option<&'a Value> {
+    sites
+        .iter()
+        .find(|(site_id, _)| *site_id == id)
+        .map(|(_, site)| *site)
+}
+
+/// Fusionne un champ (`None` = absent ou supprimé)
+fn merge_field(
+    site_id: Option<&str>,
+    path: &str,
+    base: Option<&Value>,
+    disk: Option<&Value>,
+    local: Option<&Value>,
+    conflicts: &mut Vec<FieldConflict>,
+) -> Option<Value> {
+    // Même valeur des deux côtés, ou modification d'un seul côté
+    if local == disk || disk == base {
+        return local.cloned();
+    }
+    if local == base {
+        return disk.cloned();
+    }
+
+    // Objets modifiés des deux côtés : fusion champ par champ
+    let empty = Map::new();
+    if let (Some(Value::Object(disk_map)), Some(Value::Object(local_map))) = (disk, local) {
+        let base_map = match base {
+            Some(Value::Object(map)) => map,
+            _ => &empty,
+        };
+
+        let mut keys: Vec<&String> = local_map.keys().collect();
+        keys.extend(disk_map.keys().filter(|key| !local_map.contains_key(*key)));
+
+        let mut merged = Map::new();
+        for key in keys {
+            let field_path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            let value = merge_field(
+                site_id,
+                &field_path,
+                base_map.get(key),
+                disk_map.get(key),
+                local_map.get(key),
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    conflicts.push(FieldConflict {
+        site_id: site_id.map(str::to_string),
+        field: path.to_string(),
+        base: base.cloned(),
+        disk: disk.cloned(),
+        local: local.cloned(),
+    });
+    local.cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Site;
+    use crate::trash;
+    use serde_json::json;
+
+    fn data(sites: Vec<Site>) -> AppData {
+        let mut data = AppData::default();
+        data.sites = sites;
+        data
+    }
+
+    #[test]
+    fn test_non_conflicting_changes_are_merged() {
+        let base = data(vec![
+            Site::sample("a", "Site A"),
+            Site::sample("b", "Site B"),
+        ]);
+
+        // Disque : un collègue change le serveur de A et ajoute C
+        let mut disk = base.clone();
+        disk.sites[0].server.mysql_host = "db.exemple.fr".to_string();
+        disk.sites.push(Site::sample("c", "Site C"));
+
+        // Application : on renomme A et on met B à la corbeille
+        let mut local = base.clone();
+        local.sites[0].name = "Site A (renommé)".to_string();
+        trash::trash_site(&mut local, "b").unwrap();
+        local.settings.auto_lock_minutes = 10;
+
+        let result = three_way_merge(&base, &disk, &local).unwrap();
+        assert!(result.conflicts.is_empty());
+
+        let merged = &result.merged;
+        let ids: Vec<&str> = merged.sites.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(merged.sites[0].name, "Site A (renommé)");
+        assert_eq!(merged.sites[0].server.mysql_host, "db.exemple.fr");
+        assert_eq!(merged.settings.auto_lock_minutes, 10);
+        assert_eq!(merged.trash.len(), 1);
+    }
+
+    #[test]
+    fn test_conflicts_are_reported_per_field() {
+        let base = data(vec![
+            Site::sample("a", "Site A"),
+            Site::sample("b", "Site B"),
+        ]);
+
+        let mut disk = base.clone();
+        disk.sites[0].notes = "version du disque".to_string();
+        disk.sites[0].tech.php_version = "8.2".to_string();
+        disk.sites[1].notes = "modifié sur le disque".to_string();
+
+        let mut local = base.clone();
+        local.sites[0].notes = "version locale".to_string();
+        local.sites.remove(1);
+
+        let result = three_way_merge(&base, &disk, &local).unwrap();
+        assert_eq!(result.conflicts.len(), 2);
+
+        // Même champ modifié des deux côtés
+        let notes = &result.conflicts[0];
+        assert_eq!(notes.site_id.as_deref(), Some("a"));
+        assert_eq!(notes.field, "notes");
+        assert_eq!(notes.disk, Some(json!("version du disque")));
+        assert_eq!(notes.local, Some(json!("version locale")));
+
+        // Site supprimé ici mais modifié sur le disque
+        let deleted = &result.conflicts[1];
+        assert_eq!(deleted.site_id.as_deref(), Some("b"));
+        assert_eq!(deleted.field, "");
+        assert!(deleted.local.is_none() && deleted.disk.is_some());
+
+        // La proposition garde la version locale des conflits, et le reste fusionné
+        assert_eq!(result.merged.sites.len(), 1);
+        assert_eq!(result.merged.sites[0].notes, "version locale");
+        assert_eq!(result.merged.sites[0].tech.php_version, "8.2");
+    }
+}
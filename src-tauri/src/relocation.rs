@@ -0,0 +1,283 @@
+// src-tauri/src/relocation.rs
+// Déplacement du dossier de données
+// Le coffre, les backups, le journal et le suivi des tentatives sont copiés
+// puis relus et comparés (SHA-256) avant que quoi que ce soit ne pointe vers
+// le nouvel emplacement ; en cas d'échec, les copies sont supprimées
+
+use crate::atomic::{self, TEMP_SUFFIX};
+use crate::error::{AppError, AppResult};
+use crate::journal::JOURNAL_FILE_NAME;
+use crate::throttle::THROTTLE_FILE_NAME;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Fichier de données et dossier des backups, relatifs au dossier de données
+pub const DATA_FILE_NAME: &str = "sites.encrypted";
+pub const BACKUP_DIR_NAME: &str = "backups";
+
+/// Compte rendu d'un déplacement
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelocationReport {
+    /// Nouveau dossier de données
+    pub location: String,
+
+    /// Fichiers copiés et vérifiés
+    pub files: usize,
+
+    /// Taille totale copiée, en octets
+    pub bytes: u64,
+
+    /// Fichiers de l'ancien emplacement qui n'ont pas pu être supprimés
+    /// (vide si la suppression n'a pas été demandée ou a réussi)
+    pub leftovers: Vec<String>,
+}
+
+/// Copies faites vers le nouvel emplacement, supprimées par `rollback`
+#[derive(Debug)]
+pub struct CopiedFiles {
+    /// Fichiers copiés (chemins relatifs au dossier de données)
+    pub files: Vec<PathBuf>,
+
+    pub bytes: u64,
+
+    target: PathBuf,
+
+    /// Dossiers créés pour la copie
+    created_dirs: Vec<PathBuf>,
+}
+
+impl CopiedFiles {
+    /// Supprime les copies (le nouvel emplacement retrouve son état initial)
+    pub fn rollback(self) {
+        for file in &self.files {
+            let _ = fs::remove_file(self.target.join(file));
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Vérifie que `target` peut accueillir les données de `source`
+///
+/// # Errors
+/// `InvalidInput` si le dossier n'existe pas, est l'emplacement actuel, est
+/// situé dans le dossier des backups ou contient déjà un coffre
+pub fn check_target(source: &Path, target: &Path) -> AppResult<()> {
+    if !target.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Le dossier '{}' n'existe pas",
+            target.display()
+        )));
+    }
+
+    let source = source.canonicalize()?;
+    let target = target.canonicalize()?;
+    if source == target {
+        return Err(AppError::InvalidInput(
+            "Le nouvel emplacement est identique à l'ancien".to_string(),
+        ));
+    }
+    if target.starts_with(source.join(BACKUP_DIR_NAME)) {
+        return Err(AppError::InvalidInput(
+            "Le nouvel emplacement ne peut pas être dans le dossier des backups".to_string(),
+        ));
+    }
+    if target.join(DATA_FILE_NAME).exists() {
+        return Err(AppError::InvalidInput(format!(
+            "Le dossier '{}' contient déjà un coffre",
+            target.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Fichiers à déplacer, relatifs au dossier de données
+///
+/// Le verrou et les fichiers temporaires restent sur place.
+pub fn files_to_move(source: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for name in [DATA_FILE_NAME, JOURNAL_FILE_NAME, THROTTLE_FILE_NAME] {
+        if source.join(name).is_file() {
+            files.push(PathBuf::from(name));
+        }
+    }
+
+    let backups = source.join(BACKUP_DIR_NAME);
+    if backups.is_dir() {
+        for entry in fs::read_dir(&backups)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let is_temp = name.to_str().is_some_and(|n| n.ends_with(TEMP_SUFFIX));
+            if entry.file_type()?.is_file() && !is_temp {
+                files.push(Path::new(BACKUP_DIR_NAME).join(name));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Copie les fichiers et vérifie chaque copie en la relisant
+///
+/// Le dossier des backups est toujours créé. En cas d'échec, les copies
+/// déjà faites sont supprimées.
+///
+/// # Errors
+/// `InvalidInput` si l'un des fichiers existe déjà dans `target` (rien n'est
+/// copié : la suppression des copies ne doit toucher que des fichiers créés)
+pub fn copy_verified(source: &Path, target: &Path, files: &[PathBuf]) -> AppResult<CopiedFiles> {
+    if let Some(existing) = files.iter().find(|file| target.join(file).exists()) {
+        return Err(AppError::InvalidInput(format!(
+            "Le dossier '{}' contient déjà {}",
+            target.display(),
+            existing.display()
+        )));
+    }
+
+    let mut copied = CopiedFiles {
+        files: Vec::new(),
+        bytes: 0,
+        target: target.to_path_buf(),
+        created_dirs: Vec::new(),
+    };
+
+    let result = (|| {
+        let backups = target.join(BACKUP_DIR_NAME);
+        if !backups.exists() {
+            fs::create_dir(&backups)?;
+            copied.created_dirs.push(backups);
+        }
+
+        for file in files {
+            let destination = target.join(file);
+            let content = fs::read(source.join(file))?;
+            atomic::write_atomic(&destination, &content)?;
+            copied.files.push(file.clone());
+
+            if Sha256::digest(fs::read(&destination)?) != Sha256::digest(&content) {
+                return Err(AppError::CorruptVault(format!(
+                    "copie de {} différente de l'original",
+                    file.display()
+                )));
+            }
+            copied.bytes += content.len() as u64;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => Ok(copied),
+        Err(e) => {
+            copied.rollback();
+            Err(e)
+        }
+    }
+}
+
+/// Supprime les fichiers de l'ancien emplacement après les avoir écrasés
+///
+/// L'écrasement (par des zéros) est fait au mieux : sur un SSD ou un système de fichiers
+/// copy-on-write, l'ancien contenu peut subsister physiquement (il reste
+/// chiffré). Le dossier des backups est supprimé s'il est vide.
+///
+/// # Returns
+/// Les fichiers qui n'ont pas pu être supprimés
+pub fn remove_source(source: &Path, files: &[PathBuf]) -> Vec<String> {
+    let mut leftovers = Vec::new();
+    for file in files {
+        let path = source.join(file);
+        if overwrite_with_zeros(&path)
+            .and_then(|_| fs::remove_file(&path))
+            .is_err()
+        {
+            leftovers.push(file.display().to_string());
+        }
+    }
+    // Échoue si le dossier contient encore autre chose
+    let _ = fs::remove_dir(source.join(BACKUP_DIR_NAME));
+    leftovers
+}
+
+fn overwrite_with_zeros(path: &Path) -> io::Result<()> {
+    let len = fs::metadata(path)?.len() as usize;
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.write_all(&vec![0u8; len])?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_copy_verify_and_rollback() {
+        let temp_dir = env::temp_dir().join("cockpit_relocation_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let source = temp_dir.join("ancien");
+        let target = temp_dir.join("nouveau");
+        fs::create_dir_all(source.join(BACKUP_DIR_NAME)).unwrap();
+        fs::create_dir_all(&target).unwrap();
+
+        fs::write(source.join(DATA_FILE_NAME), b"coffre").unwrap();
+        fs::write(source.join("sites.lock"), b"verrou").unwrap();
+        fs::write(
+            source.join(BACKUP_DIR_NAME).join("sites-1.encrypted"),
+            b"b1",
+        )
+        .unwrap();
+        fs::write(
+            source
+                .join(BACKUP_DIR_NAME)
+                .join(format!(".x{}", TEMP_SUFFIX)),
+            b"reste",
+        )
+        .unwrap();
+
+        assert!(check_target(&source, &source).is_err());
+        assert!(check_target(&source, &source.join(BACKUP_DIR_NAME)).is_err());
+        assert!(check_target(&source, &temp_dir.join("absent")).is_err());
+        check_target(&source, &target).unwrap();
+
+        // Le verrou et les fichiers temporaires ne sont pas déplacés
+        let files = files_to_move(&source).unwrap();
+        assert_eq!(files.len(), 2);
+
+        // Échec après la copie : le nouvel emplacement est remis en l'état
+        let copied = copy_verified(&source, &target, &files).unwrap();
+        assert_eq!(copied.bytes, 8);
+        assert_eq!(fs::read(target.join(DATA_FILE_NAME)).unwrap(), b"coffre");
+        copied.rollback();
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 0);
+
+        // Fichier déjà présent à l'arrivée : refusé, sans rien écraser ni supprimer
+        let existing = target.join(BACKUP_DIR_NAME).join("sites-1.encrypted");
+        fs::create_dir_all(target.join(BACKUP_DIR_NAME)).unwrap();
+        fs::write(&existing, b"autre").unwrap();
+        assert!(matches!(
+            copy_verified(&source, &target, &files),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!(fs::read(&existing).unwrap(), b"autre");
+        assert!(!target.join(DATA_FILE_NAME).exists());
+        fs::remove_dir_all(target.join(BACKUP_DIR_NAME)).unwrap();
+
+        // Un fichier source manquant fait échouer la copie sans rien laisser
+        let missing = [files[0].clone(), PathBuf::from("absent.encrypted")];
+        assert!(copy_verified(&source, &target, &missing).is_err());
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 0);
+
+        // Déplacement complet : la source est supprimée, sauf le verrou
+        copy_verified(&source, &target, &files).unwrap();
+        assert!(check_target(&source, &target).is_err());
+        assert!(remove_source(&source, &files).is_empty());
+        assert!(!source.join(DATA_FILE_NAME).exists());
+        assert!(source.join("sites.lock").exists());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -0,0 +1,353 @@
+// src-tauri/src/restore.rs
+// Restauration sélective depuis un backup
+// Plutôt que de remplacer tout le coffre, on reprend un ou plusieurs sites
+// d'un backup (ou seulement certaines de leurs listes) sans perdre les
+// modifications faites depuis sur les autres sites
+
+use crate::error::{AppError, AppResult};
+use crate::storage::{AppData, Site};
+use serde::{Deserialize, Serialize};
+
+/// Listes d'un site pouvant être restaurées séparément
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SiteCollection {
+    Interventions,
+    Extensions,
+    Checklist,
+    Contacts,
+    JoomlaAccounts,
+}
+
+/// Conduite à tenir quand le site existe déjà dans le coffre
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Remplacer le site (ou la liste) actuel par celui du backup
+    Overwrite,
+
+    /// Garder le site actuel ; pour une liste, n'ajouter que les éléments absents
+    Skip,
+
+    /// Restaurer le site du backup sous un nouvel identifiant ; pour une
+    /// liste, n'ajouter que les éléments absents
+    Rename,
+}
+
+/// Élément à restaurer
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SiteSelection {
+    /// Site du backup
+    pub site_id: String,
+
+    /// Listes à restaurer (`None` : le site entier). Un site absent du
+    /// coffre est toujours restauré en entier.
+    #[serde(default)]
+    pub collections: Option<Vec<SiteCollection>>,
+}
+
+/// Site d'un backup, tel que présenté avant la restauration
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupSiteSummary {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub interventions: usize,
+    pub extensions: usize,
+    pub checklist: usize,
+    pub contacts: usize,
+    pub joomla_accounts: usize,
+
+    /// Un site de même identifiant existe dans le coffre actuel
+    pub exists_in_vault: bool,
+}
+
+/// Site restauré sous un nouvel identifiant
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct RenamedSite {
+    pub from: String,
+    pub to: String,
+}
+
+/// Résultat d'une restauration sélective
+#[derive(Serialize, Debug)]
+pub struct RestoreReport {
+    /// Données du coffre après restauration
+    pub data: AppData,
+
+    /// Sites écrits (ajoutés, remplacés ou complétés)
+    pub restored: Vec<String>,
+
+    /// Sites laissés tels quels (déjà présents, stratégie `skip`)
+    pub skipped: Vec<String>,
+
+    /// Sites ajoutés sous un nouvel identifiant
+    pub renamed: Vec<RenamedSite>,
+}
+
+/// Sites d'un backup, avec leur présence dans le coffre actuel
+pub fn summarize(backup: &AppData, current: &AppData) -> Vec<BackupSiteSummary> {
+    backup
+        .sites
+        .iter()
+        .map(|site| BackupSiteSummary {
+            id: site.id.clone(),
+            name: site.name.clone(),
+            enabled: site.enabled,
+            interventions: site.interventions.len(),
+            extensions: site.extensions.len(),
+            checklist: site.checklist.len(),
+            contacts: site.contacts.len(),
+            joomla_accounts: site.joomla_accounts.len(),
+            exists_in_vault: current.sites.iter().any(|s| s.id == site.id),
+        })
+        .collect()
+}
+
+/// Applique une restauration sélective aux données du coffre
+///
+/// # Arguments
+/// * `current` - Données actuelles (non modifiées : le résultat est dans le rapport)
+/// * `backup` - Données du backup
+/// * `selections` - Sites (ou listes de sites) à restaurer
+/// * `strategy` - Conduite à tenir pour un site déjà présent
+///
+/// # Errors
+/// `InvalidInput` si un site demandé n'existe pas dans le backup
+pub fn restore_sites(
+    current: &AppData,
+    backup: &AppData,
+    selections: &[SiteSelection],
+    strategy: ConflictStrategy,
+) -> AppResult<RestoreReport> {
+    let mut report = RestoreReport {
+        data: current.clone(),
+        restored: Vec::new(),
+        skipped: Vec::new(),
+        renamed: Vec::new(),
+    };
+
+    for selection in selections {
+        let source = backup
+            .sites
+            .iter()
+            .find(|site| site.id == selection.site_id)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("Site absent du backup : {}", selection.site_id))
+            })?;
+        let existing = report.data.sites.iter().position(|s| s.id == source.id);
+
+        match (existing, &selection.collections) {
+            (None, _) => {
+                report.data.sites.push(source.clone());
+                report.restored.push(source.id.clone());
+            }
+            (Some(index), Some(collections)) => {
+                let target = &mut report.data.sites[index];
+                for collection in collections {
+                    restore_collection(target, source, *collection, strategy);
+                }
+                report.restored.push(source.id.clone());
+            }
+            (Some(index), None) => match strategy {
+                ConflictStrategy::Overwrite => {
+                    report.data.sites[index] = source.clone();
+                    report.restored.push(source.id.clone());
+                }
+                ConflictStrategy::Skip => report.skipped.push(source.id.clone()),
+                ConflictStrategy::Rename => {
+                    let mut site = source.clone();
+                    site.id = unique_id(&report.data, &source.id);
+                    site.name = format!("{} (restauré)", source.name);
+                    report.renamed.push(RenamedSite {
+                        from: source.id.clone(),
+                        to: site.id.clone(),
+                    });
+                    report.data.sites.push(site);
+                }
+            },
+        }
+    }
+
+    Ok(report)
+}
+
+/// Restaure une liste du site du backup dans le site actuel
+fn restore_collection(
+    target: &mut Site,
+    source: &Site,
+    collection: SiteCollection,
+    strategy: ConflictStrategy,
+) {
+    let overwrite = strategy == ConflictStrategy::Overwrite;
+    match collection {
+        SiteCollection::Interventions => {
+            merge_list(&mut target.interventions, &source.interventions, overwrite)
+        }
+        SiteCollection::Extensions => {
+            merge_list(&mut target.extensions, &source.extensions, overwrite)
+        }
+        SiteCollection::Checklist => {
+            merge_list(&mut target.checklist, &source.checklist, overwrite)
+        }
+        SiteCollection::Contacts => merge_list(&mut target.contacts, &source.contacts, overwrite),
+        SiteCollection::JoomlaAccounts => merge_list(
+            &mut target.joomla_accounts,
+            &source.joomla_accounts,
+            overwrite,
+        ),
+    }
+}
+
+/// Remplace la liste, ou ajoute les éléments du backup qui n'y sont pas
+fn merge_list<T: Clone + PartialEq>(target: &mut Vec<T>, source: &[T], overwrite: bool) {
+    if overwrite {
+        *target = source.to_vec();
+        return;
+    }
+    for item in source {
+        if !target.contains(item) {
+            target.push(item.clone());
+        }
+    }
+}
+
+/// Identifiant libre dérivé de `id` (ex: "cfdt-ulogistique-restaure-2")
+fn unique_id(data: &AppData, id: &str) -> String {
+    let base = format!("{}-restaure", id);
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while data.sites.iter().any(|site| site.id == candidate) {
+        candidate = format!("{}-{}", base, n);
+        n += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Intervention;
+
+    fn data(sites: Vec<Site>) -> AppData {
+        let mut data = AppData::default();
+        data.sites = sites;
+        data
+    }
+
+    fn intervention(date: &str) -> Intervention {
+        Intervention {
+            date: date.to_string(),
+            type_intervention: "Mise à jour".to_string(),
+            description: String::new(),
+            duration: "1h".to_string(),
+            result: "OK".to_string(),
+        }
+    }
+
+    fn whole(site_id: &str) -> SiteSelection {
+        SiteSelection {
+            site_id: site_id.to_string(),
+            collections: None,
+        }
+    }
+
+    #[test]
+    fn test_restore_sites_with_conflict_strategies() {
+        let mut old_a = Site::sample("a", "Site A");
+        old_a.notes = "backup".to_string();
+        let backup = data(vec![old_a, Site::sample("b", "Site B")]);
+
+        // Depuis le backup : B supprimé par erreur, A modifié, C ajouté
+        let mut new_a = Site::sample("a", "Site A");
+        new_a.notes = "actuel".to_string();
+        let current = data(vec![new_a, Site::sample("c", "Site C")]);
+
+        let summary = summarize(&backup, &current);
+        assert!(summary[0].exists_in_vault && !summary[1].exists_in_vault);
+
+        let selections = [whole("a"), whole("b")];
+
+        // Skip : seul le site supprimé revient, le reste est intact
+        let report = restore_sites(&current, &backup, &selections, ConflictStrategy::Skip).unwrap();
+        assert_eq!(report.restored, vec!["b"]);
+        assert_eq!(report.skipped, vec!["a"]);
+        let ids: Vec<&str> = report.data.sites.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c", "b"]);
+        assert_eq!(report.data.sites[0].notes, "actuel");
+
+        // Overwrite : A reprend la version du backup
+        let report =
+            restore_sites(&current, &backup, &selections, ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(report.restored, vec!["a", "b"]);
+        assert_eq!(report.data.sites[0].notes, "backup");
+
+        // Rename : les deux versions de A coexistent
+        let report =
+            restore_sites(&current, &backup, &selections, ConflictStrategy::Rename).unwrap();
+        assert_eq!(
+            report.renamed,
+            vec![RenamedSite {
+                from: "a".to_string(),
+                to: "a-restaure".to_string()
+            }]
+        );
+        assert_eq!(report.data.sites.len(), 4);
+        let copy = report
+            .data
+            .sites
+            .iter()
+            .find(|s| s.id == "a-restaure")
+            .unwrap();
+        assert_eq!(copy.name, "Site A (restauré)");
+        assert_eq!(copy.notes, "backup");
+
+        // Site inconnu du backup
+        assert!(matches!(
+            restore_sites(&current, &backup, &[whole("z")], ConflictStrategy::Skip),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_single_collection() {
+        let mut old = Site::sample("a", "Site A");
+        old.interventions = vec![intervention("2024-01-10"), intervention("2024-02-14")];
+        old.notes = "backup".to_string();
+        let backup = data(vec![old]);
+
+        // Une intervention supprimée par erreur, une autre ajoutée depuis
+        let mut site = Site::sample("a", "Site A");
+        site.interventions = vec![intervention("2024-02-14"), intervention("2024-03-20")];
+        site.notes = "actuel".to_string();
+        let current = data(vec![site]);
+
+        let selection = SiteSelection {
+            site_id: "a".to_string(),
+            collections: Some(vec![SiteCollection::Interventions]),
+        };
+
+        let report = restore_sites(
+            &current,
+            &backup,
+            std::slice::from_ref(&selection),
+            ConflictStrategy::Skip,
+        )
+        .unwrap();
+        let restored = &report.data.sites[0];
+        let dates: Vec<&str> = restored
+            .interventions
+            .iter()
+            .map(|i| i.date.as_str())
+            .collect();
+        assert_eq!(dates, vec!["2024-02-14", "2024-03-20", "2024-01-10"]);
+        // Les autres champs du site ne sont pas touchés
+        assert_eq!(restored.notes, "actuel");
+
+        let report =
+            restore_sites(&current, &backup, &[selection], ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(report.data.sites[0].interventions.len(), 2);
+        assert_eq!(report.data.sites[0].interventions[0].date, "2024-01-10");
+        assert_eq!(report.data.sites[0].notes, "actuel");
+    }
+}
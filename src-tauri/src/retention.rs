@@ -0,0 +1,212 @@
+// src-tauri/src/retention.rs
+// Politique de rétention des backups
+// Tous les backups des derniers jours (backup_keep_days) sont conservés, puis
+// ils sont éclaircis : un par jour, un par semaine, et enfin un par mois
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Secondes dans un jour
+pub(crate) const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Durée de la période « un backup par jour » après la période complète
+const DAILY_DAYS: u32 = 30;
+
+/// Âge au-delà duquel on ne garde plus qu'un backup par mois
+const WEEKLY_UNTIL_DAYS: u32 = 365;
+
+/// Fichier de backup soumis à la rétention
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupFile {
+    /// Nom du fichier dans le dossier backups
+    pub name: String,
+
+    /// Date du backup (timestamp Unix)
+    pub timestamp: u64,
+
+    /// Taille en octets
+    pub size: u64,
+}
+
+/// Paliers de rétention, exprimés en âge (jours)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Tous les backups plus récents sont conservés
+    pub keep_all_days: u32,
+
+    /// Jusqu'à cet âge : le plus récent de chaque jour
+    pub daily_until_days: u32,
+
+    /// Jusqu'à cet âge : le plus récent de chaque semaine ; au-delà, de chaque mois
+    pub weekly_until_days: u32,
+}
+
+impl RetentionPolicy {
+    /// Politique déduite du réglage `backup_keep_days`
+    pub fn from_keep_days(keep_days: u32) -> Self {
+        let daily_until_days = keep_days.saturating_add(DAILY_DAYS);
+        Self {
+            keep_all_days: keep_days,
+            daily_until_days,
+            weekly_until_days: WEEKLY_UNTIL_DAYS.max(daily_until_days),
+        }
+    }
+}
+
+/// Résultat de l'application de la politique (aperçu ou suppression effectuée)
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetentionPlan {
+    /// Backups conservés, du plus récent au plus ancien
+    pub keep: Vec<BackupFile>,
+
+    /// Backups à supprimer (ou supprimés), du plus récent au plus ancien
+    pub delete: Vec<BackupFile>,
+
+    /// Espace libéré par la suppression, en octets
+    pub delete_bytes: u64,
+}
+
+/// Période à laquelle un backup appartient ; seul le plus récent de chaque
+/// période est conservé
+#[derive(Hash, PartialEq, Eq)]
+enum Bucket {
+    Day(u64),
+    Week(u64),
+    Month(i64, u32),
+}
+
+/// Répartit les backups entre ceux à conserver et ceux à supprimer
+///
+/// # Arguments
+/// * `backups` - Backups existants, dans n'importe quel ordre
+/// * `now` - Date de référence (timestamp Unix)
+/// * `policy` - Paliers de rétention
+pub fn plan(backups: &[BackupFile], now: u64, policy: &RetentionPolicy) -> RetentionPlan {
+    let mut sorted = backups.to_vec();
+    sorted.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.name.cmp(&a.name)));
+
+    let mut seen = HashSet::new();
+    let mut result = RetentionPlan::default();
+
+    for backup in sorted {
+        // Un backup daté du futur (horloge d'un autre poste) est considéré récent
+        let age_days = now.saturating_sub(backup.timestamp) / DAY_SECS;
+        let day = backup.timestamp / DAY_SECS;
+
+        let keep = if age_days < u64::from(policy.keep_all_days) {
+            true
+        } else {
+            let bucket = if age_days < u64::from(policy.daily_until_days) {
+                Bucket::Day(day)
+            } else if age_days < u64::from(policy.weekly_until_days) {
+                // Semaines commençant le lundi (le 1er janvier 1970 était un jeudi)
+                Bucket::Week((day + 3) / 7)
+            } else {
+                let (year, month) = year_month(day);
+                Bucket::Month(year, month)
+            };
+            // Trié du plus récent au plus ancien : le premier de la période est gardé
+            seen.insert(bucket)
+        };
+
+        if keep {
+            result.keep.push(backup);
+        } else {
+            result.delete_bytes += backup.size;
+            result.delete.push(backup);
+        }
+    }
+
+    result
+}
+
+/// Année et mois (1-12) d'un jour compté depuis le 1er janvier 1970
+fn year_month(days: u64) -> (i64, u32) {
+    let (year, month, _) = civil_from_days(days);
+    (year, month)
+}
+
+/// Année, mois (1-12) et jour (1-31) d'un jour compté depuis le 1er janvier 1970
+///
+/// Algorithme `civil_from_days` de Howard Hinnant.
+pub(crate) fn civil_from_days(days: u64) -> (i64, u32, u32) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 15 juin 2024 à midi (UTC)
+    const NOW: u64 = 1_718_452_800;
+
+    fn backup(age_secs: u64) -> BackupFile {
+        let timestamp = NOW - age_secs;
+        BackupFile {
+            name: format!("sites-{}.encrypted", timestamp),
+            timestamp,
+            size: 100,
+        }
+    }
+
+    #[test]
+    fn test_year_month() {
+        assert_eq!(year_month(0), (1970, 1));
+        assert_eq!(year_month(NOW / DAY_SECS), (2024, 6));
+        // 29 février puis 1er mars 2024
+        assert_eq!(year_month(19_782), (2024, 2));
+        assert_eq!(year_month(19_783), (2024, 3));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_plan_thins_older_backups() {
+        let policy = RetentionPolicy::from_keep_days(7);
+        let hour = 60 * 60;
+
+        let mut backups = Vec::new();
+        // Dernière semaine : toutes les heures, tout est gardé
+        backups.extend((0..7 * 24).map(|h| backup(h * hour)));
+        // Jours 10 et 11 : trois backups chacun, un seul gardé par jour
+        for day in [10, 11] {
+            backups.extend((0..3).map(|h| backup(day * DAY_SECS + h * hour)));
+        }
+        // Dans l'année : tous les jours de 60 à 73, soit deux semaines complètes
+        backups.extend((60..74).map(|day| backup(day * DAY_SECS)));
+        // Au-delà d'un an : chaque semaine pendant environ deux mois
+        backups.extend((0..9).map(|week| backup((400 + week * 7) * DAY_SECS)));
+
+        let result = plan(&backups, NOW, &policy);
+
+        assert_eq!(result.keep.len() + result.delete.len(), backups.len());
+        assert_eq!(result.delete_bytes, 100 * result.delete.len() as u64);
+
+        let kept = |from: u64, to: u64| {
+            result
+                .keep
+                .iter()
+                .filter(|b| (from..to).contains(&((NOW - b.timestamp) / DAY_SECS)))
+                .count()
+        };
+        assert_eq!(kept(0, 7), 7 * 24);
+        assert_eq!(kept(10, 12), 2);
+        assert!((2..=3).contains(&kept(60, 74)));
+        assert!((2..=3).contains(&kept(400, 464)));
+
+        // Le plus récent de chaque jour est celui conservé
+        assert!(result.keep.contains(&backup(10 * DAY_SECS)));
+        assert!(result.delete.contains(&backup(10 * DAY_SECS + 2 * hour)));
+
+        // Le plan est stable : l'appliquer à nouveau ne supprime rien de plus
+        assert!(plan(&result.keep, NOW, &policy).delete.is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+// src-tauri/src/site_edit.rs
+// Modifications ciblées des sites
+// Chaque commande (ajout d'un site, d'une intervention, case de checklist…)
+// ne touche que l'élément concerné, après validation des champs côté backend
+
+use crate::error::{AppError, AppResult, FieldError};
+use crate::retention::{civil_from_days, DAY_SECS};
+use crate::storage::{zeroize_value, AppData, Intervention, Site};
+use std::net::Ipv6Addr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Vérifie un site avant de l'enregistrer
+///
+/// # Arguments
+/// * `site` - Site à enregistrer
+/// * `data` - Données actuelles, pour l'unicité de l'identifiant
+/// * `previous_id` - Identifiant actuel du site modifié (`None` pour un ajout)
+///
+/// # Errors
+/// `Validation`, avec la raison pour chaque champ refusé
+pub fn validate_site(site: &Site, data: &AppData, previous_id: Option<&str>) -> AppResult<()> {
+    let mut errors = Vec::new();
+
+    // Un identifiant existant reste accepté tel quel, même s'il précède ces règles
+    if previous_id != Some(site.id.as_str()) {
+        if !is_slug(&site.id) {
+            errors.push(FieldError::new(
+                "id",
+                "Lettres minuscules, chiffres et tirets uniquement",
+            ));
+        } else if data.sites.iter().any(|s| s.id == site.id) {
+            errors.push(FieldError::new("id", "Identifiant déjà utilisé"));
+        }
+    }
+    if site.name.trim().is_empty() {
+        errors.push(FieldError::new("name", "Nom obligatoire"));
+    }
+
+    if let Err(message) = check_url(&site.urls.frontend) {
+        errors.push(FieldError::new("urls.frontend", message));
+    }
+    // Le backend peut être un chemin relatif au frontend (ex: "/administrator")
+    if !site.urls.backend.starts_with('/') {
+        if let Err(message) = check_url(&site.urls.backend) {
+            errors.push(FieldError::new("urls.backend", message));
+        }
+    }
+    if !site.urls.phpmyadmin.is_empty() {
+        if let Err(message) = check_url(&site.urls.phpmyadmin) {
+            errors.push(FieldError::new("urls.phpmyadmin", message));
+        }
+    }
+    if let Some(url) = site
+        .analytics
+        .as_ref()
+        .and_then(|a| a.looker_report_url.as_deref())
+        .filter(|url| !url.is_empty())
+    {
+        if let Err(message) = check_url(url) {
+            errors.push(FieldError::new("analytics.looker_report_url", message));
+        }
+    }
+
+    if site.dashlane_refs.joomla_admin.trim().is_empty() {
+        errors.push(FieldError::new(
+            "dashlane_refs.joomla_admin",
+            "Référence Dashlane obligatoire",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors))
+    }
+}
+
+/// Vérifie une intervention avant de l'enregistrer
+pub fn validate_intervention(intervention: &Intervention) -> AppResult<()> {
+    let mut errors = Vec::new();
+    if !is_date(&intervention.date) {
+        errors.push(FieldError::new(
+            "date",
+            "Date attendue au format AAAA-MM-JJ",
+        ));
+    }
+    if intervention.type_intervention.trim().is_empty() {
+        errors.push(FieldError::new("type_intervention", "Type obligatoire"));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors))
+    }
+}
+
+/// Ajoute un site
+pub fn add_site(data: &mut AppData, mut site: Site) -> AppResult<()> {
+    validate_site(&site, data, None)?;
+    site.last_update = now_iso();
+    data.sites.push(site);
+    Ok(())
+}
+
+/// Remplace le site `site_id` (l'identifiant peut changer s'il reste unique)
+pub fn update_site(data: &mut AppData, site_id: &str, mut site: Site) -> AppResult<()> {
+    let index = site_index(data, site_id)?;
+    validate_site(&site, data, Some(site_id))?;
+    site.last_update = now_iso();
+    data.sites[index] = site;
+    Ok(())
+}
+
+/// Ajoute une intervention en tête du journal du site (plus récentes d'abord)
+pub fn add_intervention(
+    data: &mut AppData,
+    site_id: &str,
+    intervention: Intervention,
+) -> AppResult<()> {
+    validate_intervention(&intervention)?;
+    let site = site_mut(data, site_id)?;
+    site.interventions.insert(0, intervention);
+    site.last_update = now_iso();
+    Ok(())
+}
+
+/// Coche ou décoche une tâche de la checklist (datée du jour si cochée)
+///
+/// # Returns
+/// Le nouvel état de la tâche
+pub fn toggle_checklist_item(data: &mut AppData, site_id: &str, index: usize) -> AppResult<bool> {
+    let site = site_mut(data, site_id)?;
+    let item = site.checklist.get_mut(index).ok_or_else(|| {
+        AppError::InvalidInput(format!("Tâche introuvable : {} (site {})", index, site_id))
+    })?;
+    item.done = !item.done;
+    item.date = item.done.then(|| now_iso()[..10].to_string());
+    let done = item.done;
+    site.last_update = now_iso();
+    Ok(done)
+}
+
+/// Vérifie les sites ajoutés ou modifiés par une sauvegarde complète
+///
+/// Les sites inchangés ne sont pas revérifiés : des données antérieures à la
+/// validation restent enregistrables tant qu'on n'y touche pas.
+///
+/// # Arguments
+/// * `before` - Données du backend avant la sauvegarde
+/// * `after` - Données envoyées par le frontend
+///
+/// # Errors
+/// `Validation` pour le premier site refusé
+pub fn validate_changed_sites(before: &AppData, after: &AppData) -> AppResult<()> {
+    for site in &after.sites {
+        match before.sites.iter().find(|s| s.id == site.id) {
+            Some(old) if same_site(old, site)? => continue,
+            Some(_) => validate_site(site, after, Some(&site.id))?,
+            None => validate_site(site, before, None)?,
+        }
+        if after.sites.iter().filter(|s| s.id == site.id).count() > 1 {
+            return Err(AppError::Validation(vec![FieldError::new(
+                "id",
+                "Identifiant déjà utilisé",
+            )]));
+        }
+    }
+    Ok(())
+}
+
+/// Compare deux sites champ par champ
+fn same_site(a: &Site, b: &Site) -> AppResult<bool> {
+    let mut values = [serde_json::to_value(a)?, serde_json::to_value(b)?];
+    let same = values[0] == values[1];
+
+    // Les représentations intermédiaires contiennent les données en clair
+    values.iter_mut().for_each(zeroize_value);
+    Ok(same)
+}
+
+fn site_index(data: &AppData, site_id: &str) -> AppResult<usize> {
+    data.sites
+        .iter()
+        .position(|s| s.id == site_id)
+        .ok_or_else(|| AppError::InvalidInput(format!("Site introuvable : {}", site_id)))
+}
+
+fn site_mut<'a>(data: &'a mut AppData, site_id: &str) -> AppResult<&'a mut Site> {
+    let index = site_index(data, site_id)?;
+    Ok(&mut data.sites[index])
+}
+
+/// Identifiant de site : minuscules, chiffres et tirets (ex: "cfdt-ulogistique")
+fn is_slug(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('-')
+        && !id.ends_with('-')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Date existante au format AAAA-MM-JJ
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let number =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    if !matches!(parts.as_slice(), [y, m, d] if number(y, 4) && number(m, 2) && number(d, 2)) {
+        return false;
+    }
+    let [year, month, day] = [0, 1, 2].map(|i| parts[i].parse::<u32>().unwrap_or(0));
+    (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day)
+}
+
+/// Nombre de jours du mois `month` (1 à 12) de l'année `year`
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Vérifie une URL absolue http(s) avec un nom d'hôte
+fn check_url(url: &str) -> Result<(), &'static str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or("L'URL doit commencer par http:// ou https://")?;
+    if url.chars().any(char::is_whitespace) {
+        return Err("L'URL ne doit pas contenir d'espace");
+    }
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let is_port = |port: &str| !port.is_empty() && port.chars().all(|c| c.is_ascii_digit());
+
+    // Adresse IPv6 entre crochets (ex: http://[::1]:8080)
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        let (address, after) = bracketed.split_once(']').ok_or("Adresse IPv6 invalide")?;
+        if address.parse::<Ipv6Addr>().is_err() {
+            return Err("Adresse IPv6 invalide");
+        }
+        return match after.strip_prefix(':') {
+            None if after.is_empty() => Ok(()),
+            Some(port) if is_port(port) => Ok(()),
+            _ => Err("Port invalide"),
+        };
+    }
+
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if is_port(port) => host,
+        Some(_) => return Err("Port invalide"),
+        None => authority,
+    };
+    let valid_host = !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+    if valid_host {
+        Ok(())
+    } else {
+        Err("Nom de domaine invalide")
+    }
+}
+
+/// Date et heure actuelles au format ISO 8601 (UTC), comme le frontend
+fn now_iso() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs / DAY_SECS);
+    let time = secs % DAY_SECS;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::ChecklistItem;
+
+    fn fields(result: AppResult<()>) -> Vec<String> {
+        match result {
+            Err(AppError::Validation(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("validation attendue : {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_site_reports_each_field() {
+        let mut data = AppData::default();
+        add_site(&mut data, Site::sample("a", "Site A")).unwrap();
+        assert!(data.sites[0].last_update.ends_with('Z'));
+
+        let mut site = Site::sample("a", "Site A");
+        site.urls.frontend = "site-a.fr".to_string();
+        site.urls.backend = "https://site a.fr/administrator".to_string();
+        site.urls.phpmyadmin = "https://:8080".to_string();
+        site.dashlane_refs.joomla_admin = "  ".to_string();
+        assert_eq!(
+            fields(add_site(&mut data, site.clone())),
+            vec![
+                "id",
+                "urls.frontend",
+                "urls.backend",
+                "urls.phpmyadmin",
+                "dashlane_refs.joomla_admin"
+            ]
+        );
+
+        // Adresses IPv6 entre crochets
+        for url in [
+            "http://[::1]:8080/administrator",
+            "https://[2001:db8::1]",
+            "http://[::1]",
+        ] {
+            assert_eq!(check_url(url), Ok(()), "{url}");
+        }
+        for url in [
+            "http://[::1",
+            "http://[::g]",
+            "http://[::1]:",
+            "http://[::1]x",
+            "http://::1",
+        ] {
+            assert!(check_url(url).is_err(), "{url}");
+        }
+
+        // Le site garde son identifiant : pas de conflit avec lui-même
+        let mut site = Site::sample("a", "Site A renommé");
+        site.urls.phpmyadmin = "https://pma.ovh.net:8443/index.php".to_string();
+        update_site(&mut data, "a", site).unwrap();
+        assert_eq!(data.sites[0].name, "Site A renommé");
+
+        add_site(&mut data, Site::sample("b", "Site B")).unwrap();
+        assert_eq!(
+            fields(update_site(&mut data, "b", Site::sample("a", "Site B"))),
+            vec!["id"]
+        );
+        assert!(matches!(
+            update_site(&mut data, "z", Site::sample("z", "Site Z")),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_intervention_and_checklist() {
+        let mut data = AppData::default();
+        let mut site = Site::sample("a", "Site A");
+        site.checklist.push(ChecklistItem {
+            task: "Sauvegarde".to_string(),
+            done: false,
+            date: None,
+        });
+        add_site(&mut data, site).unwrap();
+
+        let mut intervention = Intervention {
+            date: "2024-13-02".to_string(),
+            type_intervention: String::new(),
+            description: String::new(),
+            duration: "1h".to_string(),
+            result: "OK".to_string(),
+        };
+        assert_eq!(
+            fields(add_intervention(&mut data, "a", intervention.clone())),
+            vec!["date", "type_intervention"]
+        );
+        for date in [
+            "2024-02-31",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-04-31",
+            "2024-00-10",
+        ] {
+            intervention.date = date.to_string();
+            assert!(
+                fields(add_intervention(&mut data, "a", intervention.clone()))
+                    .contains(&"date".to_string()),
+                "{date}"
+            );
+        }
+        assert!(is_date("2024-02-29") && is_date("2000-02-29") && is_date("2024-12-31"));
+        intervention.date = "2024-03-12".to_string();
+        intervention.type_intervention = "Mise à jour".to_string();
+        add_intervention(&mut data, "a", intervention.clone()).unwrap();
+        intervention.date = "2024-03-19".to_string();
+        add_intervention(&mut data, "a", intervention).unwrap();
+        assert_eq!(data.sites[0].interventions.len(), 2);
+        assert_eq!(data.sites[0].interventions[0].date, "2024-03-19");
+
+        assert!(toggle_checklist_item(&mut data, "a", 0).unwrap());
+        assert_eq!(data.sites[0].checklist[0].date.as_ref().unwrap().len(), 10);
+        assert!(!toggle_checklist_item(&mut data, "a", 0).unwrap());
+        assert!(data.sites[0].checklist[0].date.is_none());
+        assert!(toggle_checklist_item(&mut data, "a", 1).is_err());
+    }
+
+    #[test]
+    fn test_save_validates_changed_sites_only() {
+        let mut legacy = Site::sample("Ancien_Site", "Ancien site");
+        legacy.urls.frontend = "ancien-site.fr".to_string();
+        let mut before = AppData::default();
+        before.sites.push(legacy);
+        before.sites.push(Site::sample("a", "Site A"));
+
+        // Site ancien non conforme mais inchangé : enregistrable
+        let mut after = before.clone();
+        after.sites[1].notes = "Nouvelle note".to_string();
+        validate_changed_sites(&before, &after).unwrap();
+
+        // Site modifié : vérifié, sans exiger un nouvel identifiant
+        after.sites[1].urls.frontend = "site-a.fr".to_string();
+        assert_eq!(
+            fields(validate_changed_sites(&before, &after)),
+            vec!["urls.frontend"]
+        );
+        after.sites[0].name = "Ancien site renommé".to_string();
+        after.sites[1] = Site::sample("a", "Site A");
+        assert_eq!(
+            fields(validate_changed_sites(&before, &after)),
+            vec!["urls.frontend"]
+        );
+
+        // Site ajouté : identifiant vérifié, y compris contre les autres ajouts
+        let mut after = before.clone();
+        after.sites.push(Site::sample("B", "Site B"));
+        assert_eq!(fields(validate_changed_sites(&before, &after)), vec!["id"]);
+        after.sites[2].id = "b".to_string();
+        validate_changed_sites(&before, &after).unwrap();
+        after.sites.push(Site::sample("b", "Site B bis"));
+        assert_eq!(fields(validate_changed_sites(&before, &after)), vec!["id"]);
+    }
+}
@@ -2,37 +2,166 @@
 // Module de stockage sécurisé pour Cockpit CFDT
 // Gère la lecture/écriture du fichier sites.encrypted
 
-use crate::crypto::{CryptoEngine, EncryptedData};
+use crate::atomic;
+use crate::backup_catalog::{unix_millis, BackupCatalog, BackupEntry, BackupReason};
+use crate::crypto::{
+    generate_recovery_key, CipherSuite, CryptoEngine, EncryptedData, KdfParams, KeySlotInfo,
+    KeySlotKind, Keyfile, SessionKey, FORMAT_VERSION, MASTER_SLOT_LABEL,
+};
+use crate::diff::{self, VaultDiff};
+use crate::error::{AppError, AppResult};
+use crate::history::{self, PointInTime};
+use crate::journal::{self, Journal, JournalPage, JournalQuery};
+use crate::merge::{self, FieldConflict};
+use crate::relocation::{self, RelocationReport};
+use crate::restore::{self, ConflictStrategy, RestoreReport, SiteSelection};
+use crate::retention::{self, BackupFile, RetentionPlan, RetentionPolicy};
+use crate::throttle::UnlockThrottle;
+use crate::trash::{self, TrashItem};
+use crate::vault_lock::VaultLock;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 /// Résultat d'une opération de storage
-pub type StorageResult<T> = Result<T, Box<dyn Error>>;
+pub type StorageResult<T> = AppResult<T>;
+
+/// Version courante du schéma de `AppData`
+pub const CURRENT_SCHEMA_VERSION: u32 = 3;
+
+/// Étape de migration : transforme le JSON déchiffré d'une version à la suivante
+type Migration = fn(&mut Value) -> StorageResult<()>;
+
+/// Chaîne des migrations, indexée par la version de départ
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v1_to_v2), (2, migrate_v2_to_v3)];
+
+/// v1 → v2 : ajout de `schema_version` et explicitation des champs
+/// ajoutés après la première version (comptes Joomla, extensions, AdminTools)
+fn migrate_v1_to_v2(data: &mut Value) -> StorageResult<()> {
+    let sites = data
+        .get_mut("sites")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| {
+            AppError::CorruptVault("données v1 : liste des sites absente".to_string())
+        })?;
+
+    for site in sites {
+        let site = site
+            .as_object_mut()
+            .ok_or_else(|| AppError::CorruptVault("données v1 : site invalide".to_string()))?;
+        site.entry("admintools_login").or_insert(Value::Null);
+        site.entry("joomla_accounts")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        site.entry("extensions")
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+
+    Ok(())
+}
+
+/// v2 → v3 : ajout de la corbeille et de sa durée de conservation
+fn migrate_v2_to_v3(data: &mut Value) -> StorageResult<()> {
+    let data = data
+        .as_object_mut()
+        .ok_or_else(|| AppError::CorruptVault("données v2 invalides".to_string()))?;
+    data.entry("trash")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Some(settings) = data.get_mut("settings").and_then(Value::as_object_mut) {
+        settings
+            .entry("trash_keep_days")
+            .or_insert_with(|| Value::from(default_trash_keep_days()));
+    }
+    Ok(())
+}
+
+/// Version du schéma d'un JSON déchiffré (absente = version 1)
+fn schema_version_of(data: &Value) -> StorageResult<u32> {
+    match data.get("schema_version") {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| AppError::CorruptVault("version de schéma invalide".to_string())),
+    }
+}
+
+/// Applique les migrations nécessaires pour amener `data` au schéma courant
+///
+/// # Returns
+/// `true` si au moins une migration a été appliquée
+fn migrate(data: &mut Value) -> StorageResult<bool> {
+    let mut version = schema_version_of(data)?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(AppError::UnsupportedVersion(format!(
+            "ce coffre utilise le schéma {} (plus récent que {}). Mettez à jour Cockpit CFDT.",
+            version, CURRENT_SCHEMA_VERSION
+        )));
+    }
+
+    let migrated = version < CURRENT_SCHEMA_VERSION;
+    while version < CURRENT_SCHEMA_VERSION {
+        let (_, step) = MIGRATIONS
+            .iter()
+            .find(|(from, _)| *from == version)
+            .ok_or_else(|| {
+                AppError::UnsupportedVersion(format!(
+                    "aucune migration depuis le schéma {}",
+                    version
+                ))
+            })?;
+        step(data)?;
+        version += 1;
+        data["schema_version"] = Value::from(version);
+    }
+
+    Ok(migrated)
+}
+
+fn current_schema_version() -> u32 {
+    CURRENT_SCHEMA_VERSION
+}
 
 /// Structure complète des données de l'application
-#[derive(Serialize, Deserialize, Clone, Debug)]
+///
+/// Les données déchiffrées sont effacées de la mémoire à la libération
+/// (ainsi que chaque copie obtenue par `clone`).
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct AppData {
+    /// Version du schéma (voir `CURRENT_SCHEMA_VERSION`)
+    #[serde(default = "current_schema_version")]
+    pub schema_version: u32,
+
     /// Liste de tous les sites CFDT
     pub sites: Vec<Site>,
 
     /// Paramètres de l'application
     pub settings: AppSettings,
+
+    /// Sites, interventions et contacts supprimés (voir `trash.rs`)
+    #[serde(default)]
+    pub trash: Vec<TrashItem>,
 }
 
 impl Default for AppData {
     fn default() -> Self {
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             sites: Vec::new(),
             settings: AppSettings::default(),
+            trash: Vec::new(),
         }
     }
 }
 
 /// Représentation d'un site CFDT
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct Site {
     /// Identifiant unique (ex: "cfdt-ulogistique")
     pub id: String,
@@ -86,8 +215,52 @@ pub struct Site {
     pub last_update: String,
 }
 
+#[cfg(test)]
+impl Site {
+    /// Site minimal pour les tests
+    pub(crate) fn sample(id: &str, name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            enabled: true,
+            urls: SiteUrls {
+                frontend: format!("https://{}.fr", id),
+                backend: "/administrator".to_string(),
+                phpmyadmin: String::new(),
+            },
+            dashlane_refs: DashlaneRefs {
+                backend_protection: None,
+                joomla_admin: format!("[{}] Joomla Admin", name),
+                mysql_su: format!("[{}] MySQL SU", name),
+                mysql_std: None,
+                editors: vec![],
+            },
+            admintools_login: None,
+            server: ServerInfo {
+                mysql_host: "localhost:3306".to_string(),
+                database: format!("{}_db", id),
+                prefix: "jos_".to_string(),
+                ovh_vps: String::new(),
+            },
+            tech: TechInfo {
+                joomla_version: "4.4.2".to_string(),
+                php_version: "8.1".to_string(),
+                template: "Helix".to_string(),
+            },
+            analytics: None,
+            joomla_accounts: vec![],
+            extensions: vec![],
+            checklist: vec![],
+            interventions: vec![],
+            contacts: vec![],
+            notes: String::new(),
+            last_update: String::new(),
+        }
+    }
+}
+
 /// URLs d'un site
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct SiteUrls {
     pub frontend: String,
     pub backend: String,
@@ -95,7 +268,7 @@ pub struct SiteUrls {
 }
 
 /// Références Dashlane
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct DashlaneRefs {
     pub backend_protection: Option<String>,
     pub joomla_admin: String,
@@ -105,7 +278,7 @@ pub struct DashlaneRefs {
 }
 
 /// Informations serveur
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct ServerInfo {
     pub mysql_host: String,
     pub database: String,
@@ -114,7 +287,7 @@ pub struct ServerInfo {
 }
 
 /// Informations techniques
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct TechInfo {
     pub joomla_version: String,
     pub php_version: String,
@@ -122,7 +295,7 @@ pub struct TechInfo {
 }
 
 /// Informations Analytics
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct AnalyticsInfo {
     pub ga_id: Option<String>,
     pub gtm_id: Option<String>,
@@ -133,7 +306,7 @@ pub struct AnalyticsInfo {
 }
 
 /// Compte Joomla additionnel
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Zeroize, ZeroizeOnDrop)]
 pub struct JoomlaAccount {
     pub username: String,
     pub role: String,
@@ -141,7 +314,7 @@ pub struct JoomlaAccount {
 }
 
 /// Extension Joomla
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Zeroize, ZeroizeOnDrop)]
 pub struct Extension {
     pub name: String,
     pub version: Option<String>,
@@ -149,7 +322,7 @@ pub struct Extension {
 }
 
 /// Item de checklist
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Zeroize, ZeroizeOnDrop)]
 pub struct ChecklistItem {
     pub task: String,
     pub done: bool,
@@ -157,7 +330,7 @@ pub struct ChecklistItem {
 }
 
 /// Intervention sur un site
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Zeroize, ZeroizeOnDrop)]
 pub struct Intervention {
     pub date: String,
     pub type_intervention: String,
@@ -167,7 +340,7 @@ pub struct Intervention {
 }
 
 /// Contact pour un site
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Zeroize, ZeroizeOnDrop)]
 pub struct Contact {
     pub name: String,
     pub role: String,
@@ -176,7 +349,7 @@ pub struct Contact {
 }
 
 /// Paramètres de l'application
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct AppSettings {
     /// Auto-lock après X minutes d'inactivité
     pub auto_lock_minutes: u32,
@@ -189,6 +362,21 @@ pub struct AppSettings {
 
     /// Chemin vers Dashlane CLI (ou "auto")
     pub dashlane_cli_path: String,
+
+    /// Algorithme de chiffrement des prochaines sauvegardes
+    /// (les fichiers existants restent lisibles quel que soit leur algorithme)
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub cipher_suite: CipherSuite,
+
+    /// Nombre de jours pendant lesquels les éléments supprimés restent dans
+    /// la corbeille (0 = jusqu'à ce qu'elle soit vidée)
+    #[serde(default = "default_trash_keep_days")]
+    pub trash_keep_days: u32,
+}
+
+fn default_trash_keep_days() -> u32 {
+    30
 }
 
 impl Default for AppSettings {
@@ -198,14 +386,96 @@ impl Default for AppSettings {
             auto_backup: true,
             backup_keep_days: 30,
             dashlane_cli_path: "auto".to_string(),
+            cipher_suite: CipherSuite::default(),
+            trash_keep_days: default_trash_keep_days(),
         }
     }
 }
 
+/// Clé de secours nouvellement créée
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
+pub struct RecoveryKey {
+    /// Clé imprimable, affichée une seule fois
+    pub key: String,
+
+    /// Emplacement qu'elle protège
+    #[zeroize(skip)]
+    pub slot: KeySlotInfo,
+}
+
+/// Efface les chaînes d'une valeur JSON (données déchiffrées)
+pub(crate) fn zeroize_value(value: &mut Value) {
+    match value {
+        Value::String(s) => s.zeroize(),
+        Value::Array(items) => items.iter_mut().for_each(zeroize_value),
+        Value::Object(map) => map.values_mut().for_each(zeroize_value),
+        _ => {}
+    }
+}
+
+/// Compte les octets écrits sans les conserver
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sérialise en JSON dans un tampon effacé à la libération
+///
+/// La taille est calculée d'abord : le tampon n'est jamais réalloué, ce qui
+/// laisserait une copie des données en clair dans la mémoire libérée.
+fn to_json_zeroizing<T: Serialize>(value: &T) -> StorageResult<Zeroizing<Vec<u8>>> {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer_pretty(&mut counter, value)?;
+
+    let mut json = Zeroizing::new(Vec::with_capacity(counter.0));
+    serde_json::to_writer_pretty(&mut *json, value)?;
+    Ok(json)
+}
+
 /// Gestionnaire de stockage
 pub struct StorageManager {
     data_path: PathBuf,
     backup_path: PathBuf,
+    kdf_params: KdfParams,
+    throttle: UnlockThrottle,
+    /// Journal chiffré des modifications
+    journal: Journal,
+    /// Verrou d'écriture, pris au déverrouillage
+    vault_lock: Option<VaultLock>,
+    /// Coffre ouvert en lecture seule (verrou détenu par une autre instance)
+    read_only: bool,
+    /// Empreinte SHA-256 de sites.encrypted au chargement ou à la dernière
+    /// écriture, pour détecter une modification faite ailleurs
+    loaded_hash: Cell<Option<[u8; 32]>>,
+    /// Nombre de sites du fichier dont l'empreinte est `loaded_hash`
+    loaded_site_count: Cell<Option<usize>>,
+}
+
+/// Résultat d'une sauvegarde qui tient compte des modifications faites
+/// ailleurs (client de synchronisation, autre poste)
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SaveOutcome {
+    /// Fichier inchangé depuis le chargement : données écrites telles quelles
+    Saved,
+
+    /// Fichier modifié ailleurs sans conflit : la fusion a été écrite
+    Merged { data: AppData },
+
+    /// Conflits à arbitrer : rien n'a été écrit, `data` est la fusion proposée
+    /// (valeurs de l'application pour les champs en conflit)
+    Conflicts {
+        data: AppData,
+        conflicts: Vec<FieldConflict>,
+    },
 }
 
 impl StorageManager {
@@ -225,9 +495,33 @@ impl StorageManager {
         Ok(Self {
             data_path,
             backup_path,
+            kdf_params: KdfParams::recommended(),
+            throttle: UnlockThrottle::new(app_dir),
+            journal: Journal::new(app_dir),
+            vault_lock: None,
+            read_only: false,
+            loaded_hash: Cell::new(None),
+            loaded_site_count: Cell::new(None),
         })
     }
 
+    /// Définit les paramètres KDF utilisés pour les prochaines sauvegardes
+    pub fn with_kdf_params(mut self, kdf_params: KdfParams) -> Self {
+        self.kdf_params = kdf_params;
+        self
+    }
+
+    /// Définit le délai imposé après le premier échec de déverrouillage
+    pub fn with_unlock_base_delay(mut self, base_delay_secs: u64) -> Self {
+        self.throttle = self.throttle.with_base_delay(base_delay_secs);
+        self
+    }
+
+    /// Secondes à attendre avant la prochaine tentative de déverrouillage
+    pub fn unlock_wait(&self) -> u64 {
+        self.throttle.remaining_wait()
+    }
+
     /// Vérifie si le fichier de données existe
     pub fn exists(&self) -> bool {
         self.data_path.exists()
@@ -238,49 +532,673 @@ impl StorageManager {
         self.data_path.parent().unwrap_or(Path::new("."))
     }
 
+    /// Prépare l'ouverture du coffre : prend le verrou d'écriture, ou passe
+    /// en lecture seule si `read_only` (sans toucher au verrou d'une autre instance)
+    ///
+    /// # Errors
+    /// `VaultInUse` si une autre instance active a ouvert le coffre en écriture
+    pub fn acquire_lock(&mut self, read_only: bool) -> StorageResult<()> {
+        self.release_lock();
+        if read_only {
+            self.read_only = true;
+        } else {
+            self.vault_lock = Some(VaultLock::acquire(self.get_data_dir())?);
+        }
+        Ok(())
+    }
+
+    /// Libère le verrou d'écriture (au verrouillage ou à la fermeture)
+    pub fn release_lock(&mut self) {
+        self.vault_lock = None;
+        self.read_only = false;
+    }
+
+    /// Rafraîchit le verrou d'écriture pour qu'il ne soit pas jugé abandonné
+    ///
+    /// Si une autre instance l'a repris entre-temps, le coffre passe en
+    /// lecture seule plutôt que d'écraser ses sauvegardes.
+    ///
+    /// # Returns
+    /// `true` si le coffre vient de passer en lecture seule
+    pub fn keep_lock_alive(&mut self) -> StorageResult<bool> {
+        if let Some(lock) = self.vault_lock.as_mut() {
+            if !lock.keep_alive()? {
+                self.vault_lock = None;
+                self.read_only = true;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Le coffre est-il ouvert en lecture seule ?
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Refuse les écritures en lecture seule
+    fn ensure_writable(&self) -> StorageResult<()> {
+        if self.read_only {
+            return Err(AppError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Déplace le coffre, ses backups, son journal et le suivi des tentatives
+    /// vers `new_dir`
+    ///
+    /// Les fichiers sont copiés puis vérifiés (SHA-256) avant l'appel de
+    /// `commit` (enregistrement du nouvel emplacement dans la configuration) ;
+    /// le gestionnaire pointe ensuite vers le nouveau dossier, verrou compris.
+    /// En cas d'échec, les copies sont supprimées et rien ne change.
+    ///
+    /// # Arguments
+    /// * `new_dir` - Nouveau dossier de données (existant, sans coffre)
+    /// * `remove_source` - Écraser puis supprimer les fichiers de l'ancien dossier
+    /// * `commit` - Appelé une fois les copies vérifiées
+    ///
+    /// # Errors
+    /// `ReadOnly` si le coffre est ouvert en lecture seule, `VaultInUse` si une
+    /// autre instance l'a ouvert, `InvalidInput` si `new_dir` ne convient pas
+    pub fn relocate(
+        &mut self,
+        new_dir: &Path,
+        remove_source: bool,
+        commit: impl FnOnce() -> StorageResult<()>,
+    ) -> StorageResult<RelocationReport> {
+        self.ensure_writable()?;
+        let old_dir = self.get_data_dir().to_path_buf();
+        relocation::check_target(&old_dir, new_dir)?;
+
+        // Aucune autre instance ne doit écrire dans l'un ou l'autre dossier
+        let _old_lock = match self.vault_lock {
+            Some(_) => None,
+            None => Some(VaultLock::acquire(&old_dir)?),
+        };
+        let new_lock = VaultLock::acquire(new_dir)?;
+
+        let files = relocation::files_to_move(&old_dir)?;
+        let copied = relocation::copy_verified(&old_dir, new_dir, &files)?;
+        if let Err(e) = commit() {
+            copied.rollback();
+            return Err(e);
+        }
+
+        self.data_path = new_dir.join(relocation::DATA_FILE_NAME);
+        self.backup_path = new_dir.join(relocation::BACKUP_DIR_NAME);
+        self.throttle = self.throttle.moved_to(new_dir);
+        self.journal = Journal::new(new_dir);
+        // Coffre ouvert : le verrou suit le coffre ; sinon il est libéré
+        if self.vault_lock.is_some() {
+            self.vault_lock = Some(new_lock);
+        }
+
+        let leftovers = if remove_source {
+            relocation::remove_source(&old_dir, &files)
+        } else {
+            Vec::new()
+        };
+
+        Ok(RelocationReport {
+            location: new_dir.to_string_lossy().to_string(),
+            files: copied.files.len(),
+            bytes: copied.bytes,
+            leftovers,
+        })
+    }
+
+    /// Supprime les fichiers temporaires laissés par une sauvegarde interrompue,
+    /// dans le dossier de données et dans celui des backups
+    ///
+    /// Un fichier n'est remplacé qu'une fois le fichier temporaire complet :
+    /// ces restes ne contiennent rien qui ne soit déjà ailleurs.
+    ///
+    /// # Returns
+    /// Le nombre de fichiers supprimés
+    pub fn cleanup_temp_files(&self) -> StorageResult<usize> {
+        let mut removed = atomic::cleanup_temp_files(self.get_data_dir())?.len();
+        removed += atomic::cleanup_temp_files(&self.backup_path)?.len();
+        Ok(removed)
+    }
+
     /// Charge les données depuis le fichier chiffré
     ///
+    /// Voir `unlock` pour les migrations appliquées au chargement.
+    ///
     /// # Arguments
     /// * `password` - Mot de passe maître
     pub fn load(&self, password: &str) -> StorageResult<AppData> {
-        // Lire le fichier
-        let encrypted_json = fs::read_to_string(&self.data_path)?;
+        self.unlock(password, None).map(|(data, _)| data)
+    }
+
+    /// Déverrouille le coffre et retourne les données avec la clé de session
+    ///
+    /// Un coffre d'un format ou d'un schéma plus ancien est migré vers le
+    /// format courant (après un backup automatique), et l'emplacement de clé
+    /// utilisé est re-protégé s'il a des paramètres KDF plus faibles que ceux
+    /// configurés. Un coffre plus récent que l'application est refusé.
+    /// En lecture seule (voir `acquire_lock`), rien n'est réécrit sur le disque.
+    ///
+    /// Chaque mot de passe incorrect allonge le délai avant la tentative
+    /// suivante ; un déverrouillage réussi remet le compteur à zéro.
+    ///
+    /// # Arguments
+    /// * `password` - Mot de passe ou clé de secours
+    /// * `keyfile` - Fichier clé, si l'emplacement en exige un
+    ///
+    /// # Returns
+    /// Les données et la clé à utiliser pour les sauvegardes suivantes
+    pub fn unlock(
+        &self,
+        password: &str,
+        keyfile: Option<&Keyfile>,
+    ) -> StorageResult<(AppData, SessionKey)> {
+        // Lire le fichier et ses métadonnées de chiffrement
+        let (encrypted, hash) = Self::read_encrypted_with_hash(&self.data_path)?;
+
+        // Refuser la tentative si le délai après un échec n'est pas écoulé
+        self.throttle.check()?;
+
+        // Ouvrir un emplacement de clé et déchiffrer
+        let opened = SessionKey::open(&encrypted, password, keyfile).and_then(|session_key| {
+            let (app_data, migrated) = Self::decrypt_app_data(&encrypted, &session_key)?;
+            Ok((app_data, migrated, session_key))
+        });
+        let (mut app_data, migrated, mut session_key) = match opened {
+            Err(e @ (AppError::WrongPassword | AppError::KeyfileRequired)) => {
+                self.throttle.record_failure()?;
+                return Err(e);
+            }
+            other => other?,
+        };
+        self.throttle.reset()?;
+        // Un format plus ancien est réécrit au format courant
+        let mut rewrite = migrated || encrypted.version != FORMAT_VERSION;
+
+        if !encrypted.uses_key_slots() {
+            // Format sans emplacements : créer une clé de données protégée par le mot de passe
+            session_key =
+                SessionKey::generate(MASTER_SLOT_LABEL, password, None, &self.kdf_params)?;
+            rewrite = true;
+        } else if session_key
+            .unlocked_slot()
+            .is_some_and(|slot| slot.kdf_params.is_weaker_than(&self.kdf_params))
+        {
+            // Re-protéger l'emplacement utilisé avec les paramètres actuels
+            session_key.rewrap_unlocked_slot(password, keyfile, &self.kdf_params)?;
+            rewrite = true;
+        }
+
+        self.loaded_hash.set(Some(hash));
+        self.loaded_site_count.set(Some(app_data.sites.len()));
+
+        // En lecture seule, la migration n'est faite qu'en mémoire
+        if rewrite && !self.read_only {
+            // Conserver le fichier d'origine avant de l'écraser
+            self.write_data(&app_data, &session_key, Some(BackupReason::Migration))?;
+        }
+
+        // Purger la corbeille (le backup garde les éléments purgés)
+        if !self.read_only && !trash::expired(&app_data, unix_millis()).is_empty() {
+            trash::purge_expired(&mut app_data, unix_millis());
+            self.write_data(&app_data, &session_key, Some(BackupReason::Save))?;
+        }
+
+        Ok((app_data, session_key))
+    }
 
-        // Parser le JSON des métadonnées de chiffrement
-        let encrypted: EncryptedData = serde_json::from_str(&encrypted_json)?;
+    /// Lit un fichier chiffré et vérifie que son format est lisible
+    fn read_encrypted(path: &Path) -> StorageResult<EncryptedData> {
+        Self::read_encrypted_with_hash(path).map(|(encrypted, _)| encrypted)
+    }
+
+    /// Comme `read_encrypted`, avec l'empreinte SHA-256 du fichier lu
+    fn read_encrypted_with_hash(path: &Path) -> StorageResult<(EncryptedData, [u8; 32])> {
+        let encrypted_json = fs::read(path)?;
+        let encrypted: EncryptedData = serde_json::from_slice(&encrypted_json)?;
 
-        // Déchiffrer
-        let decrypted_json = CryptoEngine::decrypt(&encrypted, password)?;
+        // Refuser un format d'enveloppe plus récent que l'application
+        CryptoEngine::check_format_version(&encrypted.version)?;
+
+        Ok((encrypted, Sha256::digest(&encrypted_json).into()))
+    }
 
-        // Parser les données de l'app
-        let app_data: AppData = serde_json::from_str(&decrypted_json)?;
+    /// Le fichier de données a-t-il été modifié hors de cette instance depuis
+    /// le chargement (ou la dernière écriture) ?
+    pub fn has_changed_on_disk(&self) -> StorageResult<bool> {
+        let Some(loaded) = self.loaded_hash.get() else {
+            return Ok(false);
+        };
+        if !self.exists() {
+            return Ok(false);
+        }
+        let current: [u8; 32] = Sha256::digest(fs::read(&self.data_path)?).into();
+        Ok(current != loaded)
+    }
 
-        Ok(app_data)
+    /// Déchiffre et migre le contenu d'un fichier
+    ///
+    /// # Returns
+    /// Les données au schéma courant, et `true` si une migration a été appliquée
+    fn decrypt_app_data(
+        encrypted: &EncryptedData,
+        session_key: &SessionKey,
+    ) -> StorageResult<(AppData, bool)> {
+        let decrypted_json = CryptoEngine::decrypt_with_key(encrypted, session_key)?;
+
+        // Migrer le schéma si nécessaire
+        let mut raw: Value = serde_json::from_str(&decrypted_json)?;
+        let result =
+            migrate(&mut raw).and_then(|migrated| Ok((AppData::deserialize(&raw)?, migrated)));
+
+        // La représentation intermédiaire contient aussi les données en clair
+        zeroize_value(&mut raw);
+        result
     }
 
-    /// Sauvegarde les données dans le fichier chiffré
+    /// Sauvegarde les données avec une nouvelle clé de données protégée
+    /// uniquement par `password`
     ///
     /// # Arguments
     /// * `data` - Données de l'application
     /// * `password` - Mot de passe maître
     /// * `create_backup` - Créer un backup avant d'écraser
     pub fn save(&self, data: &AppData, password: &str, create_backup: bool) -> StorageResult<()> {
+        let session_key =
+            SessionKey::generate(MASTER_SLOT_LABEL, password, None, &self.kdf_params)?;
+        self.save_with_key(data, &session_key, create_backup)
+    }
+
+    /// Sauvegarde les données avec la clé de session (sans dérivation Argon2)
+    ///
+    /// # Arguments
+    /// * `data` - Données de l'application
+    /// * `session_key` - Clé obtenue au déverrouillage
+    /// * `create_backup` - Créer un backup avant d'écraser
+    pub fn save_with_key(
+        &self,
+        data: &AppData,
+        session_key: &SessionKey,
+        create_backup: bool,
+    ) -> StorageResult<()> {
+        self.write_data(
+            data,
+            session_key,
+            create_backup.then_some(BackupReason::Save),
+        )
+    }
+
+    /// Chiffre et écrit les données, après un backup du fichier actuel pour
+    /// la raison `backup` si elle est indiquée
+    fn write_data(
+        &self,
+        data: &AppData,
+        session_key: &SessionKey,
+        backup: Option<BackupReason>,
+    ) -> StorageResult<()> {
+        self.ensure_writable()?;
         // Backup si demandé et si le fichier existe déjà
-        if create_backup && self.exists() {
-            self.create_backup()?;
+        if let Some(reason) = backup {
+            self.create_backup(reason)?;
         }
 
-        // Sérialiser les données en JSON
-        let json = serde_json::to_string_pretty(data)?;
+        // Modifications par rapport au fichier actuel, pour le journal (aucune
+        // si le fichier a été chiffré avec une autre clé de données)
+        let previous = if self.exists() {
+            self.read_disk_data(session_key).ok()
+        } else {
+            None
+        };
+        let events = match previous {
+            Some((previous, _)) => {
+                journal::changes(&previous, data, unix_millis(), &journal::author())?
+            }
+            None => Vec::new(),
+        };
+
+        // Sérialiser les données en JSON (tampon effacé après chiffrement)
+        let json = to_json_zeroizing(data)?;
+        let json = std::str::from_utf8(&json)
+            .map_err(|e| AppError::Crypto(format!("JSON non UTF-8: {}", e)))?;
 
         // Chiffrer
-        let encrypted = CryptoEngine::encrypt(&json, password)?;
+        let encrypted =
+            CryptoEngine::encrypt_with_key(json, session_key, data.settings.cipher_suite)?;
 
         // Sérialiser les métadonnées de chiffrement
         let encrypted_json = serde_json::to_string_pretty(&encrypted)?;
 
-        // Écrire dans le fichier
-        fs::write(&self.data_path, encrypted_json)?;
+        // Écrire dans le fichier (fichier temporaire puis renommage)
+        atomic::write_atomic(&self.data_path, encrypted_json.as_bytes())?;
+        self.loaded_hash
+            .set(Some(Sha256::digest(encrypted_json.as_bytes()).into()));
+        self.loaded_site_count.set(Some(data.sites.len()));
+
+        // Le coffre est déjà écrit : un échec du journal (disque plein,
+        // fichier verrouillé…) ne doit pas faire croire que l'écriture a échoué
+        if !events.is_empty() {
+            let _ = self
+                .journal
+                .append(&events, session_key, data.settings.cipher_suite);
+        }
+
+        // Éclaircir les backups ; un échec (fichier verrouillé par un
+        // antivirus…) n'empêche pas la sauvegarde, nouvel essai la prochaine fois
+        if backup.is_some() {
+            let _ = self.apply_retention(data.settings.backup_keep_days);
+        }
+
+        Ok(())
+    }
+
+    /// Sauvegarde sans écraser les modifications faites ailleurs
+    ///
+    /// Si le fichier a changé sur le disque depuis le chargement, les
+    /// modifications des deux côtés sont fusionnées (voir `merge.rs`). La
+    /// fusion n'est écrite que s'il n'y a aucun conflit.
+    ///
+    /// # Arguments
+    /// * `base` - Données telles qu'elles ont été chargées ou écrites ; mises à
+    ///   jour avec ce qui est désormais sur le disque (en cas de conflit, la
+    ///   version du disque : une nouvelle sauvegarde après arbitrage ne
+    ///   redemande pas la fusion)
+    /// * `data` - Données modifiées dans l'application
+    /// * `session_key` - Clé obtenue au déverrouillage
+    /// * `create_backup` - Créer un backup avant d'écraser
+    pub fn save_checked(
+        &self,
+        base: &mut AppData,
+        data: &AppData,
+        session_key: &SessionKey,
+        create_backup: bool,
+    ) -> StorageResult<SaveOutcome> {
+        self.ensure_writable()?;
+        if !self.has_changed_on_disk()? {
+            self.save_with_key(data, session_key, create_backup)?;
+            *base = data.clone();
+            return Ok(SaveOutcome::Saved);
+        }
+
+        let (disk, disk_hash) = self.read_disk_data(session_key)?;
+        let result = merge::three_way_merge(base, &disk, data)?;
+        if !result.conflicts.is_empty() {
+            self.loaded_hash.set(Some(disk_hash));
+            self.loaded_site_count.set(Some(disk.sites.len()));
+            *base = disk;
+            return Ok(SaveOutcome::Conflicts {
+                data: result.merged,
+                conflicts: result.conflicts,
+            });
+        }
+
+        self.save_with_key(&result.merged, session_key, create_backup)?;
+        *base = result.merged.clone();
+        Ok(SaveOutcome::Merged {
+            data: result.merged,
+        })
+    }
+
+    /// Applique une modification ciblée aux données puis l'enregistre
+    ///
+    /// Si le fichier a changé sur le disque depuis le chargement, la
+    /// modification est appliquée à la version du disque : rien de ce qui a
+    /// été enregistré ailleurs n'est perdu.
+    ///
+    /// # Arguments
+    /// * `current` - Données du coffre, remplacées seulement si l'écriture réussit
+    /// * `session_key` - Clé obtenue au déverrouillage
+    /// * `edit` - Modification (voir `site_edit.rs` et `trash.rs`) ; en cas d'erreur, rien n'est écrit
+    pub fn apply_edit<T>(
+        &self,
+        current: &mut AppData,
+        session_key: &SessionKey,
+        edit: impl FnOnce(&mut AppData) -> StorageResult<T>,
+    ) -> StorageResult<T> {
+        self.apply_edit_with_backup(current, session_key, BackupReason::Save, edit)
+    }
+
+    /// Comme `apply_edit`, avec la raison du backup créé avant l'écriture
+    pub fn apply_edit_with_backup<T>(
+        &self,
+        current: &mut AppData,
+        session_key: &SessionKey,
+        reason: BackupReason,
+        edit: impl FnOnce(&mut AppData) -> StorageResult<T>,
+    ) -> StorageResult<T> {
+        self.ensure_writable()?;
+        let mut data = if self.has_changed_on_disk()? {
+            self.read_disk_data(session_key)?.0
+        } else {
+            current.clone()
+        };
+
+        let value = edit(&mut data)?;
+        self.write_data(&data, session_key, Some(reason))?;
+        *current = data;
+        Ok(value)
+    }
+
+    /// Version du disque, qui doit être chiffrée avec la même clé de données
+    fn read_disk_data(&self, session_key: &SessionKey) -> StorageResult<(AppData, [u8; 32])> {
+        let (encrypted, disk_hash) = Self::read_encrypted_with_hash(&self.data_path)?;
+        let (disk, _) = Self::decrypt_app_data(&encrypted, session_key).map_err(|e| match e {
+            AppError::WrongPassword | AppError::HeaderTampered => AppError::VaultReplaced,
+            other => other,
+        })?;
+        Ok((disk, disk_hash))
+    }
+
+    /// Lit le journal des modifications (par site et/ou par période)
+    pub fn query_journal(
+        &self,
+        session_key: &SessionKey,
+        query: &JournalQuery,
+    ) -> StorageResult<JournalPage> {
+        self.journal.query(session_key, query)
+    }
+
+    /// Reconstitue le coffre tel qu'il était à `at_ms`
+    ///
+    /// Le point de départ est celui qui demande le moins de modifications à
+    /// rejouer : le coffre actuel ou un backup ouvrable avec la clé de
+    /// session. Avec `site_id`, seules les modifications de ce site sont
+    /// rejouées (les autres sites sont ceux du point de départ).
+    pub fn state_at(
+        &self,
+        at_ms: u64,
+        site_id: Option<&str>,
+        session_key: &SessionKey,
+    ) -> StorageResult<PointInTime<AppData>> {
+        let page = self.journal.query(session_key, &JournalQuery::default())?;
+        let journal_start_ms = page.events.iter().map(|e| e.at_ms).min();
+        let mut events = page.events;
+        if let Some(id) = site_id {
+            events.retain(|e| e.site_id.as_deref() == Some(id));
+        }
+
+        let backups = self.list_backups()?;
+        let cost = |snapshot_ms| {
+            history::events_to_replay(&events, snapshot_ms, at_ms)
+                .0
+                .len()
+        };
+        let mut snapshots: Vec<(Option<&BackupEntry>, usize)> = std::iter::once((None, cost(None)))
+            .chain(
+                backups
+                    .iter()
+                    .map(|b| (Some(b), cost(Some(b.created_at_ms)))),
+            )
+            .collect();
+        // À coût égal, le coffre actuel (en tête) évite de déchiffrer un backup
+        snapshots.sort_by_key(|(_, cost)| *cost);
+
+        let mut last_error = AppError::NotInitialized;
+        for (backup, _) in snapshots {
+            // Backup chiffré avec une autre clé de données : point de départ suivant
+            let snapshot = match backup {
+                Some(entry) => self.open_backup(&entry.id, session_key, None),
+                None => self.read_disk_data(session_key).map(|(data, _)| data),
+            };
+            let snapshot = match snapshot {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    last_error = e;
+                    continue;
+                }
+            };
+
+            let replay =
+                history::replay(&snapshot, backup.map(|b| b.created_at_ms), &events, at_ms)?;
+            return Ok(PointInTime {
+                at_ms,
+                snapshot_id: backup.map(|b| b.id.clone()),
+                replayed: replay.replayed,
+                unresolved: replay.unresolved,
+                journal_start_ms,
+                unreadable: page.unreadable,
+                data: replay.data,
+            });
+        }
+        Err(last_error)
+    }
+
+    /// Reconstitue un site tel qu'il était à `at_ms` (`None` : il n'existait pas)
+    pub fn site_at(
+        &self,
+        site_id: &str,
+        at_ms: u64,
+        session_key: &SessionKey,
+    ) -> StorageResult<PointInTime<Option<Site>>> {
+        let state = self.state_at(at_ms, Some(site_id), session_key)?;
+        let site = state.data.sites.iter().find(|s| s.id == site_id).cloned();
+        Ok(PointInTime {
+            at_ms: state.at_ms,
+            snapshot_id: state.snapshot_id,
+            replayed: state.replayed,
+            unresolved: state.unresolved,
+            journal_start_ms: state.journal_start_ms,
+            unreadable: state.unreadable,
+            data: site,
+        })
+    }
+
+    /// Change le mot de passe d'un emplacement de clé
+    ///
+    /// Seul l'emplacement ouvert par `old_password` est re-protégé : les
+    /// données et les autres emplacements ne changent pas. Le fichier clé
+    /// éventuellement requis par l'emplacement reste requis.
+    pub fn change_password(
+        &self,
+        session_key: &mut SessionKey,
+        old_password: &str,
+        new_password: &str,
+        keyfile: Option<&Keyfile>,
+    ) -> StorageResult<()> {
+        self.ensure_writable()?;
+        session_key.rewrap_slot(old_password, new_password, keyfile, &self.kdf_params)?;
+        self.write_key_slots(session_key)
+    }
+
+    /// Exige un fichier clé en plus du mot de passe pour ouvrir l'emplacement
+    ///
+    /// # Arguments
+    /// * `password` - Mot de passe de l'emplacement
+    /// * `current_keyfile` - Fichier clé actuel, si l'emplacement en exige déjà un
+    /// * `keyfile` - Nouveau fichier clé
+    pub fn attach_keyfile(
+        &self,
+        session_key: &mut SessionKey,
+        password: &str,
+        current_keyfile: Option<&Keyfile>,
+        keyfile: &Keyfile,
+    ) -> StorageResult<()> {
+        self.ensure_writable()?;
+        session_key.set_slot_keyfile(password, current_keyfile, Some(keyfile), &self.kdf_params)?;
+        self.write_key_slots(session_key)
+    }
+
+    /// Retire l'exigence du fichier clé (le mot de passe seul suffit à nouveau)
+    pub fn remove_keyfile(
+        &self,
+        session_key: &mut SessionKey,
+        password: &str,
+        keyfile: &Keyfile,
+    ) -> StorageResult<()> {
+        self.ensure_writable()?;
+        session_key.set_slot_keyfile(password, Some(keyfile), None, &self.kdf_params)?;
+        self.write_key_slots(session_key)
+    }
+
+    /// Indique si le coffre exige un fichier clé (pour l'écran de déverrouillage)
+    pub fn requires_keyfile(&self) -> StorageResult<bool> {
+        Ok(Self::read_encrypted(&self.data_path)?.requires_keyfile())
+    }
+
+    /// Ajoute un mot de passe supplémentaire (ex: celui d'un collègue)
+    pub fn add_password_slot(
+        &self,
+        session_key: &mut SessionKey,
+        label: &str,
+        password: &str,
+    ) -> StorageResult<KeySlotInfo> {
+        self.ensure_writable()?;
+        let info = session_key.add_slot(
+            KeySlotKind::Password,
+            label,
+            password,
+            None,
+            &self.kdf_params,
+        )?;
+        self.write_key_slots(session_key)?;
+        Ok(info)
+    }
+
+    /// Génère une clé de secours imprimable et l'ajoute au coffre
+    ///
+    /// # Returns
+    /// La clé de secours (à afficher une seule fois) et son emplacement
+    pub fn add_recovery_key(
+        &self,
+        session_key: &mut SessionKey,
+        label: &str,
+    ) -> StorageResult<RecoveryKey> {
+        self.ensure_writable()?;
+        let key = generate_recovery_key();
+        let slot =
+            session_key.add_slot(KeySlotKind::Recovery, label, &key, None, &self.kdf_params)?;
+        self.write_key_slots(session_key)?;
+        Ok(RecoveryKey { key, slot })
+    }
+
+    /// Révoque un emplacement de clé
+    pub fn revoke_key_slot(
+        &self,
+        session_key: &mut SessionKey,
+        slot_id: &str,
+    ) -> StorageResult<()> {
+        self.ensure_writable()?;
+        session_key.revoke_slot(slot_id)?;
+        self.write_key_slots(session_key)
+    }
+
+    /// Réécrit les emplacements de clé du fichier sans re-chiffrer les données
+    fn write_key_slots(&self, session_key: &SessionKey) -> StorageResult<()> {
+        self.ensure_writable()?;
+        let unchanged = !self.has_changed_on_disk()?;
+        let mut encrypted = Self::read_encrypted(&self.data_path)?;
+
+        // Vérifie au passage que le fichier est bien chiffré avec la clé de la session
+        encrypted.replace_key_slots(session_key)?;
+
+        self.create_backup(BackupReason::Save)?;
+        let encrypted_json = serde_json::to_string_pretty(&encrypted)?;
+        atomic::write_atomic(&self.data_path, encrypted_json.as_bytes())?;
+
+        // Des données modifiées ailleurs restent à fusionner à la prochaine sauvegarde
+        if unchanged {
+            self.loaded_hash
+                .set(Some(Sha256::digest(encrypted_json.as_bytes()).into()));
+        }
 
         Ok(())
     }
@@ -292,85 +1210,206 @@ impl StorageManager {
         Ok(())
     }
 
-    /// Crée un backup du fichier actuel
-    fn create_backup(&self) -> StorageResult<()> {
+    /// Crée un backup du fichier actuel et l'ajoute au catalogue
+    ///
+    /// # Returns
+    /// L'entrée du backup, ou `None` si le fichier de données n'existe pas encore
+    fn create_backup(&self, reason: BackupReason) -> StorageResult<Option<BackupEntry>> {
         if !self.exists() {
-            return Ok(());
+            return Ok(None);
         }
 
-        // Nom du backup avec timestamp
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let backup_name = format!("sites-{}.encrypted", timestamp);
-        let backup_file = self.backup_path.join(backup_name);
-
-        // Copier le fichier
-        fs::copy(&self.data_path, &backup_file)?;
+        // Nombre de sites connu si le fichier est celui chargé ou écrit ici
+        let current: [u8; 32] = Sha256::digest(fs::read(&self.data_path)?).into();
+        let site_count = if self.loaded_hash.get() == Some(current) {
+            self.loaded_site_count.get()
+        } else {
+            None
+        };
 
-        Ok(())
+        let mut catalog = BackupCatalog::load(&self.backup_path)?;
+        catalog.add(&self.data_path, reason, site_count).map(Some)
     }
 
-    /// Liste les backups disponibles
-    pub fn list_backups(&self) -> StorageResult<Vec<String>> {
-        let mut backups = Vec::new();
+    /// Crée un backup à la demande de l'utilisateur (jamais supprimé par la rétention)
+    pub fn create_manual_backup(&self) -> StorageResult<BackupEntry> {
+        self.ensure_writable()?;
+        self.create_backup(BackupReason::Manual)?
+            .ok_or(AppError::NotInitialized)
+    }
 
-        if !self.backup_path.exists() {
-            return Ok(backups);
-        }
+    /// Liste les backups disponibles, du plus récent au plus ancien
+    pub fn list_backups(&self) -> StorageResult<Vec<BackupEntry>> {
+        Ok(BackupCatalog::load(&self.backup_path)?.entries().to_vec())
+    }
 
-        for entry in fs::read_dir(&self.backup_path)? {
-            let entry = entry?;
-            if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("sites-") && name.ends_with(".encrypted") {
-                    backups.push(name.to_string());
-                }
-            }
-        }
+    /// Restaure depuis un backup
+    ///
+    /// Le contenu du backup est re-chiffré avec la clé de session : les
+    /// emplacements de clé courants restent valables. Si le backup a été
+    /// chiffré avec une autre clé de données (ancien format), un mot de passe
+    /// capable de l'ouvrir doit être fourni.
+    ///
+    /// # Arguments
+    /// * `backup_id` - Identifiant du backup (voir `list_backups`)
+    /// * `session_key` - Clé de session courante
+    /// * `backup_password` - Mot de passe du backup s'il diffère
+    ///
+    /// # Returns
+    /// Les données restaurées
+    pub fn restore_backup(
+        &self,
+        backup_id: &str,
+        session_key: &SessionKey,
+        backup_password: Option<&str>,
+    ) -> StorageResult<AppData> {
+        self.ensure_writable()?;
+        let data = self.open_backup(backup_id, session_key, backup_password)?;
 
-        // Trier par ordre décroissant (plus récent en premier)
-        backups.sort_by(|a, b| b.cmp(a));
+        // Créer un backup du fichier actuel avant restauration
+        self.write_data(&data, session_key, Some(BackupReason::Restore))?;
 
-        Ok(backups)
+        Ok(data)
     }
 
-    /// Restaure depuis un backup
-    pub fn restore_backup(&self, backup_name: &str) -> StorageResult<()> {
-        let backup_file = self.backup_path.join(backup_name);
+    /// Déchiffre un backup du catalogue
+    ///
+    /// Le backup est désigné par son identifiant (jamais par un chemin), son
+    /// contenu doit correspondre à l'empreinte du catalogue et être une
+    /// enveloppe complète. La clé de session est essayée en premier ; un
+    /// backup chiffré avec une autre clé de données (ancien format) exige un
+    /// mot de passe capable de l'ouvrir.
+    pub fn open_backup(
+        &self,
+        backup_id: &str,
+        session_key: &SessionKey,
+        backup_password: Option<&str>,
+    ) -> StorageResult<AppData> {
+        let content = BackupCatalog::load(&self.backup_path)?.read(backup_id)?;
+        let encrypted: EncryptedData = serde_json::from_slice(&content)?;
+        encrypted.check_well_formed()?;
+
+        let (data, _) = match Self::decrypt_app_data(&encrypted, session_key) {
+            Ok(decrypted) => decrypted,
+            Err(_) => {
+                let password = backup_password.ok_or(AppError::BackupPasswordRequired)?;
+                let backup_key = SessionKey::open(&encrypted, password, None)?;
+                Self::decrypt_app_data(&encrypted, &backup_key)?
+            }
+        };
+        Ok(data)
+    }
 
-        if !backup_file.exists() {
-            return Err("Backup introuvable".into());
-        }
+    /// Restaure des sites (ou certaines de leurs listes) d'un backup sans
+    /// toucher aux autres sites du coffre
+    ///
+    /// Comme pour `apply_edit`, si le fichier a changé sur le disque depuis
+    /// le chargement, la restauration est appliquée à la version du disque.
+    ///
+    /// # Arguments
+    /// * `backup_id` - Identifiant du backup (voir `list_backups`)
+    /// * `selections` - Sites ou listes à restaurer
+    /// * `strategy` - Conduite à tenir pour un site déjà présent
+    /// * `current` - Données actuelles, remplacées par le résultat
+    /// * `session_key` - Clé de session courante
+    /// * `backup_password` - Mot de passe du backup s'il diffère
+    pub fn restore_sites(
+        &self,
+        backup_id: &str,
+        selections: &[SiteSelection],
+        strategy: ConflictStrategy,
+        current: &mut AppData,
+        session_key: &SessionKey,
+        backup_password: Option<&str>,
+    ) -> StorageResult<RestoreReport> {
+        self.ensure_writable()?;
+        let backup = self.open_backup(backup_id, session_key, backup_password)?;
 
         // Créer un backup du fichier actuel avant restauration
-        if self.exists() {
-            self.create_backup()?;
-        }
+        self.apply_edit_with_backup(current, session_key, BackupReason::Restore, |data| {
+            let report = restore::restore_sites(data, &backup, selections, strategy)?;
+            *data = report.data.clone();
+            Ok(report)
+        })
+    }
 
-        // Copier le backup
-        fs::copy(&backup_file, &self.data_path)?;
+    /// Compare deux backups, ou un backup et le coffre actuel
+    ///
+    /// # Arguments
+    /// * `from` - Identifiant du backup de départ
+    /// * `to` - Backup d'arrivée (`None` : le coffre actuel sur le disque)
+    /// * `session_key` - Clé de session courante
+    /// * `from_password` / `to_password` - Mots de passe des backups s'ils diffèrent
+    pub fn diff_backups(
+        &self,
+        from: &str,
+        to: Option<&str>,
+        session_key: &SessionKey,
+        from_password: Option<&str>,
+        to_password: Option<&str>,
+    ) -> StorageResult<VaultDiff> {
+        let old = self.open_backup(from, session_key, from_password)?;
+        let new = match to {
+            Some(name) => self.open_backup(name, session_key, to_password)?,
+            None => {
+                let encrypted = Self::read_encrypted(&self.data_path)?;
+                Self::decrypt_app_data(&encrypted, session_key)?.0
+            }
+        };
+        diff::diff(&old, &new)
+    }
 
-        Ok(())
+    /// Backups soumis à la rétention (tous sauf les backups manuels)
+    fn backup_files(&self) -> StorageResult<Vec<BackupFile>> {
+        let catalog = BackupCatalog::load(&self.backup_path)?;
+        Ok(catalog
+            .entries()
+            .iter()
+            .filter(|entry| entry.reason != Some(BackupReason::Manual))
+            .map(|entry| BackupFile {
+                name: entry.name.clone(),
+                timestamp: entry.created_at_ms / 1000,
+                size: entry.size,
+            })
+            .collect())
     }
 
-    /// Nettoie les vieux backups (garde les N plus récents)
-    pub fn cleanup_old_backups(&self, keep_count: usize) -> StorageResult<()> {
-        let backups = self.list_backups()?;
+    /// Aperçu de la rétention : backups qui seraient conservés et supprimés
+    ///
+    /// # Arguments
+    /// * `keep_days` - Nombre de jours pendant lesquels tous les backups sont gardés
+    pub fn retention_preview(&self, keep_days: u32) -> StorageResult<RetentionPlan> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Ok(retention::plan(
+            &self.backup_files()?,
+            now,
+            &RetentionPolicy::from_keep_days(keep_days),
+        ))
+    }
 
-        // Supprimer les backups au-delà de keep_count
-        for backup in backups.iter().skip(keep_count) {
-            let backup_file = self.backup_path.join(backup);
-            fs::remove_file(backup_file)?;
+    /// Supprime les backups au-delà de la politique de rétention
+    ///
+    /// # Returns
+    /// Le plan appliqué (backups conservés et supprimés)
+    pub fn apply_retention(&self, keep_days: u32) -> StorageResult<RetentionPlan> {
+        self.ensure_writable()?;
+        let plan = self.retention_preview(keep_days)?;
+        if !plan.delete.is_empty() {
+            let names: Vec<&str> = plan.delete.iter().map(|b| b.name.as_str()).collect();
+            BackupCatalog::load(&self.backup_path)?.remove(&names)?;
         }
-
-        Ok(())
+        Ok(plan)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backup_catalog::{CATALOG_COPY_FILE_NAME, CATALOG_FILE_NAME};
+    use crate::trash::TrashedRecord;
     use std::env;
 
     #[test]
@@ -441,4 +1480,871 @@ mod tests {
         // Nettoyage
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_cleanup_temp_files_in_backups() {
+        let temp_dir = env::temp_dir().join("cockpit_storage_cleanup_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let storage = StorageManager::new(&temp_dir).unwrap();
+
+        // Sauvegarde et backup interrompus
+        let leftover = format!(".sites.encrypted{}", atomic::TEMP_SUFFIX);
+        let backup_leftover = format!(".sites-1.encrypted{}", atomic::TEMP_SUFFIX);
+        fs::write(temp_dir.join(&leftover), b"vers").unwrap();
+        fs::write(storage.backup_path.join(&backup_leftover), b"vers").unwrap();
+        fs::write(storage.backup_path.join("sites-1.encrypted"), b"b1").unwrap();
+
+        assert_eq!(storage.cleanup_temp_files().unwrap(), 2);
+        assert!(!storage.backup_path.join(&backup_leftover).exists());
+        assert!(storage.backup_path.join("sites-1.encrypted").exists());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_load_upgrades_weaker_kdf_params() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_kdf_upgrade");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let weak = KdfParams {
+            memory: 8192,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let strong = KdfParams {
+            memory: 16384,
+            iterations: 2,
+            parallelism: 1,
+        };
+        let password = "test_password_123";
+
+        // Écrire un vault avec des paramètres faibles
+        let weak_storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(weak.clone());
+        weak_storage.initialize(password).unwrap();
+
+        // Le recharger avec une configuration plus forte
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(strong.clone());
+        storage.load(password).unwrap();
+
+        let encrypted: EncryptedData =
+            serde_json::from_str(&fs::read_to_string(&storage.data_path).unwrap()).unwrap();
+        assert_eq!(encrypted.key_slots[0].kdf_params, strong);
+        assert_eq!(storage.list_backups().unwrap().len(), 1);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    /// Paramètres légers pour garder les tests rapides
+    fn fast_params() -> KdfParams {
+        KdfParams {
+            memory: 8192,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    /// Écrit directement un coffre contenant le JSON fourni
+    fn write_raw_vault(storage: &StorageManager, json: &str, password: &str) {
+        let encrypted = CryptoEngine::encrypt_with_params(json, password, &fast_params()).unwrap();
+        fs::write(
+            &storage.data_path,
+            serde_json::to_string_pretty(&encrypted).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_migrates_v1_vault() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_migration");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+
+        // Coffre v1 : pas de schema_version ni de champs ajoutés ensuite
+        let v1 = r#"{
+            "sites": [{
+                "id": "old-site", "name": "Ancien site", "enabled": true,
+                "urls": {"frontend": "https://old.fr", "backend": "/administrator", "phpmyadmin": ""},
+                "dashlane_refs": {"backend_protection": null, "joomla_admin": "[Old] Admin",
+                                  "mysql_su": "[Old] SU", "mysql_std": null, "editors": []},
+                "server": {"mysql_host": "localhost", "database": "old", "prefix": "jos_", "ovh_vps": ""},
+                "tech": {"joomla_version": "3.10", "php_version": "7.4", "template": "Protostar"},
+                "analytics": null, "checklist": [], "interventions": [], "contacts": [],
+                "notes": "", "last_update": "2023-01-01T00:00:00Z"
+            }],
+            "settings": {"auto_lock_minutes": 5, "auto_backup": true,
+                         "backup_keep_days": 30, "dashlane_cli_path": "auto"}
+        }"#;
+        write_raw_vault(&storage, v1, password);
+
+        let data = storage.load(password).unwrap();
+        assert_eq!(data.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(data.sites[0].id, "old-site");
+
+        // Un backup a été créé avant la migration et le fichier a été réécrit
+        assert_eq!(storage.list_backups().unwrap().len(), 1);
+        let encrypted: EncryptedData =
+            serde_json::from_str(&fs::read_to_string(&storage.data_path).unwrap()).unwrap();
+        let raw: Value =
+            serde_json::from_str(&CryptoEngine::decrypt(&encrypted, password).unwrap()).unwrap();
+        assert_eq!(raw["schema_version"], CURRENT_SCHEMA_VERSION);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_load_refuses_newer_schema() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_newer_schema");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+
+        let future = format!(
+            r#"{{"schema_version": {}, "sites": [], "settings": {{}}}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        write_raw_vault(&storage, &future, password);
+
+        let err = storage.load(password).unwrap_err();
+        assert!(err.to_string().contains("plus récent"));
+        assert!(storage.list_backups().unwrap().is_empty());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_session_key_save_and_restore() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_session_key");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+
+        // Sauvegarder avec la clé de session uniquement
+        let (mut data, session_key) = storage.unlock(password, None).unwrap();
+        data.settings.auto_lock_minutes = 42;
+        storage.save_with_key(&data, &session_key, true).unwrap();
+        assert_eq!(
+            storage.load(password).unwrap().settings.auto_lock_minutes,
+            42
+        );
+
+        // Le catalogue décrit le backup créé avant l'écriture
+        let backups = storage.list_backups().unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].reason, Some(BackupReason::Save));
+        assert_eq!(backups[0].site_count, Some(0));
+        assert_eq!(backups[0].format_version.as_deref(), Some(FORMAT_VERSION));
+
+        // Ce que la restauration changerait par rapport au coffre actuel
+        let diff = storage
+            .diff_backups(&backups[0].id, None, &session_key, None, None)
+            .unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+        assert_eq!(diff.settings.len(), 1);
+        assert_eq!(diff.settings[0].path, "auto_lock_minutes");
+        assert_eq!(diff.settings[0].new, Some(serde_json::json!(42)));
+
+        // Restaurer le backup (état initial) avec la même clé
+        let restored = storage
+            .restore_backup(&backups[0].id, &session_key, None)
+            .unwrap();
+        assert_eq!(restored.settings.auto_lock_minutes, 5);
+        assert_eq!(
+            storage.load(password).unwrap().settings.auto_lock_minutes,
+            5
+        );
+        let backups = storage.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].reason, Some(BackupReason::Restore));
+        assert!(matches!(
+            storage.restore_backup("0000000000000000", &session_key, None),
+            Err(AppError::BackupNotFound(_))
+        ));
+
+        // Les deux écritures sont au journal, avec leur auteur
+        let events = storage
+            .query_journal(&session_key, &JournalQuery::default())
+            .unwrap()
+            .events;
+        let values: Vec<_> = events.iter().map(|e| e.new.clone()).collect();
+        assert_eq!(
+            values,
+            vec![Some(serde_json::json!(42)), Some(serde_json::json!(5))]
+        );
+        assert!(events
+            .iter()
+            .all(|e| e.site_id.is_none() && e.path == "auto_lock_minutes"));
+        assert_eq!(events[0].author, journal::author());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_state_at_replays_journal() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_state_at");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        storage.initialize("test_password_123").unwrap();
+        let (mut data, session_key) = storage.unlock("test_password_123", None).unwrap();
+
+        // Trois écritures à des dates distinctes, sans backup
+        data.sites.push(Site::sample("a", "Site A"));
+        storage.save_with_key(&data, &session_key, false).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        data.sites[0].tech.php_version = "8.3".to_string();
+        storage.save_with_key(&data, &session_key, false).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        data.sites.clear();
+        storage.save_with_key(&data, &session_key, false).unwrap();
+
+        let events = storage
+            .query_journal(&session_key, &JournalQuery::default())
+            .unwrap()
+            .events;
+        let (created, updated) = (events[0].at_ms, events[1].at_ms);
+
+        // Rejoué à rebours depuis le coffre actuel
+        let state = storage.state_at(created, None, &session_key).unwrap();
+        assert_eq!(state.snapshot_id, None);
+        assert_eq!(state.replayed, 2);
+        assert_eq!(state.data.sites[0].tech.php_version, "8.1");
+        // Site supprimé recréé depuis le journal : ses références Dashlane,
+        // masquées, sont inconnues (vides)
+        let unresolved: Vec<_> = state.unresolved.iter().map(|u| u.path.as_str()).collect();
+        assert_eq!(unresolved, vec![""]);
+        assert_eq!(state.data.sites[0].dashlane_refs.joomla_admin, "");
+
+        let site = storage.site_at("a", updated, &session_key).unwrap().data;
+        assert_eq!(site.unwrap().tech.php_version, "8.3");
+        assert!(storage
+            .site_at("a", created - 1, &session_key)
+            .unwrap()
+            .data
+            .is_none());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_restore_sites_keeps_changes_made_elsewhere() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_restore_sites_disk");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+        let (mut current, session_key) = storage.unlock(password, None).unwrap();
+        current.sites.push(Site::sample("a", "Site A"));
+        storage
+            .save_with_key(&current, &session_key, false)
+            .unwrap();
+        current.sites[0].notes = "Note erronée".to_string();
+        storage.save_with_key(&current, &session_key, true).unwrap();
+        let backup_id = storage.list_backups().unwrap()[0].id.clone();
+
+        // Un autre poste ajoute un site pendant ce temps
+        let other = StorageManager::new(&temp_dir).unwrap();
+        let (mut elsewhere, other_key) = other.unlock(password, None).unwrap();
+        elsewhere.sites.push(Site::sample("b", "Site B"));
+        other.save_with_key(&elsewhere, &other_key, false).unwrap();
+
+        let selections = vec![SiteSelection {
+            site_id: "a".to_string(),
+            collections: None,
+        }];
+        storage
+            .restore_sites(
+                &backup_id,
+                &selections,
+                ConflictStrategy::Overwrite,
+                &mut current,
+                &session_key,
+                None,
+            )
+            .unwrap();
+
+        // Le site ajouté ailleurs est conservé
+        assert_eq!(current.sites[0].notes, "");
+        let ids: Vec<_> = current.sites.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(storage.load(password).unwrap().sites.len(), 2);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_unlock_purges_expired_trash() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_trash_purge");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+
+        // Un site supprimé il y a longtemps, un autre à l'instant
+        let (mut data, session_key) = storage.unlock(password, None).unwrap();
+        data.sites = vec![Site::sample("a", "Site A"), Site::sample("b", "Site B")];
+        trash::trash_site(&mut data, "a").unwrap();
+        trash::trash_site(&mut data, "b").unwrap();
+        data.trash[0].deleted_at_ms = 0;
+        storage.save_with_key(&data, &session_key, false).unwrap();
+
+        let data = storage.load(password).unwrap();
+        assert_eq!(data.trash.len(), 1);
+        assert!(matches!(&data.trash[0].record, TrashedRecord::Site { site } if site.id == "b"));
+        // Le backup pris avant la purge garde le site "a"
+        let backups = storage.list_backups().unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].reason, Some(BackupReason::Save));
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_restore_rejects_traversal_and_malformed_backups() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_restore_traversal");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+        let (mut data, session_key) = storage.unlock(password, None).unwrap();
+        data.settings.auto_lock_minutes = 42;
+        storage.save_with_key(&data, &session_key, true).unwrap();
+        let vault = fs::read(&storage.data_path).unwrap();
+
+        // Un fichier chiffré avec la même clé, placé hors du dossier des backups
+        fs::copy(&storage.data_path, temp_dir.join("autre.encrypted")).unwrap();
+        for name in [
+            "../autre.encrypted",
+            "../sites.encrypted",
+            "..\\autre.encrypted",
+            "backups/../autre.encrypted",
+            temp_dir.join("autre.encrypted").to_str().unwrap(),
+        ] {
+            assert!(
+                matches!(
+                    storage.restore_backup(name, &session_key, None),
+                    Err(AppError::InvalidInput(_))
+                ),
+                "{name}"
+            );
+        }
+
+        // Backup modifié après sa création : refusé avant toute écriture
+        let backups = storage.list_backups().unwrap();
+        let backup_file = storage.backup_path.join(&backups[0].name);
+        let hollow = format!(
+            r#"{{"version":"{}","algorithm":"AES-256-GCM","kdf":"Argon2id",
+                "nonce":"","ciphertext":"","auth_tag":""}}"#,
+            FORMAT_VERSION
+        );
+        fs::write(&backup_file, hollow).unwrap();
+        assert!(matches!(
+            storage.restore_backup(&backups[0].id, &session_key, None),
+            Err(AppError::CorruptVault(_))
+        ));
+
+        // Catalogue recalculé pour ce fichier : l'enveloppe incomplète est refusée
+        fs::remove_file(storage.backup_path.join(CATALOG_FILE_NAME)).unwrap();
+        fs::remove_file(storage.backup_path.join(CATALOG_COPY_FILE_NAME)).unwrap();
+        let backups = storage.list_backups().unwrap();
+        assert!(matches!(
+            storage.restore_backup(&backups[0].id, &session_key, None),
+            Err(AppError::CorruptVault(message)) if message.contains("vide")
+        ));
+
+        assert_eq!(fs::read(&storage.data_path).unwrap(), vault);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_retention_after_save() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_retention");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+
+        // Anciens backups : trois le même jour il y a 60 jours, un récent,
+        // et un fichier renommé à la main
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let day = 24 * 60 * 60;
+        let old_day = (now / day - 60) * day;
+        let names: Vec<String> = [old_day + 3, old_day + 2, old_day + 1, now - day]
+            .iter()
+            .map(|ts| format!("sites-{}.encrypted", ts))
+            .collect();
+        for name in &names {
+            fs::write(storage.backup_path.join(name), b"{}").unwrap();
+        }
+        fs::write(
+            storage.backup_path.join("sites-avant-migration.encrypted"),
+            b"{}",
+        )
+        .unwrap();
+
+        // Aperçu : rien n'est supprimé
+        let preview = storage.retention_preview(30).unwrap();
+        let to_delete: Vec<&str> = preview.delete.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(to_delete, vec![names[1].as_str(), names[2].as_str()]);
+        assert_eq!(preview.delete_bytes, 4);
+        assert_eq!(storage.list_backups().unwrap().len(), 5);
+
+        // Une sauvegarde applique la rétention du réglage
+        let (mut data, session_key) = storage.unlock(password, None).unwrap();
+        data.settings.backup_keep_days = 30;
+        storage.save_with_key(&data, &session_key, true).unwrap();
+
+        let remaining: Vec<String> = storage
+            .list_backups()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(remaining.len(), 4);
+        assert!(remaining.contains(&names[0]));
+        assert!(remaining.contains(&names[3]));
+        // Fichier renommé à la main : traité comme un backup manuel
+        assert!(remaining.contains(&"sites-avant-migration.encrypted".to_string()));
+        assert!(storage.retention_preview(30).unwrap().delete.is_empty());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_unlock_upgrades_password_format() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_format_upgrade");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params());
+        let password = "test_password_123";
+
+        // Coffre 1.1 : clé dérivée directement du mot de passe
+        let json = serde_json::to_string(&AppData::default()).unwrap();
+        let legacy = crate::crypto::encrypt_password_format(
+            &json,
+            password,
+            &fast_params(),
+            crate::crypto::PASSWORD_FORMAT_VERSION,
+        );
+        fs::write(&storage.data_path, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        // Le déverrouillage convertit le coffre au format à emplacements de clé
+        let (_, session_key) = storage.unlock(password, None).unwrap();
+        let encrypted = StorageManager::read_encrypted(&storage.data_path).unwrap();
+        assert_eq!(encrypted.version, FORMAT_VERSION);
+        assert_eq!(encrypted.key_slots.len(), 1);
+        assert!(encrypted.salt.is_none());
+
+        // Le backup de l'ancien format exige son mot de passe pour être restauré
+        let backups = storage.list_backups().unwrap();
+        assert!(storage
+            .restore_backup(&backups[0].id, &session_key, None)
+            .is_err());
+        storage
+            .restore_backup(&backups[0].id, &session_key, Some(password))
+            .unwrap();
+        assert!(storage.load(password).is_ok());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_key_slot_management() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_key_slots");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(0);
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+        let (_, mut session_key) = storage.unlock(password, None).unwrap();
+        let ciphertext = StorageManager::read_encrypted(&storage.data_path)
+            .unwrap()
+            .ciphertext;
+
+        // Clé de secours et mot de passe collègue, sans re-saisir le mot de passe maître
+        let recovery_key = storage
+            .add_recovery_key(&mut session_key, "Clé de secours")
+            .unwrap()
+            .key
+            .clone();
+        let colleague = storage
+            .add_password_slot(&mut session_key, "Collègue", "collegue_789")
+            .unwrap();
+        assert!(storage.load(&recovery_key).is_ok());
+        assert!(storage.load("collegue_789").is_ok());
+
+        // Changer le mot de passe maître ne touche ni aux données ni aux autres emplacements
+        storage
+            .change_password(&mut session_key, password, "nouveau_mdp_456", None)
+            .unwrap();
+        assert!(storage.load(password).is_err());
+        assert!(storage.load("nouveau_mdp_456").is_ok());
+        assert!(storage.load("collegue_789").is_ok());
+
+        // Révoquer l'accès du collègue
+        storage
+            .revoke_key_slot(&mut session_key, &colleague.id)
+            .unwrap();
+        assert!(storage.load("collegue_789").is_err());
+
+        let encrypted = StorageManager::read_encrypted(&storage.data_path).unwrap();
+        assert_eq!(encrypted.ciphertext, ciphertext);
+        assert_eq!(encrypted.key_slots.len(), 2);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_keyfile_attach_and_remove() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_keyfile");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(0);
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+        let (_, mut session_key) = storage.unlock(password, None).unwrap();
+
+        // Générer un fichier clé et l'exiger au déverrouillage
+        let keyfile_path = temp_dir.join("cockpit.key");
+        let keyfile = Keyfile::generate(&keyfile_path).unwrap();
+        let key_content = fs::read(&keyfile_path).unwrap();
+        assert!(matches!(
+            Keyfile::generate(&keyfile_path),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!(fs::read(&keyfile_path).unwrap(), key_content);
+        storage
+            .attach_keyfile(&mut session_key, password, None, &keyfile)
+            .unwrap();
+        assert!(storage.requires_keyfile().unwrap());
+
+        assert!(storage.unlock(password, None).is_err());
+        let reread = Keyfile::from_file(&keyfile_path).unwrap();
+        assert!(storage.unlock(password, Some(&reread)).is_ok());
+
+        // Changer le mot de passe conserve l'exigence du fichier clé
+        storage
+            .change_password(
+                &mut session_key,
+                password,
+                "nouveau_mdp_456",
+                Some(&keyfile),
+            )
+            .unwrap();
+        assert!(storage.unlock("nouveau_mdp_456", None).is_err());
+        assert!(storage.unlock("nouveau_mdp_456", Some(&keyfile)).is_ok());
+
+        // Retirer le fichier clé
+        storage
+            .remove_keyfile(&mut session_key, "nouveau_mdp_456", &keyfile)
+            .unwrap();
+        assert!(!storage.requires_keyfile().unwrap());
+        assert!(storage.unlock("nouveau_mdp_456", None).is_ok());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_unlock_throttling() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_throttle");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(60);
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+
+        // Un échec impose un délai, même pour le bon mot de passe
+        assert!(matches!(
+            storage.unlock("mauvais", None),
+            Err(AppError::WrongPassword)
+        ));
+        assert!(storage.unlock_wait() > 0);
+        assert!(matches!(
+            storage.unlock(password, None),
+            Err(AppError::Throttled(_))
+        ));
+
+        // Le compteur est conservé d'une instance à l'autre, et remis à zéro
+        // par un déverrouillage réussi
+        let restarted = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(0);
+        assert_eq!(restarted.throttle.failures(), 1);
+        assert!(restarted.unlock(password, None).is_ok());
+        assert_eq!(restarted.throttle.failures(), 0);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_read_only_refuses_writes() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_read_only");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let mut storage = StorageManager::new(&temp_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(0);
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+
+        // Le verrou d'écriture est posé à côté du coffre, et retiré à la libération
+        storage.acquire_lock(false).unwrap();
+        assert!(VaultLock::holder(&temp_dir).is_some());
+        storage.release_lock();
+        assert!(VaultLock::holder(&temp_dir).is_none());
+
+        // En lecture seule : lecture possible, aucune écriture
+        storage.acquire_lock(true).unwrap();
+        assert!(storage.is_read_only());
+        assert!(VaultLock::holder(&temp_dir).is_none());
+        let (data, mut session_key) = storage.unlock(password, None).unwrap();
+        assert!(matches!(
+            storage.save_with_key(&data, &session_key, true),
+            Err(AppError::ReadOnly)
+        ));
+        assert!(matches!(
+            storage.add_recovery_key(&mut session_key, "Clé de secours"),
+            Err(AppError::ReadOnly)
+        ));
+        assert_eq!(session_key.key_slots().len(), 1);
+
+        storage.release_lock();
+        assert!(storage.save_with_key(&data, &session_key, true).is_ok());
+
+        // Verrou perdu (repris par une autre instance) : bascule signalée une seule fois
+        storage.acquire_lock(false).unwrap();
+        assert!(!storage.keep_lock_alive().unwrap());
+        fs::remove_file(temp_dir.join(crate::vault_lock::LOCK_FILE_NAME)).unwrap();
+        assert!(storage.keep_lock_alive().unwrap());
+        assert!(storage.is_read_only());
+        assert!(!storage.keep_lock_alive().unwrap());
+        assert!(matches!(
+            storage.save_with_key(&data, &session_key, true),
+            Err(AppError::ReadOnly)
+        ));
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_relocate_moves_vault_and_lock() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_relocate");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let old_dir = temp_dir.join("ancien");
+        let new_dir = temp_dir.join("nouveau");
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+
+        let mut storage = StorageManager::new(&old_dir)
+            .unwrap()
+            .with_kdf_params(fast_params())
+            .with_unlock_base_delay(0);
+        let password = "test_password_123";
+        storage.initialize(password).unwrap();
+        storage.acquire_lock(false).unwrap();
+        let (mut data, session_key) = storage.unlock(password, None).unwrap();
+        storage.create_manual_backup().unwrap();
+
+        // Échec de l'enregistrement de la configuration : rien ne bouge
+        let result = storage.relocate(&new_dir, true, || {
+            Err(AppError::InvalidInput("config".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(&new_dir).unwrap().count(), 0);
+        assert_eq!(storage.get_data_dir(), old_dir.as_path());
+        assert!(VaultLock::holder(&old_dir).is_some());
+
+        let report = storage.relocate(&new_dir, true, || Ok(())).unwrap();
+        assert!(report.leftovers.is_empty());
+        assert_eq!(storage.get_data_dir(), new_dir.as_path());
+        assert!(!old_dir.join("sites.encrypted").exists());
+        assert!(VaultLock::holder(&old_dir).is_none());
+        assert!(VaultLock::holder(&new_dir).is_some());
+        assert_eq!(storage.list_backups().unwrap().len(), 1);
+
+        // Le coffre déplacé reste utilisable sans nouveau déverrouillage
+        data.sites.push(Site::sample("a", "Site A"));
+        storage.save_with_key(&data, &session_key, false).unwrap();
+        let (reloaded, _) = storage.unlock(password, None).unwrap();
+        assert_eq!(reloaded.sites.len(), 1);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_save_merges_concurrent_changes() {
+        let temp_dir = env::temp_dir().join("fluent_app_test_concurrent");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let password = "test_password_123";
+        let open = || {
+            StorageManager::new(&temp_dir)
+                .unwrap()
+                .with_kdf_params(fast_params())
+                .with_unlock_base_delay(0)
+        };
+        let setup = open();
+        let mut data = AppData::default();
+        data.sites.push(Site::sample("a", "Site A"));
+        setup.save(&data, password, false).unwrap();
+
+        // Deux postes ouvrent le même coffre synchronisé
+        let ours = open();
+        let theirs = open();
+        let (mut base, key) = ours.unlock(password, None).unwrap();
+        let (mut their_data, their_key) = theirs.unlock(password, None).unwrap();
+        assert!(!ours.has_changed_on_disk().unwrap());
+
+        // Un collègue ajoute un site pendant qu'on modifie les notes de A
+        their_data.sites.push(Site::sample("b", "Site B"));
+        theirs
+            .save_with_key(&their_data, &their_key, false)
+            .unwrap();
+        assert!(ours.has_changed_on_disk().unwrap());
+
+        let mut local = base.clone();
+        local.sites[0].notes = "modifié ici".to_string();
+        let outcome = ours.save_checked(&mut base, &local, &key, false).unwrap();
+        let SaveOutcome::Merged { data: merged } = outcome else {
+            panic!("fusion attendue");
+        };
+        assert_eq!(merged.sites.len(), 2);
+        assert_eq!(merged.sites[0].notes, "modifié ici");
+        assert_eq!(ours.load(password).unwrap().sites.len(), 2);
+
+        // Même champ modifié des deux côtés : rien n'est écrit
+        let (mut their_data, _) = theirs.unlock(password, None).unwrap();
+        their_data.sites[0].notes = "modifié ailleurs".to_string();
+        theirs
+            .save_with_key(&their_data, &their_key, false)
+            .unwrap();
+
+        let mut local = base.clone();
+        local.sites[0].notes = "encore modifié ici".to_string();
+        let outcome = ours.save_checked(&mut base, &local, &key, false).unwrap();
+        let SaveOutcome::Conflicts {
+            data: proposal,
+            conflicts,
+        } = outcome
+        else {
+            panic!("conflit attendu");
+        };
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "notes");
+        assert_eq!(
+            ours.load(password).unwrap().sites[0].notes,
+            "modifié ailleurs"
+        );
+
+        // Après arbitrage, la sauvegarde suivante n'est plus bloquée
+        assert_eq!(base.sites[0].notes, "modifié ailleurs");
+        let outcome = ours
+            .save_checked(&mut base, &proposal, &key, false)
+            .unwrap();
+        assert!(matches!(outcome, SaveOutcome::Saved));
+        assert_eq!(
+            ours.load(password).unwrap().sites[0].notes,
+            "encore modifié ici"
+        );
+
+        // Modification ciblée : appliquée à la version du disque
+        let (mut their_data, _) = theirs.unlock(password, None).unwrap();
+        their_data.sites.push(Site::sample("c", "Site C"));
+        theirs
+            .save_with_key(&their_data, &their_key, false)
+            .unwrap();
+        let item_id = ours
+            .apply_edit(&mut base, &key, |data| trash::trash_site(data, "b"))
+            .unwrap();
+        assert_eq!(base.trash[0].id, item_id);
+        let ids: Vec<String> = ours
+            .load(password)
+            .unwrap()
+            .sites
+            .iter()
+            .map(|s| s.id.clone())
+            .collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(base.sites.len(), 2);
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -0,0 +1,174 @@
+// src-tauri/src/throttle.rs
+// Limitation des tentatives de déverrouillage
+// Les échecs sont comptés dans un petit fichier à côté de sites.encrypted,
+// pour que le délai survive à un redémarrage de l'application
+
+use crate::atomic;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nom du fichier de suivi des échecs
+pub const THROTTLE_FILE_NAME: &str = "unlock-attempts.json";
+
+/// Délai de base après le premier échec (secondes)
+pub const DEFAULT_BASE_DELAY_SECS: u64 = 1;
+
+/// Délai maximal entre deux tentatives (secondes)
+const MAX_DELAY_SECS: u64 = 15 * 60;
+
+/// État persistant des échecs de déverrouillage
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+struct Attempts {
+    /// Échecs consécutifs depuis le dernier déverrouillage réussi
+    failures: u32,
+
+    /// Date du dernier échec (timestamp Unix)
+    last_failure: u64,
+}
+
+/// Délai croissant entre les tentatives de déverrouillage
+///
+/// Le délai double à chaque échec (1 s, 2 s, 4 s…) jusqu'à 15 minutes.
+pub struct UnlockThrottle {
+    path: PathBuf,
+    base_delay_secs: u64,
+}
+
+impl UnlockThrottle {
+    /// Crée le suivi des tentatives pour le dossier de données `data_dir`
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(THROTTLE_FILE_NAME),
+            base_delay_secs: DEFAULT_BASE_DELAY_SECS,
+        }
+    }
+
+    /// Définit le délai après le premier échec (0 = compter sans attendre)
+    pub fn with_base_delay(mut self, base_delay_secs: u64) -> Self {
+        self.base_delay_secs = base_delay_secs;
+        self
+    }
+
+    /// Même réglage, pour le dossier de données `data_dir` (après un déplacement)
+    pub fn moved_to(&self, data_dir: &Path) -> Self {
+        Self::new(data_dir).with_base_delay(self.base_delay_secs)
+    }
+
+    /// Nombre d'échecs consécutifs enregistrés
+    pub fn failures(&self) -> u32 {
+        self.read().failures
+    }
+
+    /// Secondes à attendre avant la prochaine tentative (0 si aucune attente)
+    pub fn remaining_wait(&self) -> u64 {
+        let attempts = self.read();
+        let delay = self.delay_after(attempts.failures);
+        let now = unix_now();
+
+        // Horloge reculée : on ne raccourcit pas le délai
+        if attempts.last_failure > now {
+            return delay;
+        }
+        (attempts.last_failure + delay).saturating_sub(now)
+    }
+
+    /// Refuse la tentative si le délai n'est pas écoulé
+    pub fn check(&self) -> AppResult<()> {
+        match self.remaining_wait() {
+            0 => Ok(()),
+            remaining => Err(AppError::Throttled(remaining)),
+        }
+    }
+
+    /// Enregistre un échec
+    pub fn record_failure(&self) -> AppResult<()> {
+        let mut attempts = self.read();
+        attempts.failures = attempts.failures.saturating_add(1);
+        attempts.last_failure = unix_now();
+        // Une écriture interrompue ne doit pas remettre le compteur à zéro
+        atomic::write_atomic(&self.path, serde_json::to_string(&attempts)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Remet le compteur à zéro après un déverrouillage réussi
+    pub fn reset(&self) -> AppResult<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Délai imposé après `failures` échecs
+    fn delay_after(&self, failures: u32) -> u64 {
+        match failures {
+            0 => 0,
+            n => self
+                .base_delay_secs
+                .saturating_mul(1u64 << (n - 1).min(20))
+                .min(MAX_DELAY_SECS),
+        }
+    }
+
+    /// Lit l'état ; un fichier illisible compte comme un échec récent
+    /// plutôt que d'effacer le compteur
+    fn read(&self) -> Attempts {
+        match fs::read_to_string(&self.path) {
+            Err(_) => Attempts::default(),
+            Ok(content) => serde_json::from_str(&content).unwrap_or(Attempts {
+                failures: 1,
+                last_failure: unix_now(),
+            }),
+        }
+    }
+}
+
+/// Secondes écoulées depuis l'epoch Unix
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_throttle_delay_grows_and_resets() {
+        let temp_dir = env::temp_dir().join("cockpit_throttle_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let throttle = UnlockThrottle::new(&temp_dir).with_base_delay(10);
+        assert!(throttle.check().is_ok());
+
+        // Le délai double à chaque échec
+        throttle.record_failure().unwrap();
+        assert!(throttle.remaining_wait() > 0 && throttle.remaining_wait() <= 10);
+        throttle.record_failure().unwrap();
+        assert!(throttle.remaining_wait() > 10 && throttle.remaining_wait() <= 20);
+        assert!(matches!(throttle.check(), Err(AppError::Throttled(_))));
+        assert_eq!(throttle.delay_after(30), MAX_DELAY_SECS);
+
+        // Le compteur survit à une nouvelle instance (redémarrage)
+        let restarted = UnlockThrottle::new(&temp_dir).with_base_delay(10);
+        assert_eq!(restarted.failures(), 2);
+        assert!(restarted.check().is_err());
+
+        // Un fichier modifié à la main ne remet pas le compteur à zéro
+        fs::write(temp_dir.join(THROTTLE_FILE_NAME), "{}garbage").unwrap();
+        assert!(restarted.check().is_err());
+
+        restarted.reset().unwrap();
+        assert_eq!(restarted.failures(), 0);
+        assert!(restarted.check().is_ok());
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -0,0 +1,304 @@
+// src-tauri/src/trash.rs
+// Corbeille du coffre
+// Un site, une intervention ou un contact supprimé n'est pas effacé : il est
+// déplacé dans la corbeille (à l'intérieur du coffre chiffré) avec sa date de
+// suppression, d'où il peut être remis à sa place ou purgé. Les éléments
+// plus anciens que `trash_keep_days` sont purgés au déverrouillage
+
+use crate::backup_catalog::unix_millis;
+use crate::error::{AppError, AppResult};
+use crate::retention::DAY_SECS;
+use crate::storage::{AppData, Contact, Intervention, Site};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Longueur d'un identifiant d'élément (octets aléatoires en hexadécimal)
+const ID_LEN: usize = 16;
+
+/// Élément supprimé
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrashedRecord {
+    /// Site entier, avec ses listes
+    Site { site: Box<Site> },
+
+    /// Intervention du site `site_id`
+    Intervention {
+        site_id: String,
+        intervention: Intervention,
+    },
+
+    /// Contact du site `site_id`
+    Contact { site_id: String, contact: Contact },
+}
+
+/// Entrée de la corbeille
+#[derive(Serialize, Deserialize, Clone, Debug, Zeroize, ZeroizeOnDrop)]
+pub struct TrashItem {
+    /// Identifiant opaque, utilisé par les commandes
+    pub id: String,
+
+    /// Date de suppression (timestamp Unix en millisecondes)
+    pub deleted_at_ms: u64,
+
+    /// Position d'origine dans sa liste, pour la restauration
+    pub index: usize,
+
+    #[serde(flatten)]
+    pub record: TrashedRecord,
+}
+
+/// Déplace le site `site_id` dans la corbeille
+///
+/// # Returns
+/// L'identifiant de l'entrée de la corbeille
+pub fn trash_site(data: &mut AppData, site_id: &str) -> AppResult<String> {
+    let index = site_index(data, site_id)?;
+    let site = data.sites.remove(index);
+    Ok(push(
+        data,
+        index,
+        TrashedRecord::Site {
+            site: Box::new(site),
+        },
+    ))
+}
+
+/// Déplace l'intervention `index` du site `site_id` dans la corbeille
+pub fn trash_intervention(data: &mut AppData, site_id: &str, index: usize) -> AppResult<String> {
+    let site_position = site_index(data, site_id)?;
+    let site = &mut data.sites[site_position];
+    if index >= site.interventions.len() {
+        return Err(AppError::InvalidInput(format!(
+            "Intervention introuvable : {} (site {})",
+            index, site_id
+        )));
+    }
+    let intervention = site.interventions.remove(index);
+    let record = TrashedRecord::Intervention {
+        site_id: site_id.to_string(),
+        intervention,
+    };
+    Ok(push(data, index, record))
+}
+
+/// Déplace le contact `index` du site `site_id` dans la corbeille
+pub fn trash_contact(data: &mut AppData, site_id: &str, index: usize) -> AppResult<String> {
+    let site_position = site_index(data, site_id)?;
+    let site = &mut data.sites[site_position];
+    if index >= site.contacts.len() {
+        return Err(AppError::InvalidInput(format!(
+            "Contact introuvable : {} (site {})",
+            index, site_id
+        )));
+    }
+    let contact = site.contacts.remove(index);
+    let record = TrashedRecord::Contact {
+        site_id: site_id.to_string(),
+        contact,
+    };
+    Ok(push(data, index, record))
+}
+
+/// Remet un élément de la corbeille à sa place d'origine
+///
+/// # Errors
+/// `InvalidInput` si un site porte déjà l'identifiant du site à restaurer,
+/// ou si le site d'une intervention ou d'un contact n'existe plus (il faut
+/// d'abord le restaurer)
+pub fn restore(data: &mut AppData, item_id: &str) -> AppResult<()> {
+    let position = item_position(data, item_id)?;
+    let item = &data.trash[position];
+
+    // Vérifier avant de retirer l'élément de la corbeille
+    match &item.record {
+        TrashedRecord::Site { site } => {
+            if data.sites.iter().any(|s| s.id == site.id) {
+                return Err(AppError::InvalidInput(format!(
+                    "Un site porte déjà l'identifiant {}",
+                    site.id
+                )));
+            }
+        }
+        TrashedRecord::Intervention { site_id, .. } | TrashedRecord::Contact { site_id, .. } => {
+            site_index(data, site_id).map_err(|_| {
+                AppError::InvalidInput(format!(
+                    "Site introuvable : {} (restaurez d'abord le site)",
+                    site_id
+                ))
+            })?;
+        }
+    }
+
+    // L'entrée retirée est effacée de la mémoire à sa destruction : son
+    // contenu est copié à sa place d'origine
+    let item = data.trash.remove(position);
+    let index = item.index;
+    match &item.record {
+        TrashedRecord::Site { site } => {
+            data.sites
+                .insert(index.min(data.sites.len()), site.as_ref().clone());
+        }
+        TrashedRecord::Intervention {
+            site_id,
+            intervention,
+        } => {
+            let site_position = site_index(data, site_id)?;
+            let site = &mut data.sites[site_position];
+            site.interventions
+                .insert(index.min(site.interventions.len()), intervention.clone());
+        }
+        TrashedRecord::Contact { site_id, contact } => {
+            let site_position = site_index(data, site_id)?;
+            let site = &mut data.sites[site_position];
+            site.contacts
+                .insert(index.min(site.contacts.len()), contact.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Supprime définitivement un élément de la corbeille
+pub fn purge(data: &mut AppData, item_id: &str) -> AppResult<()> {
+    let position = item_position(data, item_id)?;
+    data.trash.remove(position);
+    Ok(())
+}
+
+/// Vide la corbeille
+///
+/// # Returns
+/// Le nombre d'éléments supprimés
+pub fn empty(data: &mut AppData) -> usize {
+    let count = data.trash.len();
+    data.trash.clear();
+    count
+}
+
+/// Éléments supprimés depuis plus de `trash_keep_days` jours (0 = jamais purgés)
+pub fn expired(data: &AppData, now_ms: u64) -> Vec<&TrashItem> {
+    let keep_days = u64::from(data.settings.trash_keep_days);
+    if keep_days == 0 {
+        return Vec::new();
+    }
+    let keep_ms = keep_days * DAY_SECS * 1000;
+    data.trash
+        .iter()
+        .filter(|item| now_ms.saturating_sub(item.deleted_at_ms) > keep_ms)
+        .collect()
+}
+
+/// Purge les éléments expirés (voir `expired`)
+///
+/// # Returns
+/// Le nombre d'éléments supprimés
+pub fn purge_expired(data: &mut AppData, now_ms: u64) -> usize {
+    let expired: Vec<String> = expired(data, now_ms)
+        .into_iter()
+        .map(|item| item.id.clone())
+        .collect();
+    data.trash.retain(|item| !expired.contains(&item.id));
+    expired.len()
+}
+
+fn push(data: &mut AppData, index: usize, record: TrashedRecord) -> String {
+    let mut id = [0u8; ID_LEN / 2];
+    rand::thread_rng().fill_bytes(&mut id);
+    let id: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+
+    data.trash.push(TrashItem {
+        id: id.clone(),
+        deleted_at_ms: unix_millis(),
+        index,
+        record,
+    });
+    id
+}
+
+fn site_index(data: &AppData, site_id: &str) -> AppResult<usize> {
+    data.sites
+        .iter()
+        .position(|s| s.id == site_id)
+        .ok_or_else(|| AppError::InvalidInput(format!("Site introuvable : {}", site_id)))
+}
+
+fn item_position(data: &AppData, item_id: &str) -> AppResult<usize> {
+    data.trash
+        .iter()
+        .position(|item| item.id == item_id)
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Élément introuvable dans la corbeille : {}",
+                item_id
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str) -> Contact {
+        Contact {
+            name: name.to_string(),
+            role: "Webmaster".to_string(),
+            email: None,
+            phone: None,
+        }
+    }
+
+    #[test]
+    fn test_trash_and_restore_in_place() {
+        let mut data = AppData::default();
+        let mut site = Site::sample("a", "Site A");
+        site.contacts = vec![contact("Marie"), contact("Paul")];
+        data.sites = vec![site, Site::sample("b", "Site B")];
+
+        let contact_id = trash_contact(&mut data, "a", 0).unwrap();
+        let site_id = trash_site(&mut data, "a").unwrap();
+        assert_eq!(data.sites.len(), 1);
+        assert_eq!(data.trash.len(), 2);
+        assert!(trash_contact(&mut data, "b", 0).is_err());
+
+        // Le contact attend que son site soit restauré
+        assert!(matches!(
+            restore(&mut data, &contact_id),
+            Err(AppError::InvalidInput(_))
+        ));
+        restore(&mut data, &site_id).unwrap();
+        restore(&mut data, &contact_id).unwrap();
+        assert_eq!(data.sites[0].id, "a");
+        let names: Vec<_> = data.sites[0].contacts.iter().map(|c| &c.name).collect();
+        assert_eq!(names, vec!["Marie", "Paul"]);
+        assert!(data.trash.is_empty());
+
+        // Un site recréé entre-temps sous le même identifiant n'est pas écrasé
+        let site_id = trash_site(&mut data, "b").unwrap();
+        data.sites.push(Site::sample("b", "Site B bis"));
+        assert!(restore(&mut data, &site_id).is_err());
+        purge(&mut data, &site_id).unwrap();
+        assert!(purge(&mut data, &site_id).is_err());
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let day_ms = DAY_SECS * 1000;
+        let mut data = AppData::default();
+        data.settings.trash_keep_days = 30;
+        data.sites = vec![Site::sample("a", "Site A"), Site::sample("b", "Site B")];
+        trash_site(&mut data, "a").unwrap();
+        trash_site(&mut data, "b").unwrap();
+        data.trash[0].deleted_at_ms = 0;
+        data.trash[1].deleted_at_ms = 10 * day_ms;
+
+        assert_eq!(expired(&data, 30 * day_ms).len(), 0);
+        assert_eq!(purge_expired(&mut data, 31 * day_ms), 1);
+        assert_eq!(data.trash.len(), 1);
+
+        // 0 : jamais purgés automatiquement
+        data.settings.trash_keep_days = 0;
+        assert_eq!(purge_expired(&mut data, 1000 * day_ms), 0);
+        assert_eq!(empty(&mut data), 1);
+    }
+}
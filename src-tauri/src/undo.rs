@@ -0,0 +1,261 @@
+// src-tauri/src/undo.rs
+// Historique d'annulation des modifications de la session
+// Chaque modification des données est mémorisée (données avant et après).
+// Annuler applique l'inverse de l'étape aux données actuelles par fusion à
+// trois voies : ce qui a été modifié depuis, ici ou ailleurs, est conservé.
+// L'historique ne vit qu'en mémoire et est effacé au verrouillage
+
+use crate::error::{AppError, AppResult};
+use crate::merge;
+use crate::storage::AppData;
+use serde::Serialize;
+use std::collections::VecDeque;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Nombre maximal d'étapes annulables ; au-delà, les plus anciennes sont oubliées
+pub const UNDO_LIMIT: usize = 50;
+
+/// Modification mémorisée
+#[derive(Clone, Debug)]
+pub struct UndoStep {
+    /// Identifiant de l'étape dans l'historique
+    id: u64,
+    /// Description affichée (ex: "Modification du site CFDT Ulogistique")
+    pub description: String,
+    before: AppData,
+    after: AppData,
+}
+
+impl UndoStep {
+    /// Annule l'étape dans `data`
+    ///
+    /// # Errors
+    /// `InvalidInput` si un champ touché par l'étape a été modifié depuis
+    pub fn revert(&self, data: &mut AppData) -> AppResult<()> {
+        self.apply(&self.after, &self.before, data)
+    }
+
+    /// Rétablit l'étape dans `data`
+    ///
+    /// # Errors
+    /// `InvalidInput` si un champ touché par l'étape a été modifié depuis
+    pub fn reapply(&self, data: &mut AppData) -> AppResult<()> {
+        self.apply(&self.before, &self.after, data)
+    }
+
+    /// Passe de `from` à `to` dans `data`, sans toucher au reste
+    fn apply(&self, from: &AppData, to: &AppData, data: &mut AppData) -> AppResult<()> {
+        let result = merge::three_way_merge(from, data, to)?;
+        if !result.conflicts.is_empty() {
+            let fields: Vec<String> = result
+                .conflicts
+                .iter()
+                .map(|c| match &c.site_id {
+                    Some(site_id) if c.field.is_empty() => site_id.clone(),
+                    Some(site_id) => format!("{}.{}", site_id, c.field),
+                    None => c.field.clone(),
+                })
+                .collect();
+            return Err(AppError::InvalidInput(format!(
+                "Étape « {} » en conflit avec des modifications ultérieures ({})",
+                self.description,
+                fields.join(", ")
+            )));
+        }
+        *data = result.merged;
+        Ok(())
+    }
+}
+
+impl Drop for UndoStep {
+    fn drop(&mut self) {
+        // La description peut contenir un nom de site
+        self.description.zeroize();
+    }
+}
+
+/// Descriptions des étapes, de la plus récente à la plus ancienne
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct UndoSummary {
+    pub undo: Vec<String>,
+    pub redo: Vec<String>,
+}
+
+/// Piles d'annulation et de rétablissement
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo: VecDeque<UndoStep>,
+    redo: Vec<UndoStep>,
+    next_id: u64,
+}
+
+impl UndoHistory {
+    /// Mémorise une modification ; les étapes annulées ne peuvent plus être
+    /// rétablies. Une modification sans effet n'est pas mémorisée.
+    pub fn record(&mut self, description: String, before: &AppData, after: &AppData) {
+        if same_data(before, after) {
+            return;
+        }
+        self.redo.clear();
+        self.next_id += 1;
+        self.undo.push_back(UndoStep {
+            id: self.next_id,
+            description,
+            before: before.clone(),
+            after: after.clone(),
+        });
+        while self.undo.len() > UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Prochaine étape à annuler
+    pub fn next_undo(&self) -> Option<&UndoStep> {
+        self.undo.back()
+    }
+
+    /// Prochaine étape à rétablir
+    pub fn next_redo(&self) -> Option<&UndoStep> {
+        self.redo.last()
+    }
+
+    /// Fait passer `step` dans la pile de rétablissement, une fois son
+    /// annulation enregistrée
+    ///
+    /// L'historique n'est pas verrouillé pendant l'écriture : si une autre
+    /// modification a été mémorisée entre-temps, l'étape est seulement
+    /// retirée (elle ne peut plus être rétablie) ; si l'historique a été
+    /// effacé ou l'étape déjà annulée, rien ne change.
+    pub fn undone(&mut self, step: &UndoStep) {
+        let Some(pos) = self.undo.iter().position(|s| s.id == step.id) else {
+            return;
+        };
+        let was_last = pos + 1 == self.undo.len();
+        if let Some(step) = self.undo.remove(pos) {
+            if was_last {
+                self.redo.push(step);
+            }
+        }
+    }
+
+    /// Fait repasser `step` dans la pile d'annulation, une fois son
+    /// rétablissement enregistré (s'il est toujours le prochain à rétablir)
+    pub fn redone(&mut self, step: &UndoStep) {
+        if self.redo.last().is_some_and(|s| s.id == step.id) {
+            if let Some(step) = self.redo.pop() {
+                self.undo.push_back(step);
+            }
+        }
+    }
+
+    /// Descriptions des étapes, pour le frontend
+    pub fn summary(&self) -> UndoSummary {
+        UndoSummary {
+            undo: self
+                .undo
+                .iter()
+                .rev()
+                .map(|step| step.description.clone())
+                .collect(),
+            redo: self
+                .redo
+                .iter()
+                .rev()
+                .map(|step| step.description.clone())
+                .collect(),
+        }
+    }
+
+    /// Oublie toutes les étapes (verrouillage)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+fn same_data(a: &AppData, b: &AppData) -> bool {
+    match (serde_json::to_vec(a), serde_json::to_vec(b)) {
+        (Ok(a), Ok(b)) => Zeroizing::new(a) == Zeroizing::new(b),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Site;
+
+    #[test]
+    fn test_undo_redo_keeps_later_changes() {
+        let mut data = AppData::default();
+        data.sites = vec![Site::sample("a", "Site A")];
+        let mut history = UndoHistory::default();
+
+        let before = data.clone();
+        data.sites[0].notes = "Note erronée".to_string();
+        history.record("Modification du site Site A".to_string(), &before, &data);
+        let before = data.clone();
+        history.record("Sans effet".to_string(), &before, &data);
+
+        // Modification faite ensuite sur un autre site : conservée
+        data.sites.push(Site::sample("b", "Site B"));
+
+        let step = history.next_undo().unwrap().clone();
+        step.revert(&mut data).unwrap();
+        history.undone(&step);
+        assert_eq!(data.sites[0].notes, "");
+        assert_eq!(data.sites.len(), 2);
+        assert_eq!(
+            history.summary(),
+            UndoSummary {
+                undo: vec![],
+                redo: vec!["Modification du site Site A".to_string()],
+            }
+        );
+
+        let step = history.next_redo().unwrap().clone();
+        step.reapply(&mut data).unwrap();
+        history.redone(&step);
+        // Déjà rétablie : sans effet
+        history.redone(&step);
+        assert_eq!(data.sites[0].notes, "Note erronée");
+
+        // Champ modifié depuis l'étape : l'annulation est refusée
+        data.sites[0].notes = "Autre note".to_string();
+        assert!(matches!(
+            history.next_undo().unwrap().revert(&mut data),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!(data.sites[0].notes, "Autre note");
+
+        // Étape annulée pendant qu'une autre modification était mémorisée :
+        // elle est retirée mais ne peut plus être rétablie
+        let step = history.next_undo().unwrap().clone();
+        data.sites[0].notes = String::new();
+        let before = data.clone();
+        data.sites[0].notes = "Modification concurrente".to_string();
+        history.record("Concurrente".to_string(), &before, &data);
+        history.undone(&step);
+        assert_eq!(
+            history.summary(),
+            UndoSummary {
+                undo: vec!["Concurrente".to_string()],
+                redo: vec![],
+            }
+        );
+
+        // Nouvelle étape : plus rien à rétablir ; historique borné
+        for i in 0..UNDO_LIMIT + 5 {
+            let before = data.clone();
+            data.sites[0].notes = i.to_string();
+            history.record(format!("Étape {}", i), &before, &data);
+        }
+        let summary = history.summary();
+        assert!(summary.redo.is_empty());
+        assert_eq!(summary.undo.len(), UNDO_LIMIT);
+        assert_eq!(summary.undo[0], format!("Étape {}", UNDO_LIMIT + 4));
+
+        history.clear();
+        assert!(history.next_undo().is_none());
+    }
+}
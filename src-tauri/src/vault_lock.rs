@@ -0,0 +1,329 @@
+// src-tauri/src/vault_lock.rs
+// Verrou d'écriture du coffre
+// Un fichier sites.lock indique quelle instance (machine, PID) a ouvert le
+// coffre en écriture, pour que deux PC partageant une clé USB ou un lecteur
+// réseau n'écrasent pas mutuellement leurs sauvegardes
+
+use crate::atomic;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nom du fichier verrou, à côté de sites.encrypted
+pub const LOCK_FILE_NAME: &str = "sites.lock";
+
+/// Intervalle de rafraîchissement du verrou par l'instance qui le détient
+pub const LOCK_REFRESH_SECS: u64 = 60;
+
+/// Un verrou non rafraîchi depuis ce délai est considéré comme abandonné
+pub const LOCK_STALE_SECS: u64 = 5 * 60;
+
+/// Détenteur affiché quand le fichier verrou ne peut pas être lu
+const UNKNOWN_HOLDER: &str = "autre instance";
+
+/// Instance détenant le verrou
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LockOwner {
+    /// Nom de la machine
+    pub hostname: String,
+
+    /// Processus de l'application sur cette machine
+    pub pid: u32,
+
+    /// Prise du verrou (timestamp Unix)
+    pub acquired_at: u64,
+
+    /// Dernier rafraîchissement (timestamp Unix)
+    pub refreshed_at: u64,
+}
+
+impl LockOwner {
+    /// Instance courante
+    fn current() -> Self {
+        let now = unix_now();
+        Self {
+            hostname: hostname(),
+            pid: process::id(),
+            acquired_at: now,
+            refreshed_at: now,
+        }
+    }
+
+    /// Le verrou a-t-il été posé par ce processus ?
+    fn is_current_process(&self) -> bool {
+        self.pid == process::id() && self.hostname == hostname()
+    }
+
+    /// Le verrou est-il abandonné ?
+    ///
+    /// Un verrou qui n'est plus rafraîchi (plantage, PC éteint) est périmé.
+    /// Sur la même machine, on vérifie aussi que le processus existe encore.
+    pub fn is_stale(&self) -> bool {
+        let now = unix_now();
+        if self.refreshed_at.saturating_add(LOCK_STALE_SECS) < now {
+            return true;
+        }
+        self.hostname == hostname() && !process_alive(self.pid)
+    }
+
+    /// Description pour l'utilisateur (ex: "PC-ACCUEIL (PID 4242)")
+    pub fn describe(&self) -> String {
+        format!("{} (PID {})", self.hostname, self.pid)
+    }
+}
+
+/// Verrou d'écriture détenu par cette instance, libéré à la destruction
+pub struct VaultLock {
+    path: PathBuf,
+    owner: LockOwner,
+}
+
+impl VaultLock {
+    /// Prend le verrou du coffre de `data_dir`
+    ///
+    /// Un verrou périmé ou laissé par ce même processus est remplacé. Un
+    /// fichier vide ou illisible peut être en cours d'écriture par une autre
+    /// instance : il n'est remplacé que s'il n'a pas été modifié depuis
+    /// `LOCK_STALE_SECS`.
+    ///
+    /// # Errors
+    /// `VaultInUse` si une autre instance active détient le verrou
+    pub fn acquire(data_dir: &Path) -> AppResult<Self> {
+        let path = data_dir.join(LOCK_FILE_NAME);
+        let owner = LockOwner::current();
+
+        // Deux essais : le second après suppression d'un verrou abandonné
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(serde_json::to_string_pretty(&owner)?.as_bytes())?;
+                    file.sync_all()?;
+                    return Ok(Self { path, owner });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    match Self::holder(data_dir) {
+                        Some(holder) => {
+                            if !holder.is_stale() && !holder.is_current_process() {
+                                return Err(AppError::VaultInUse(holder.describe()));
+                            }
+                        }
+                        None => {
+                            if !modified_long_ago(&path) {
+                                return Err(AppError::VaultInUse(UNKNOWN_HOLDER.to_string()));
+                            }
+                        }
+                    }
+                    match fs::remove_file(&path) {
+                        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                        _ => {}
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Une autre instance a repris le verrou entre-temps
+        let holder = Self::holder(data_dir).map(|h| h.describe());
+        Err(AppError::VaultInUse(
+            holder.unwrap_or_else(|| UNKNOWN_HOLDER.to_string()),
+        ))
+    }
+
+    /// Instance qui détient le verrou de `data_dir`, si le fichier est lisible
+    pub fn holder(data_dir: &Path) -> Option<LockOwner> {
+        read_owner(&data_dir.join(LOCK_FILE_NAME))
+    }
+
+    /// Rafraîchit le verrou si `LOCK_REFRESH_SECS` sont écoulés
+    ///
+    /// # Returns
+    /// `false` si le verrou a été repris par une autre instance (il était
+    /// considéré comme abandonné) : cette instance ne doit plus écrire
+    pub fn keep_alive(&mut self) -> AppResult<bool> {
+        if read_owner(&self.path).as_ref() != Some(&self.owner) {
+            return Ok(false);
+        }
+
+        let now = unix_now();
+        if now >= self.owner.refreshed_at.saturating_add(LOCK_REFRESH_SECS) {
+            let mut owner = self.owner.clone();
+            owner.refreshed_at = now;
+            atomic::write_atomic(&self.path, serde_json::to_string_pretty(&owner)?.as_bytes())?;
+            self.owner = owner;
+        }
+        Ok(true)
+    }
+}
+
+impl Drop for VaultLock {
+    fn drop(&mut self) {
+        // Ne supprimer que notre propre verrou
+        if read_owner(&self.path).as_ref() == Some(&self.owner) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Lit le fichier verrou (`None` s'il est absent ou illisible)
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Le fichier n'a-t-il pas été modifié depuis `LOCK_STALE_SECS` ?
+///
+/// Une date illisible ou dans le futur (horloges décalées) compte comme
+/// récente ; un fichier disparu entre-temps, comme ancien.
+fn modified_long_ago(path: &Path) -> bool {
+    match fs::metadata(path).and_then(|m| m.modified()) {
+        Ok(modified) => modified
+            .elapsed()
+            .is_ok_and(|age| age.as_secs() > LOCK_STALE_SECS),
+        Err(e) => e.kind() == io::ErrorKind::NotFound,
+    }
+}
+
+/// Secondes écoulées depuis l'epoch Unix
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Nom de la machine
+#[cfg(unix)]
+pub(crate) fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    // SAFETY: le tampon est valide sur toute sa longueur
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return "inconnu".to_string();
+    }
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "inconnu".to_string())
+}
+
+/// Le processus `pid` existe-t-il sur cette machine ?
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: le signal 0 ne fait que tester l'existence du processus
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // Processus existant mais appartenant à un autre utilisateur
+    io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED};
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    // SAFETY: le handle est fermé aussitôt après ouverture
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle == 0 {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        CloseHandle(handle);
+        true
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::time::Duration;
+
+    fn write_owner(dir: &Path, owner: &LockOwner) {
+        fs::write(
+            dir.join(LOCK_FILE_NAME),
+            serde_json::to_string(owner).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_vault_lock_single_writer() {
+        let temp_dir = env::temp_dir().join("cockpit_vault_lock_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // Prise et libération du verrou
+        let lock = VaultLock::acquire(&temp_dir).unwrap();
+        let holder = VaultLock::holder(&temp_dir).unwrap();
+        assert_eq!(holder.pid, process::id());
+        assert_eq!(holder.hostname, hostname());
+        drop(lock);
+        assert!(VaultLock::holder(&temp_dir).is_none());
+
+        // Verrou actif d'une autre machine : refusé
+        let now = unix_now();
+        let other = LockOwner {
+            hostname: "autre-pc".to_string(),
+            pid: 1234,
+            acquired_at: now,
+            refreshed_at: now,
+        };
+        write_owner(&temp_dir, &other);
+        assert!(matches!(
+            VaultLock::acquire(&temp_dir),
+            Err(AppError::VaultInUse(holder)) if holder.contains("autre-pc")
+        ));
+
+        // Fichier vide (en cours d'écriture) : refusé tant qu'il est récent
+        let path = temp_dir.join(LOCK_FILE_NAME);
+        fs::write(&path, b"").unwrap();
+        assert!(matches!(
+            VaultLock::acquire(&temp_dir),
+            Err(AppError::VaultInUse(_))
+        ));
+        let old = SystemTime::now() - Duration::from_secs(LOCK_STALE_SECS + 60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        drop(VaultLock::acquire(&temp_dir).unwrap());
+
+        // Verrou non rafraîchi depuis trop longtemps : repris
+        let stale = LockOwner {
+            refreshed_at: now - LOCK_STALE_SECS - 1,
+            ..other.clone()
+        };
+        assert!(stale.is_stale());
+        write_owner(&temp_dir, &stale);
+        let mut lock = VaultLock::acquire(&temp_dir).unwrap();
+        assert!(lock.keep_alive().unwrap());
+
+        // Verrou repris par une autre instance : on ne doit plus écrire,
+        // et on ne supprime pas son verrou en partant
+        write_owner(&temp_dir, &other);
+        assert!(!lock.keep_alive().unwrap());
+        drop(lock);
+        assert_eq!(VaultLock::holder(&temp_dir), Some(other));
+
+        // Nettoyage
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,16 +1,31 @@
 import { useState, useEffect, useCallback } from 'react';
-import { AppData, AppStatus } from './types';
-import { initializeStorage, isLocked, saveData, lock } from './utils/tauri';
+import { AppData, AppStatus, FieldConflict } from './types';
+import {
+  initializeStorage,
+  isLocked,
+  isReadOnly,
+  isAppError,
+  saveData,
+  lock,
+  errorMessage,
+  onAutoLock,
+  onReadOnly,
+} from './utils/tauri';
 import { UnlockScreen } from './pages/UnlockScreen';
 import { MainLayout } from './pages/MainLayout';
+import { MergeConflictsModal } from './components/MergeConflictsModal';
 import './App.css';
 
 function App() {
   const [status, setStatus] = useState<AppStatus>('initializing');
   const [appData, setAppData] = useState<AppData | null>(null);
   const [dataFileExists, setDataFileExists] = useState(false);
-  const [password, setPassword] = useState<string>(''); // Garder le mot de passe pour sauvegarder
   const [initError, setInitError] = useState<string>(''); // Erreur d'initialisation
+  // Conflits avec des modifications faites sur un autre poste
+  const [conflicts, setConflicts] = useState<FieldConflict[]>([]);
+  // Coffre ouvert en lecture seule, ou verrou repris par une autre instance
+  const [readOnly, setReadOnly] = useState(false);
+  const [saveError, setSaveError] = useState<string>('');
 
   useEffect(() => {
     async function init() {
@@ -28,10 +43,10 @@ function App() {
         // Vérifier si verrouillé
         const locked = await isLocked();
         setStatus(locked ? 'locked' : 'unlocked');
+        if (!locked) setReadOnly(await isReadOnly());
       } catch (error) {
         console.error('Erreur initialisation:', error);
-        const errorMessage = error instanceof Error ? error.message : String(error);
-        setInitError(errorMessage);
+        setInitError(errorMessage(error));
         setStatus('locked');
       }
     }
@@ -39,10 +54,37 @@ function App() {
     init();
   }, []);
 
-  const handleUnlock = (data: AppData, pwd: string) => {
+  // Verrouillage automatique décidé par le backend (inactivité)
+  useEffect(() => {
+    if (import.meta.env.DEV) return;
+
+    const unlisten = onAutoLock(() => {
+      setAppData(null);
+      setReadOnly(false);
+      setStatus('locked');
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  // Verrou d'écriture repris par une autre instance : plus aucune sauvegarde
+  useEffect(() => {
+    const unlisten = onReadOnly(() => setReadOnly(true));
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  const handleUnlock = async (data: AppData) => {
     setAppData(data);
-    setPassword(pwd); // Stocker le mot de passe pour les sauvegardes
+    setSaveError('');
     setStatus('unlocked');
+    try {
+      setReadOnly(await isReadOnly());
+    } catch (error) {
+      console.error('Erreur lecture seule:', error);
+    }
   };
 
   const handleLock = async () => {
@@ -52,25 +94,43 @@ function App() {
       console.error('Erreur verrouillage:', error);
     }
     setAppData(null);
-    setPassword(''); // Effacer le mot de passe de la mémoire
+    setReadOnly(false);
     setStatus('locked');
   };
 
+  // Écriture refusée : l'afficher, et basculer en lecture seule si c'est la cause
+  const handleSaveError = useCallback((error: unknown) => {
+    if (isAppError(error) && error.code === 'READ_ONLY') setReadOnly(true);
+    setSaveError(`Modification non enregistrée : ${errorMessage(error)}`);
+  }, []);
+
   // Sauvegarder les données quand elles changent
   const handleDataChange = useCallback(async (newData: AppData) => {
+    // En lecture seule, la modification serait perdue à la fermeture
+    if (readOnly) {
+      setSaveError('Coffre en lecture seule : modification non enregistrée');
+      return;
+    }
     setAppData(newData);
 
-    // Sauvegarder dans le fichier chiffré
-    if (password) {
-      try {
-        await saveData(password, newData);
-        console.log('Données sauvegardées');
-      } catch (error) {
-        console.error('Erreur sauvegarde:', error);
-        // TODO: Afficher une notification d'erreur à l'utilisateur
+    // Sauvegarder dans le fichier chiffré (la clé de session reste côté backend)
+    try {
+      const outcome = await saveData(newData);
+      if (outcome.status === 'merged') {
+        // Modifications d'un autre poste intégrées
+        setAppData(outcome.data);
+      } else if (outcome.status === 'conflicts') {
+        setAppData(outcome.data);
+        setConflicts(outcome.conflicts);
+        return;
       }
+      setSaveError('');
+      console.log('Données sauvegardées');
+    } catch (error) {
+      console.error('Erreur sauvegarde:', error);
+      handleSaveError(error);
     }
-  }, [password]);
+  }, [readOnly, handleSaveError]);
 
   if (status === 'initializing') {
     return (
@@ -92,12 +152,42 @@ function App() {
   }
 
   return (
-    <MainLayout
-      appData={appData!}
-      onDataChange={handleDataChange}
-      onLock={handleLock}
-      onPasswordChanged={setPassword}
-    />
+    <>
+      <MainLayout
+        appData={appData!}
+        onDataChange={handleDataChange}
+        onDataSaved={setAppData}
+        onSaveError={handleSaveError}
+        onLock={handleLock}
+      />
+      {(readOnly || saveError) && (
+        <div className={`app-banner ${readOnly ? 'app-banner-read-only' : 'app-banner-error'}`} role="alert">
+          {readOnly && (
+            <span>
+              Coffre en lecture seule : une autre instance détient le verrou d'écriture, vos
+              modifications ne seront pas enregistrées.
+            </span>
+          )}
+          {saveError && <span>{saveError}</span>}
+          {saveError && (
+            <button type="button" className="app-banner-close" onClick={() => setSaveError('')}>
+              ✕
+            </button>
+          )}
+        </div>
+      )}
+      {conflicts.length > 0 && (
+        <MergeConflictsModal
+          data={appData!}
+          conflicts={conflicts}
+          onResolve={(resolved) => {
+            setConflicts([]);
+            handleDataChange(resolved);
+          }}
+          onClose={() => setConflicts([])}
+        />
+      )}
+    </>
   );
 }
 
@@ -2,10 +2,11 @@ import { useState } from 'react';
 import { Site } from '../types';
 import { Button } from './Button';
 import { Input } from './Input';
+import { errorMessage, fieldErrors } from '../utils/tauri';
 import './AddSiteModal.css';
 
 interface AddSiteModalProps {
-  onAdd: (site: Site) => void;
+  onAdd: (site: Site) => Promise<void>; // rejetée si le backend refuse le site
   onClose: () => void;
 }
 
@@ -13,19 +14,23 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
   const [name, setName] = useState('');
   const [frontendUrl, setFrontendUrl] = useState('https://');
   const [backendUrl, setBackendUrl] = useState('');
+  const [joomlaAdmin, setJoomlaAdmin] = useState('');
+  // Erreurs par champ (mêmes noms que la validation du backend)
+  const [errors, setErrors] = useState<Record<string, string>>({});
   const [error, setError] = useState('');
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
+    setErrors({});
     setError('');
 
     if (!name.trim()) {
-      setError('Le nom du site est requis');
+      setErrors({ name: 'Le nom du site est requis' });
       return;
     }
 
     if (!frontendUrl.trim() || frontendUrl === 'https://') {
-      setError("L'URL du site est requise");
+      setErrors({ 'urls.frontend': "L'URL du site est requise" });
       return;
     }
 
@@ -46,7 +51,7 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
       },
       dashlane_refs: {
         backend_protection: null,
-        joomla_admin: '',
+        joomla_admin: joomlaAdmin.trim(),
         mysql_su: '',
         mysql_std: null,
         editors: [],
@@ -73,7 +78,15 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
       last_update: new Date().toISOString(),
     };
 
-    onAdd(newSite);
+    try {
+      await onAdd(newSite);
+    } catch (err) {
+      const fields = fieldErrors(err);
+      setErrors(fields);
+      // Champ sans saisie dans ce formulaire (identifiant…) ou autre erreur
+      const shown = ['name', 'urls.frontend', 'urls.backend', 'dashlane_refs.joomla_admin'];
+      if (!shown.some((field) => field in fields)) setError(errorMessage(err));
+    }
   };
 
   return (
@@ -92,7 +105,7 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
             placeholder="Ex: CFDT Transport"
             value={name}
             onChange={(e) => setName(e.target.value)}
-            error={error && !name ? error : ''}
+            error={errors['name']}
             autoFocus
           />
 
@@ -101,7 +114,7 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
             placeholder="https://mon-site.fr"
             value={frontendUrl}
             onChange={(e) => setFrontendUrl(e.target.value)}
-            error={error && frontendUrl === 'https://' ? error : ''}
+            error={errors['urls.frontend']}
           />
 
           <Input
@@ -109,11 +122,18 @@ export const AddSiteModal: React.FC<AddSiteModalProps> = ({ onAdd, onClose }) =>
             placeholder="https://mon-site.fr/administrator"
             value={backendUrl}
             onChange={(e) => setBackendUrl(e.target.value)}
+            error={errors['urls.backend']}
+          />
+
+          <Input
+            label="Référence Dashlane (admin Joomla)"
+            placeholder="Nom de l'entrée Dashlane"
+            value={joomlaAdmin}
+            onChange={(e) => setJoomlaAdmin(e.target.value)}
+            error={errors['dashlane_refs.joomla_admin']}
           />
 
-          {error && name && frontendUrl !== 'https://' && (
-            <p className="form-error">{error}</p>
-          )}
+          {error && <p className="form-error">{error}</p>}
 
           <div className="modal-actions">
             <Button type="button" variant="secondary" onClick={onClose}>
@@ -1,11 +1,12 @@
 import { useState, useEffect } from 'react';
 import { ChecklistItem } from '../types';
 import { Button } from './Button';
+import { errorMessage } from '../utils/tauri';
 import './ChecklistModal.css';
 
 interface ChecklistModalProps {
   item: ChecklistItem | null; // null = ajout, sinon édition
-  onSave: (item: ChecklistItem) => void;
+  onSave: (item: ChecklistItem) => Promise<void>; // rejetée si l'enregistrement échoue
   onDelete?: () => void;
   onClose: () => void;
 }
@@ -18,6 +19,7 @@ export const ChecklistModal: React.FC<ChecklistModalProps> = ({
 }) => {
   const [task, setTask] = useState('');
   const [done, setDone] = useState(false);
+  const [error, setError] = useState('');
 
   useEffect(() => {
     if (item) {
@@ -26,15 +28,20 @@ export const ChecklistModal: React.FC<ChecklistModalProps> = ({
     }
   }, [item]);
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!task.trim()) return;
+    setError('');
 
-    onSave({
-      task: task.trim(),
-      done,
-      date: done ? (item?.date || new Date().toISOString().split('T')[0]) : null,
-    });
+    try {
+      await onSave({
+        task: task.trim(),
+        done,
+        date: done ? (item?.date || new Date().toISOString().split('T')[0]) : null,
+      });
+    } catch (err) {
+      setError(errorMessage(err));
+    }
   };
 
   const isEditing = item !== null;
@@ -72,6 +79,8 @@ export const ChecklistModal: React.FC<ChecklistModalProps> = ({
             </label>
           </div>
 
+          {error && <p className="form-error">{error}</p>}
+
           <div className="modal-actions">
             {isEditing && onDelete && (
               <Button
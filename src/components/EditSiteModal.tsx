@@ -2,11 +2,12 @@ import { useState } from 'react';
 import { Site } from '../types';
 import { Button } from './Button';
 import { Input } from './Input';
+import { errorMessage, fieldErrors } from '../utils/tauri';
 import './EditSiteModal.css';
 
 interface EditSiteModalProps {
   site: Site;
-  onSave: (site: Site) => void;
+  onSave: (site: Site) => Promise<void>; // rejetée si le backend refuse le site
   onClose: () => void;
   onDelete: () => void;
 }
@@ -38,13 +39,18 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
   });
 
   const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
+  // Erreurs par champ renvoyées par la validation du backend
+  const [errors, setErrors] = useState<Record<string, string>>({});
+  const [error, setError] = useState('');
 
   const handleChange = (field: string, value: string | boolean) => {
     setFormData((prev) => ({ ...prev, [field]: value }));
   };
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
+    setErrors({});
+    setError('');
 
     const updatedSite: Site = {
       ...site,
@@ -77,7 +83,21 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
       last_update: new Date().toISOString(),
     };
 
-    onSave(updatedSite);
+    try {
+      await onSave(updatedSite);
+    } catch (err) {
+      const fields = fieldErrors(err);
+      setErrors(fields);
+      // Champ absent de ce formulaire (ex: analytics) ou autre erreur
+      const shown = [
+        'name',
+        'urls.frontend',
+        'urls.backend',
+        'urls.phpmyadmin',
+        'dashlane_refs.joomla_admin',
+      ];
+      if (!shown.some((field) => field in fields)) setError(errorMessage(err));
+    }
   };
 
   return (
@@ -98,6 +118,7 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
               label="Nom du site"
               value={formData.name}
               onChange={(e) => handleChange('name', e.target.value)}
+              error={errors['name']}
             />
 
             <label className="checkbox-label">
@@ -117,18 +138,21 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
               label="URL Frontend"
               value={formData.frontendUrl}
               onChange={(e) => handleChange('frontendUrl', e.target.value)}
+              error={errors['urls.frontend']}
             />
 
             <Input
               label="URL Backend (Admin)"
               value={formData.backendUrl}
               onChange={(e) => handleChange('backendUrl', e.target.value)}
+              error={errors['urls.backend']}
             />
 
             <Input
               label="URL phpMyAdmin"
               value={formData.phpmyadminUrl}
               onChange={(e) => handleChange('phpmyadminUrl', e.target.value)}
+              error={errors['urls.phpmyadmin']}
             />
           </div>
 
@@ -216,6 +240,7 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
               value={formData.joomlaAdmin}
               onChange={(e) => handleChange('joomlaAdmin', e.target.value)}
               placeholder="Nom de l'entrée Dashlane"
+              error={errors['dashlane_refs.joomla_admin']}
             />
 
             <Input
@@ -237,6 +262,8 @@ export const EditSiteModal: React.FC<EditSiteModalProps> = ({
             />
           </div>
 
+          {error && <p className="form-error">{error}</p>}
+
           <div className="modal-actions">
             <div className="actions-left">
               {!showDeleteConfirm ? (
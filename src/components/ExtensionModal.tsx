@@ -1,11 +1,12 @@
 import { useState, useEffect } from 'react';
 import { Extension } from '../types';
 import { Button } from './Button';
+import { errorMessage } from '../utils/tauri';
 import './ExtensionModal.css';
 
 interface ExtensionModalProps {
   extension: Extension | null; // null = ajout, sinon édition
-  onSave: (extension: Extension) => void;
+  onSave: (extension: Extension) => Promise<void>; // rejetée si l'enregistrement échoue
   onDelete?: () => void;
   onClose: () => void;
 }
@@ -19,6 +20,7 @@ export const ExtensionModal: React.FC<ExtensionModalProps> = ({
   const [name, setName] = useState('');
   const [version, setVersion] = useState('');
   const [critical, setCritical] = useState(false);
+  const [error, setError] = useState('');
 
   useEffect(() => {
     if (extension) {
@@ -28,15 +30,20 @@ export const ExtensionModal: React.FC<ExtensionModalProps> = ({
     }
   }, [extension]);
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!name.trim()) return;
+    setError('');
 
-    onSave({
-      name: name.trim(),
-      version: version.trim() || null,
-      critical,
-    });
+    try {
+      await onSave({
+        name: name.trim(),
+        version: version.trim() || null,
+        critical,
+      });
+    } catch (err) {
+      setError(errorMessage(err));
+    }
   };
 
   const isEditing = extension !== null;
@@ -85,6 +92,8 @@ export const ExtensionModal: React.FC<ExtensionModalProps> = ({
             </label>
           </div>
 
+          {error && <p className="form-error">{error}</p>}
+
           <div className="modal-actions">
             {isEditing && onDelete && (
               <Button
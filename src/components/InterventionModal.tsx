@@ -1,11 +1,12 @@
 import { useState, useEffect } from 'react';
 import { Intervention } from '../types';
 import { Button } from './Button';
+import { errorMessage, fieldErrors } from '../utils/tauri';
 import './InterventionModal.css';
 
 interface InterventionModalProps {
   intervention: Intervention | null; // null = ajout, sinon édition
-  onSave: (intervention: Intervention) => void;
+  onSave: (intervention: Intervention) => Promise<void>; // rejetée si le backend refuse
   onDelete?: () => void;
   onClose: () => void;
 }
@@ -46,6 +47,9 @@ export const InterventionModal: React.FC<InterventionModalProps> = ({
   const [description, setDescription] = useState('');
   const [duration, setDuration] = useState('');
   const [result, setResult] = useState('Succès');
+  // Erreurs par champ renvoyées par la validation du backend
+  const [errors, setErrors] = useState<Record<string, string>>({});
+  const [error, setError] = useState('');
 
   useEffect(() => {
     if (intervention) {
@@ -57,17 +61,26 @@ export const InterventionModal: React.FC<InterventionModalProps> = ({
     }
   }, [intervention]);
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!typeIntervention.trim() || !description.trim()) return;
+    setErrors({});
+    setError('');
 
-    onSave({
-      date,
-      type_intervention: typeIntervention,
-      description: description.trim(),
-      duration: duration.trim() || 'Non spécifié',
-      result,
-    });
+    try {
+      await onSave({
+        date,
+        type_intervention: typeIntervention,
+        description: description.trim(),
+        duration: duration.trim() || 'Non spécifié',
+        result,
+      });
+    } catch (err) {
+      const fields = fieldErrors(err);
+      setErrors(fields);
+      // Refus portant sur le site lui-même (édition) ou autre erreur
+      if (!('date' in fields) && !('type_intervention' in fields)) setError(errorMessage(err));
+    }
   };
 
   const isEditing = intervention !== null;
@@ -91,6 +104,7 @@ export const InterventionModal: React.FC<InterventionModalProps> = ({
                 onChange={(e) => setDate(e.target.value)}
                 required
               />
+              {errors['date'] && <span className="input-error-message">{errors['date']}</span>}
             </div>
 
             <div className="form-group">
@@ -118,6 +132,9 @@ export const InterventionModal: React.FC<InterventionModalProps> = ({
                 <option key={type} value={type}>{type}</option>
               ))}
             </select>
+            {errors['type_intervention'] && (
+              <span className="input-error-message">{errors['type_intervention']}</span>
+            )}
           </div>
 
           <div className="form-group">
@@ -146,6 +163,8 @@ export const InterventionModal: React.FC<InterventionModalProps> = ({
             </select>
           </div>
 
+          {error && <p className="form-error">{error}</p>}
+
           <div className="modal-actions">
             {isEditing && onDelete && (
               <Button
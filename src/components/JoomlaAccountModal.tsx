@@ -1,11 +1,12 @@
 import { useState, useEffect } from 'react';
 import { JoomlaAccount } from '../types';
 import { Button } from './Button';
+import { errorMessage } from '../utils/tauri';
 import './JoomlaAccountModal.css';
 
 interface JoomlaAccountModalProps {
   account: JoomlaAccount | null;
-  onSave: (account: JoomlaAccount) => void;
+  onSave: (account: JoomlaAccount) => Promise<void>; // rejetée si l'enregistrement échoue
   onDelete?: () => void;
   onClose: () => void;
 }
@@ -31,6 +32,7 @@ export const JoomlaAccountModal: React.FC<JoomlaAccountModalProps> = ({
   const [username, setUsername] = useState('');
   const [role, setRole] = useState('Éditeur');
   const [dashlaneRef, setDashlaneRef] = useState('');
+  const [error, setError] = useState('');
 
   useEffect(() => {
     if (account) {
@@ -40,15 +42,20 @@ export const JoomlaAccountModal: React.FC<JoomlaAccountModalProps> = ({
     }
   }, [account]);
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!username.trim()) return;
+    setError('');
 
-    onSave({
-      username: username.trim(),
-      role,
-      dashlane_ref: dashlaneRef.trim() || null,
-    });
+    try {
+      await onSave({
+        username: username.trim(),
+        role,
+        dashlane_ref: dashlaneRef.trim() || null,
+      });
+    } catch (err) {
+      setError(errorMessage(err));
+    }
   };
 
   const isEditing = account !== null;
@@ -100,6 +107,8 @@ export const JoomlaAccountModal: React.FC<JoomlaAccountModalProps> = ({
             />
           </div>
 
+          {error && <p className="form-error">{error}</p>}
+
           <div className="modal-actions">
             {isEditing && onDelete && (
               <Button
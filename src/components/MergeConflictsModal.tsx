@@ -0,0 +1,133 @@
+import { useState } from 'react';
+import { AppData, FieldConflict } from '../types';
+import { Button } from './Button';
+import './MergeConflictsModal.css';
+
+interface MergeConflictsModalProps {
+  data: AppData; // fusion proposée (ma version pour les champs en conflit)
+  conflicts: FieldConflict[];
+  onResolve: (data: AppData) => void;
+  onClose: () => void;
+}
+
+type Choice = 'local' | 'disk';
+
+// Remplace un champ par la version du disque (null = site supprimé sur le disque)
+function applyDiskValue(data: AppData, conflict: FieldConflict): AppData {
+  const result = structuredClone(data) as AppData;
+
+  if (conflict.site_id === null) {
+    // Paramètres : chemin depuis la racine ("settings.auto_lock_minutes")
+    setPath(result as unknown as Record<string, unknown>, conflict.field, conflict.disk);
+    return result;
+  }
+
+  const index = result.sites.findIndex((site) => site.id === conflict.site_id);
+  if (conflict.field === '') {
+    if (conflict.disk === null) {
+      if (index >= 0) result.sites.splice(index, 1);
+    } else if (index >= 0) {
+      result.sites[index] = conflict.disk as AppData['sites'][number];
+    } else {
+      result.sites.push(conflict.disk as AppData['sites'][number]);
+    }
+  } else if (index >= 0) {
+    setPath(result.sites[index] as unknown as Record<string, unknown>, conflict.field, conflict.disk);
+  }
+  return result;
+}
+
+function setPath(target: Record<string, unknown>, path: string, value: unknown) {
+  const keys = path.split('.');
+  let current = target;
+  for (const key of keys.slice(0, -1)) {
+    current = current[key] as Record<string, unknown>;
+  }
+  current[keys[keys.length - 1]] = value;
+}
+
+function display(value: unknown): string {
+  if (value === null || value === undefined) return '(supprimé)';
+  if (typeof value === 'string') return value || '(vide)';
+  return JSON.stringify(value, null, 2);
+}
+
+export const MergeConflictsModal: React.FC<MergeConflictsModalProps> = ({
+  data,
+  conflicts,
+  onResolve,
+  onClose,
+}) => {
+  const [choices, setChoices] = useState<Choice[]>(conflicts.map(() => 'local'));
+
+  const siteName = (siteId: string | null) => {
+    if (siteId === null) return 'Paramètres';
+    return data.sites.find((site) => site.id === siteId)?.name ?? siteId;
+  };
+
+  const handleResolve = () => {
+    const resolved = conflicts.reduce(
+      (current, conflict, i) => (choices[i] === 'disk' ? applyDiskValue(current, conflict) : current),
+      data
+    );
+    onResolve(resolved);
+  };
+
+  return (
+    <div className="modal-overlay">
+      <div className="modal-content merge-modal">
+        <div className="modal-header">
+          <h2>Modifications concurrentes</h2>
+          <button className="modal-close" onClick={onClose}>×</button>
+        </div>
+
+        <p className="merge-intro">
+          Le coffre a été modifié sur un autre poste. Les changements compatibles ont été
+          fusionnés ; choisissez la version à garder pour les champs suivants.
+        </p>
+
+        <ul className="merge-conflicts">
+          {conflicts.map((conflict, i) => (
+            <li key={`${conflict.site_id}-${conflict.field}`} className="merge-conflict">
+              <div className="merge-field">
+                <strong>{siteName(conflict.site_id)}</strong>
+                {' — '}
+                {conflict.field === '' ? 'site entier' : conflict.field}
+              </div>
+              <div className="merge-choices">
+                {(['local', 'disk'] as Choice[]).map((choice) => (
+                  <label
+                    key={choice}
+                    className={`merge-choice ${choices[i] === choice ? 'selected' : ''}`}
+                  >
+                    <input
+                      type="radio"
+                      name={`conflict-${i}`}
+                      checked={choices[i] === choice}
+                      onChange={() =>
+                        setChoices(choices.map((c, j) => (j === i ? choice : c)))
+                      }
+                    />
+                    <span className="merge-choice-label">
+                      {choice === 'local' ? 'Ma version' : 'Version du disque'}
+                    </span>
+                    <pre>{display(choice === 'local' ? conflict.local : conflict.disk)}</pre>
+                  </label>
+                ))}
+              </div>
+            </li>
+          ))}
+        </ul>
+
+        <div className="modal-actions">
+          <Button type="button" variant="secondary" onClick={onClose}>
+            Plus tard
+          </Button>
+          <Button type="button" variant="primary" onClick={handleResolve}>
+            Enregistrer
+          </Button>
+        </div>
+      </div>
+    </div>
+  );
+};
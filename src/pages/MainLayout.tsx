@@ -1,17 +1,28 @@
 import { useState } from 'react';
-import { AppData, Site } from '../types';
+import { AppData, Intervention, Site } from '../types';
 import { Sidebar } from '../components/Sidebar';
 import { SitesList } from './SitesList';
 import { SiteDetail } from './SiteDetail';
 import { Settings } from './Settings';
 import { AddSiteModal } from '../components/AddSiteModal';
+import {
+  addSite,
+  updateSite,
+  addIntervention,
+  toggleChecklistItem,
+  deleteSite,
+  deleteIntervention,
+} from '../utils/tauri';
 import './MainLayout.css';
 
 interface MainLayoutProps {
   appData: AppData;
   onDataChange: (data: AppData) => void;
+  // Données déjà enregistrées par le backend (commandes ciblées, validées)
+  onDataSaved: (data: AppData) => void;
+  // Échec d'une commande d'écriture (affiché dans le bandeau de l'application)
+  onSaveError: (error: unknown) => void;
   onLock: () => void;
-  onPasswordChanged: (newPassword: string) => void;
 }
 
 export type ViewMode = 'all' | 'up-to-date' | 'action-required' | 'in-progress';
@@ -19,8 +30,9 @@ export type ViewMode = 'all' | 'up-to-date' | 'action-required' | 'in-progress';
 export const MainLayout: React.FC<MainLayoutProps> = ({
   appData,
   onDataChange,
+  onDataSaved,
+  onSaveError,
   onLock,
-  onPasswordChanged,
 }) => {
   const [currentView, setCurrentView] = useState<ViewMode>('all');
   const [selectedSiteId, setSelectedSiteId] = useState<string | null>(null);
@@ -81,22 +93,47 @@ export const MainLayout: React.FC<MainLayoutProps> = ({
     setSelectedSiteId(null);
   };
 
-  const handleAddSite = (newSite: Site) => {
-    const updatedData = {
-      ...appData,
-      sites: [...appData.sites, newSite],
-    };
-    onDataChange(updatedData);
+  // Validé par le backend : un refus remonte au formulaire (erreurs par champ)
+  const handleAddSite = async (newSite: Site) => {
+    onDataSaved(await addSite(newSite));
     setShowAddModal(false);
   };
 
-  const handleDeleteSite = (siteId: string) => {
-    const updatedData = {
-      ...appData,
-      sites: appData.sites.filter((s) => s.id !== siteId),
-    };
-    onDataChange(updatedData);
-    setSelectedSiteId(null); // Retourner à la liste
+  const handleUpdateSite = async (siteId: string, site: Site) => {
+    onDataSaved(await updateSite(siteId, site));
+  };
+
+  const handleAddIntervention = async (siteId: string, intervention: Intervention) => {
+    onDataSaved(await addIntervention(siteId, intervention));
+  };
+
+  const handleToggleChecklistItem = async (siteId: string, index: number) => {
+    try {
+      onDataSaved(await toggleChecklistItem(siteId, index));
+    } catch (error) {
+      console.error('Erreur checklist:', error);
+      onSaveError(error);
+    }
+  };
+
+  // Le backend met le site à la corbeille et enregistre le coffre
+  const handleDeleteSite = async (siteId: string) => {
+    try {
+      onDataSaved(await deleteSite(siteId));
+      setSelectedSiteId(null); // Retourner à la liste
+    } catch (error) {
+      console.error('Erreur suppression:', error);
+      onSaveError(error);
+    }
+  };
+
+  const handleDeleteIntervention = async (siteId: string, index: number) => {
+    try {
+      onDataSaved(await deleteIntervention(siteId, index));
+    } catch (error) {
+      console.error('Erreur suppression:', error);
+      onSaveError(error);
+    }
   };
 
   const handleImportSites = (importedSites: Site[]) => {
@@ -143,7 +180,6 @@ export const MainLayout: React.FC<MainLayoutProps> = ({
         {showSettings ? (
           <Settings
             onBack={() => setShowSettings(false)}
-            onPasswordChanged={onPasswordChanged}
             appData={appData}
             onImportSites={handleImportSites}
           />
@@ -151,16 +187,18 @@ export const MainLayout: React.FC<MainLayoutProps> = ({
           <SiteDetail
             site={selectedSite}
             onBack={handleBackToList}
-            onUpdate={(updatedSite) => {
-              const updatedData = {
-                ...appData,
-                sites: appData.sites.map((s) =>
-                  s.id === updatedSite.id ? updatedSite : s
-                ),
-              };
-              onDataChange(updatedData);
-            }}
+            onUpdate={(updatedSite) => handleUpdateSite(selectedSite.id, updatedSite)}
+            onAddIntervention={(intervention) =>
+              handleAddIntervention(selectedSite.id, intervention)
+            }
+            onToggleChecklistItem={(index) =>
+              handleToggleChecklistItem(selectedSite.id, index)
+            }
             onDelete={handleDeleteSite}
+            onDeleteIntervention={(index) =>
+              handleDeleteIntervention(selectedSite.id, index)
+            }
+            onSaveError={onSaveError}
           />
         ) : (
           <SitesList
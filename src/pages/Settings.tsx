@@ -1,20 +1,26 @@
 import { useState, useRef, useEffect } from 'react';
 import { Button } from '../components/Button';
 import { Input } from '../components/Input';
-import { changePassword, getDataLocation, setDataLocation } from '../utils/tauri';
+import {
+  applyBackupRetention,
+  changePassword,
+  errorMessage,
+  getDataLocation,
+  previewBackupRetention,
+  setDataLocation,
+} from '../utils/tauri';
 import { exportToExcel, downloadTemplate, importFromExcel } from '../utils/importExport';
 import { open } from '@tauri-apps/api/dialog';
-import { AppData, Site } from '../types';
+import { AppData, RetentionPlan, Site } from '../types';
 import './Settings.css';
 
 interface SettingsProps {
   onBack: () => void;
-  onPasswordChanged: (newPassword: string) => void;
   appData: AppData;
   onImportSites: (sites: Site[]) => void;
 }
 
-export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, appData, onImportSites }) => {
+export const Settings: React.FC<SettingsProps> = ({ onBack, appData, onImportSites }) => {
   const [currentPassword, setCurrentPassword] = useState('');
   const [newPassword, setNewPassword] = useState('');
   const fileInputRef = useRef<HTMLInputElement>(null);
@@ -29,6 +35,13 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
   const [dataLocation, setDataLocationState] = useState<string>('');
   const [storageError, setStorageError] = useState('');
   const [storageSuccess, setStorageSuccess] = useState('');
+  const [removeOldData, setRemoveOldData] = useState(false);
+  const [storageLeftovers, setStorageLeftovers] = useState<string[]>([]);
+
+  // Rétention des backups
+  const [retentionPlan, setRetentionPlan] = useState<RetentionPlan | null>(null);
+  const [retentionError, setRetentionError] = useState('');
+  const [retentionSuccess, setRetentionSuccess] = useState('');
 
   // Charger l'emplacement actuel au montage
   useEffect(() => {
@@ -71,10 +84,8 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
       setCurrentPassword('');
       setNewPassword('');
       setConfirmPassword('');
-      onPasswordChanged(newPassword);
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage || 'Erreur lors du changement de mot de passe');
+      setError(errorMessage(err) || 'Erreur lors du changement de mot de passe');
     } finally {
       setLoading(false);
     }
@@ -117,6 +128,7 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
   const handleChooseDataLocation = async () => {
     setStorageError('');
     setStorageSuccess('');
+    setStorageLeftovers([]);
 
     try {
       // Ouvrir le dialogue pour choisir un dossier
@@ -127,14 +139,37 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
       });
 
       if (selected && typeof selected === 'string') {
-        // Mettre à jour l'emplacement
-        await setDataLocation(selected);
-        setDataLocationState(selected);
-        setStorageSuccess('Emplacement modifié ! Redémarrez l\'application pour appliquer les changements.');
+        // Déplacer les données (rien ne change en cas d'échec)
+        const report = await setDataLocation(selected, removeOldData);
+        setDataLocationState(report.location);
+        setStorageLeftovers(report.leftovers);
+        setStorageSuccess(
+          `Données déplacées : ${report.files} fichier(s) copiés et vérifiés (${(report.bytes / 1024).toFixed(0)} Ko).`
+        );
       }
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setStorageError(errorMessage || 'Erreur lors du changement d\'emplacement');
+      setStorageError(errorMessage(err) || 'Erreur lors du changement d\'emplacement');
+    }
+  };
+
+  const handlePreviewRetention = async () => {
+    setRetentionError('');
+    setRetentionSuccess('');
+    try {
+      setRetentionPlan(await previewBackupRetention());
+    } catch (err) {
+      setRetentionError(errorMessage(err) || 'Erreur lors de l\'analyse des backups');
+    }
+  };
+
+  const handleApplyRetention = async () => {
+    setRetentionError('');
+    try {
+      const applied = await applyBackupRetention();
+      setRetentionPlan(null);
+      setRetentionSuccess(`${applied.delete.length} backup(s) supprimé(s)`);
+    } catch (err) {
+      setRetentionError(errorMessage(err) || 'Erreur lors de la suppression des backups');
     }
   };
 
@@ -250,6 +285,14 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
             </div>
 
             <div className="storage-actions">
+              <label>
+                <input
+                  type="checkbox"
+                  checked={removeOldData}
+                  onChange={(e) => setRemoveOldData(e.target.checked)}
+                />{' '}
+                Supprimer les fichiers de l'ancien emplacement après le déplacement
+              </label>
               <Button variant="primary" onClick={handleChooseDataLocation} icon="📁">
                 Choisir un nouvel emplacement
               </Button>
@@ -259,9 +302,11 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
             {storageSuccess && (
               <div className="form-success">
                 <p>{storageSuccess}</p>
-                <p className="storage-warning">
-                  ⚠️ Important : Les données existantes ne seront pas déplacées automatiquement. Vous devrez copier manuellement le dossier "data" vers le nouvel emplacement.
-                </p>
+                {storageLeftovers.length > 0 && (
+                  <p className="storage-warning">
+                    ⚠️ Fichiers de l'ancien emplacement non supprimés : {storageLeftovers.join(', ')}
+                  </p>
+                )}
               </div>
             )}
 
@@ -276,6 +321,46 @@ export const Settings: React.FC<SettingsProps> = ({ onBack, onPasswordChanged, a
           </div>
         </section>
 
+        <section className="settings-section">
+          <h2>Backups</h2>
+
+          <div className="settings-card">
+            <h3>Rétention</h3>
+            <p className="settings-description">
+              Tous les backups des {appData.settings.backup_keep_days} derniers jours sont conservés,
+              puis un par jour, un par semaine et enfin un par mois. Le nettoyage est fait à chaque sauvegarde.
+            </p>
+
+            <div className="storage-actions">
+              <Button variant="secondary" onClick={handlePreviewRetention} icon="🔍">
+                Aperçu du nettoyage
+              </Button>
+            </div>
+
+            {retentionPlan && (
+              <div className="storage-info">
+                {retentionPlan.delete.length === 0 ? (
+                  <p>Aucun backup à supprimer ({retentionPlan.keep.length} conservés).</p>
+                ) : (
+                  <>
+                    <p>
+                      <strong>{retentionPlan.delete.length} fichier(s)</strong> seraient supprimés
+                      ({(retentionPlan.delete_bytes / 1024).toFixed(0)} Ko),{' '}
+                      {retentionPlan.keep.length} conservés.
+                    </p>
+                    <Button variant="danger" onClick={handleApplyRetention} icon="🗑️">
+                      Supprimer maintenant
+                    </Button>
+                  </>
+                )}
+              </div>
+            )}
+
+            {retentionError && <p className="form-error">{retentionError}</p>}
+            {retentionSuccess && <p className="form-success">{retentionSuccess}</p>}
+          </div>
+        </section>
+
         <section className="settings-section">
           <h2>À propos</h2>
 
@@ -13,11 +13,26 @@ import './SiteDetail.css';
 interface SiteDetailProps {
   site: Site;
   onBack: () => void;
-  onUpdate: (site: Site) => void;
+  // Enregistrée par le backend (validée) ; rejetée en cas de refus
+  onUpdate: (site: Site) => Promise<void>;
+  onAddIntervention: (intervention: Intervention) => Promise<void>;
+  onToggleChecklistItem: (index: number) => void;
   onDelete: (siteId: string) => void;
+  onDeleteIntervention: (index: number) => void;
+  // Échec d'un enregistrement lancé hors formulaire (suppression d'un élément…)
+  onSaveError: (error: unknown) => void;
 }
 
-export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate, onDelete }) => {
+export const SiteDetail: React.FC<SiteDetailProps> = ({
+  site,
+  onBack,
+  onUpdate,
+  onAddIntervention,
+  onToggleChecklistItem,
+  onDelete,
+  onDeleteIntervention,
+  onSaveError,
+}) => {
   const [showPhpMyAdminModal, setShowPhpMyAdminModal] = useState(false);
   const [showEditModal, setShowEditModal] = useState(false);
   const [showChecklistModal, setShowChecklistModal] = useState(false);
@@ -29,21 +44,6 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
   const [showAccountModal, setShowAccountModal] = useState(false);
   const [editingAccountIndex, setEditingAccountIndex] = useState<number | null>(null);
 
-  const handleToggleChecklistItem = (index: number) => {
-    const updatedChecklist = [...site.checklist];
-    updatedChecklist[index] = {
-      ...updatedChecklist[index],
-      done: !updatedChecklist[index].done,
-      date: !updatedChecklist[index].done ? new Date().toISOString().split('T')[0] : null,
-    };
-
-    onUpdate({
-      ...site,
-      checklist: updatedChecklist,
-      last_update: new Date().toISOString(),
-    });
-  };
-
   const openDashlane = async (ref: string, e?: React.MouseEvent) => {
     if (e) {
       e.stopPropagation(); // Empêche l'ouverture du modal d'édition
@@ -86,7 +86,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
     setShowChecklistModal(true);
   };
 
-  const handleSaveChecklist = (item: ChecklistItem) => {
+  const handleSaveChecklist = async (item: ChecklistItem) => {
     let updatedChecklist: ChecklistItem[];
 
     if (editingChecklistIndex !== null) {
@@ -98,7 +98,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       updatedChecklist = [...site.checklist, item];
     }
 
-    onUpdate({
+    await onUpdate({
       ...site,
       checklist: updatedChecklist,
       last_update: new Date().toISOString(),
@@ -114,7 +114,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       ...site,
       checklist: updatedChecklist,
       last_update: new Date().toISOString(),
-    });
+    }).catch(onSaveError);
     setShowChecklistModal(false);
   };
 
@@ -129,35 +129,28 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
     setShowInterventionModal(true);
   };
 
-  const handleSaveIntervention = (intervention: Intervention) => {
-    let updatedInterventions: Intervention[];
-
+  const handleSaveIntervention = async (intervention: Intervention) => {
     if (editingInterventionIndex !== null) {
       // Édition
-      updatedInterventions = [...site.interventions];
+      const updatedInterventions = [...site.interventions];
       updatedInterventions[editingInterventionIndex] = intervention;
+      await onUpdate({
+        ...site,
+        interventions: updatedInterventions,
+        last_update: new Date().toISOString(),
+      });
     } else {
-      // Ajout (en début de liste pour avoir les plus récentes en premier)
-      updatedInterventions = [intervention, ...site.interventions];
+      // Ajout (placée en tête par le backend : les plus récentes en premier)
+      await onAddIntervention(intervention);
     }
-
-    onUpdate({
-      ...site,
-      interventions: updatedInterventions,
-      last_update: new Date().toISOString(),
-    });
     setShowInterventionModal(false);
   };
 
   const handleDeleteIntervention = () => {
     if (editingInterventionIndex === null) return;
 
-    const updatedInterventions = site.interventions.filter((_, i) => i !== editingInterventionIndex);
-    onUpdate({
-      ...site,
-      interventions: updatedInterventions,
-      last_update: new Date().toISOString(),
-    });
+    // Mise à la corbeille par le backend (récupérable)
+    onDeleteIntervention(editingInterventionIndex);
     setShowInterventionModal(false);
   };
 
@@ -172,7 +165,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
     setShowExtensionModal(true);
   };
 
-  const handleSaveExtension = (extension: Extension) => {
+  const handleSaveExtension = async (extension: Extension) => {
     const extensions = site.extensions || [];
     let updatedExtensions: Extension[];
 
@@ -191,7 +184,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       return a.name.localeCompare(b.name);
     });
 
-    onUpdate({
+    await onUpdate({
       ...site,
       extensions: updatedExtensions,
       last_update: new Date().toISOString(),
@@ -208,7 +201,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       ...site,
       extensions: updatedExtensions,
       last_update: new Date().toISOString(),
-    });
+    }).catch(onSaveError);
     setShowExtensionModal(false);
   };
 
@@ -223,7 +216,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
     setShowAccountModal(true);
   };
 
-  const handleSaveAccount = (account: JoomlaAccount) => {
+  const handleSaveAccount = async (account: JoomlaAccount) => {
     const accounts = site.joomla_accounts || [];
     let updatedAccounts: JoomlaAccount[];
 
@@ -234,7 +227,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       updatedAccounts = [...accounts, account];
     }
 
-    onUpdate({
+    await onUpdate({
       ...site,
       joomla_accounts: updatedAccounts,
       last_update: new Date().toISOString(),
@@ -251,7 +244,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       ...site,
       joomla_accounts: updatedAccounts,
       last_update: new Date().toISOString(),
-    });
+    }).catch(onSaveError);
     setShowAccountModal(false);
   };
 
@@ -588,7 +581,7 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
                     <input
                       type="checkbox"
                       checked={item.done}
-                      onChange={() => handleToggleChecklistItem(index)}
+                      onChange={() => onToggleChecklistItem(index)}
                     />
                     <span
                       className={`checklist-task ${item.done ? 'done' : ''}`}
@@ -645,8 +638,8 @@ export const SiteDetail: React.FC<SiteDetailProps> = ({ site, onBack, onUpdate,
       {showEditModal && (
         <EditSiteModal
           site={site}
-          onSave={(updatedSite) => {
-            onUpdate(updatedSite);
+          onSave={async (updatedSite) => {
+            await onUpdate(updatedSite);
             setShowEditModal(false);
           }}
           onClose={() => setShowEditModal(false)}
@@ -1,14 +1,14 @@
-import { useState } from 'react';
+import { useState, useEffect } from 'react';
 import { AppData } from '../types';
 import { Button } from '../components/Button';
 import { Input } from '../components/Input';
-import { unlock, createInitialData } from '../utils/tauri';
+import { unlock, createInitialData, errorMessage, getUnlockWait, isAppError } from '../utils/tauri';
 import { getMockData } from '../utils/mockData';
 import './UnlockScreen.css';
 
 interface UnlockScreenProps {
   dataFileExists: boolean;
-  onUnlock: (data: AppData, password: string) => void;
+  onUnlock: (data: AppData) => void;
   initError?: string;
 }
 
@@ -22,13 +22,39 @@ export const UnlockScreen: React.FC<UnlockScreenProps> = ({
   const [error, setError] = useState(initError || '');
   const [loading, setLoading] = useState(false);
   const [isFirstTime, setIsFirstTime] = useState(!dataFileExists);
+  const [waitSeconds, setWaitSeconds] = useState(0);
+  // Instance qui a déjà ouvert le coffre (ex: "PC-ACCUEIL (PID 4242)")
+  const [vaultHolder, setVaultHolder] = useState<string | null>(null);
 
   // MODE DÉVELOPPEMENT : Utiliser des données mockées
   const isDevelopment = import.meta.env.DEV;
 
+  // Délai imposé après des échecs (conservé par le backend entre deux lancements)
+  const refreshWait = async () => {
+    try {
+      setWaitSeconds(await getUnlockWait());
+    } catch (err) {
+      console.error('Erreur lecture délai:', err);
+    }
+  };
+
+  useEffect(() => {
+    if (!isDevelopment && dataFileExists) {
+      refreshWait();
+    }
+  }, []);
+
+  // Compte à rebours
+  useEffect(() => {
+    if (waitSeconds <= 0) return;
+    const timer = setTimeout(() => setWaitSeconds(waitSeconds - 1), 1000);
+    return () => clearTimeout(timer);
+  }, [waitSeconds]);
+
   const handleUnlock = async (e: React.FormEvent) => {
     e.preventDefault();
     setError('');
+    setVaultHolder(null);
     setLoading(true);
 
     try {
@@ -37,7 +63,7 @@ export const UnlockScreen: React.FC<UnlockScreenProps> = ({
         console.log('🔓 Mode développement : Utilisation de données mockées');
         await new Promise(resolve => setTimeout(resolve, 500)); // Simule chargement
         const mockData = getMockData();
-        onUnlock(mockData, password);
+        onUnlock(mockData);
         return;
       }
 
@@ -55,17 +81,38 @@ export const UnlockScreen: React.FC<UnlockScreenProps> = ({
 
         await createInitialData(password);
         const data = await unlock(password);
-        onUnlock(data, password);
+        onUnlock(data);
       } else {
         // Déverrouillage normal
         const data = await unlock(password);
-        onUnlock(data, password);
+        onUnlock(data);
       }
     } catch (err) {
       console.error('Erreur déverrouillage:', err);
       // Afficher l'erreur exacte pour le débogage
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage || 'Erreur inconnue');
+      setError(errorMessage(err) || 'Erreur inconnue');
+      if (isAppError(err) && err.code === 'VAULT_IN_USE') {
+        setVaultHolder(err.details ?? '');
+      }
+      if (!isDevelopment && !isFirstTime) {
+        await refreshWait();
+      }
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // Coffre ouvert ailleurs : consultation sans écriture
+  const handleReadOnly = async () => {
+    setError('');
+    setLoading(true);
+    try {
+      const data = await unlock(password, undefined, true);
+      onUnlock(data);
+    } catch (err) {
+      console.error('Erreur ouverture lecture seule:', err);
+      setError(errorMessage(err) || 'Erreur inconnue');
+      await refreshWait();
     } finally {
       setLoading(false);
     }
@@ -121,11 +168,35 @@ export const UnlockScreen: React.FC<UnlockScreenProps> = ({
             variant="primary"
             size="lg"
             loading={loading}
+            disabled={!isFirstTime && waitSeconds > 0}
             className="unlock-button"
           >
             {isFirstTime ? 'Créer et déverrouiller' : 'Déverrouiller'}
           </Button>
 
+          {!isFirstTime && waitSeconds > 0 && (
+            <p className="unlock-wait">
+              ⏳ Trop de tentatives : réessayez dans {waitSeconds} s
+            </p>
+          )}
+
+          {vaultHolder !== null && (
+            <div className="unlock-wait">
+              <p>
+                🔒 Coffre déjà ouvert sur {vaultHolder || 'un autre poste'}.
+                Vos modifications écraseraient les siennes.
+              </p>
+              <Button
+                type="button"
+                variant="secondary"
+                loading={loading}
+                onClick={handleReadOnly}
+              >
+                Ouvrir en lecture seule
+              </Button>
+            </div>
+          )}
+
           {isDevelopment && (
             <p className="dev-hint">
               💡 Mode dev : Cliquez sur déverrouiller (mot de passe ignoré)
@@ -2,8 +2,10 @@
 // Correspondent exactement aux structures Rust dans storage.rs
 
 export interface AppData {
+  schema_version?: number;
   sites: Site[];
   settings: AppSettings;
+  trash?: TrashItem[];
 }
 
 export interface Site {
@@ -97,6 +99,226 @@ export interface AppSettings {
   auto_backup: boolean;
   backup_keep_days: number;
   dashlane_cli_path: string;
+  cipher_suite: CipherSuite;
+  trash_keep_days: number; // 0 : jusqu'à ce que la corbeille soit vidée
+}
+
+// Corbeille (voir trash.rs)
+export type TrashedRecord =
+  | { kind: 'site'; site: Site }
+  | { kind: 'intervention'; site_id: string; intervention: Intervention }
+  | { kind: 'contact'; site_id: string; contact: Contact };
+
+export type TrashItem = TrashedRecord & {
+  id: string; // identifiant à passer aux commandes de la corbeille
+  deleted_at_ms: number;
+  index: number; // position d'origine dans sa liste
+};
+
+// Historique d'annulation de la session (voir undo.rs), plus récent d'abord
+export interface UndoSummary {
+  undo: string[];
+  redo: string[];
+}
+
+// Algorithme de chiffrement des sauvegardes (voir crypto.rs)
+export type CipherSuite = 'AES-256-GCM' | 'XChaCha20-Poly1305';
+
+// Emplacements de clé du coffre (voir crypto.rs)
+export type KeySlotKind = 'password' | 'recovery';
+
+export interface KeySlotInfo {
+  id: string;
+  label: string;
+  kind: KeySlotKind;
+  keyfile: boolean;
+  created_at: number;
+}
+
+export interface RecoveryKey {
+  key: string;
+  slot: KeySlotInfo;
+}
+
+// Catalogue des backups (voir backup_catalog.rs)
+export type BackupReason = 'save' | 'restore' | 'migration' | 'manual';
+
+export interface BackupEntry {
+  id: string; // identifiant à passer aux commandes de restauration
+  name: string;
+  created_at_ms: number;
+  size: number;
+  sha256: string;
+  format_version: string | null;
+  reason: BackupReason | null; // null : backup antérieur au catalogue
+  site_count: number | null;
+}
+
+// Différences entre deux versions des données (voir diff.rs)
+export interface FieldChange {
+  path: string; // ex: "extensions[JCE].version"
+  old: unknown | null; // null : élément ajouté
+  new: unknown | null; // null : élément supprimé
+}
+
+export interface SiteRef {
+  id: string;
+  name: string;
+}
+
+export interface SiteDiff {
+  id: string;
+  name: string;
+  changes: FieldChange[];
+}
+
+export interface VaultDiff {
+  added: SiteRef[];
+  removed: SiteRef[];
+  modified: SiteDiff[];
+  settings: FieldChange[];
+}
+
+// Restauration sélective (voir restore.rs)
+export type SiteCollection = 'interventions' | 'extensions' | 'checklist' | 'contacts' | 'joomla_accounts';
+
+export type ConflictStrategy = 'overwrite' | 'skip' | 'rename';
+
+export interface SiteSelection {
+  site_id: string;
+  collections?: SiteCollection[]; // absent : le site entier
+}
+
+export interface BackupSiteSummary {
+  id: string;
+  name: string;
+  enabled: boolean;
+  interventions: number;
+  extensions: number;
+  checklist: number;
+  contacts: number;
+  joomla_accounts: number;
+  exists_in_vault: boolean;
+}
+
+export interface RestoreReport {
+  data: AppData;
+  restored: string[];
+  skipped: string[];
+  renamed: { from: string; to: string }[];
+}
+
+// Journal des modifications (voir journal.rs)
+export interface JournalEvent {
+  at_ms: number;
+  author: string; // ex: "marie@PC-ACCUEIL"
+  site_id: string | null; // null : paramètres de l'application
+  path: string; // "" : site entier ajouté ou supprimé
+  old: unknown | null; // secrets remplacés par "[masqué]"
+  new: unknown | null;
+  index?: number; // position d'un élément ajouté ou supprimé
+}
+
+export interface JournalQuery {
+  site_id?: string;
+  from_ms?: number; // incluse
+  to_ms?: number; // exclue
+}
+
+export interface JournalPage {
+  events: JournalEvent[];
+  unreadable: number; // lignes chiffrées avec une autre clé de données
+}
+
+// Reconstitution à une date (voir history.rs)
+export interface UnresolvedChange {
+  site_id: string | null;
+  path: string;
+}
+
+export interface PointInTime<T> {
+  at_ms: number;
+  snapshot_id: string | null; // backup de départ ; null : coffre actuel
+  replayed: number;
+  // Champs gardant la valeur du point de départ ; un secret masqué d'un
+  // élément recréé (site supprimé depuis…) est vide dans `data`
+  unresolved: UnresolvedChange[];
+  journal_start_ms: number | null; // avant : seul un backup antérieur est exact
+  unreadable: number;
+  data: T;
+}
+
+// Rétention des backups (voir retention.rs)
+export interface BackupFile {
+  name: string;
+  timestamp: number;
+  size: number;
+}
+
+export interface RetentionPlan {
+  keep: BackupFile[];
+  delete: BackupFile[];
+  delete_bytes: number;
+}
+
+// Résultat de set_data_location (voir relocation.rs)
+export interface RelocationReport {
+  location: string;
+  files: number;
+  bytes: number;
+  leftovers: string[];
+}
+
+// Erreurs renvoyées par les commandes Tauri (voir error.rs)
+export type AppErrorCode =
+  | 'WRONG_PASSWORD'
+  | 'KEYFILE_REQUIRED'
+  | 'HEADER_TAMPERED'
+  | 'DATA_TAMPERED'
+  | 'CORRUPT_VAULT'
+  | 'UNSUPPORTED_VERSION'
+  | 'UNSUPPORTED_ALGORITHM'
+  | 'BACKUP_NOT_FOUND'
+  | 'BACKUP_PASSWORD_REQUIRED'
+  | 'THROTTLED'
+  | 'VAULT_IN_USE'
+  | 'READ_ONLY'
+  | 'VAULT_REPLACED'
+  | 'LOCKED'
+  | 'NOT_INITIALIZED'
+  | 'INVALID_INPUT'
+  | 'VALIDATION'
+  | 'CRYPTO'
+  | 'IO'
+  | 'SERIALIZATION';
+
+// Champ modifié à la fois sur le disque et dans l'application
+// (valeur null : champ ou site absent de cette version)
+export interface FieldConflict {
+  site_id: string | null; // null = paramètres
+  field: string; // "" = site entier
+  base: unknown;
+  disk: unknown;
+  local: unknown;
+}
+
+// Résultat de save_data
+export type SaveOutcome =
+  | { status: 'saved' }
+  | { status: 'merged'; data: AppData }
+  | { status: 'conflicts'; data: AppData; conflicts: FieldConflict[] };
+
+// Champ refusé par la validation du backend (ex: "urls.frontend")
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  details: string | null;
+  fields: FieldError[] | null; // code 'VALIDATION' uniquement
 }
 
 // Types pour l'état de l'application
@@ -281,6 +281,8 @@ export function getMockData(): AppData {
       auto_backup: true,
       backup_keep_days: 30,
       dashlane_cli_path: 'auto',
+      cipher_suite: 'AES-256-GCM',
+      trash_keep_days: 30,
     },
   };
 }
@@ -1,6 +1,51 @@
 // Utilitaires pour communiquer avec le backend Rust via Tauri
 import { invoke } from '@tauri-apps/api/tauri';
-import { AppData } from '../types';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import {
+  AppData,
+  AppError,
+  BackupEntry,
+  BackupSiteSummary,
+  ConflictStrategy,
+  Intervention,
+  JournalPage,
+  JournalQuery,
+  KeySlotInfo,
+  PointInTime,
+  RecoveryKey,
+  RelocationReport,
+  RestoreReport,
+  RetentionPlan,
+  SaveOutcome,
+  Site,
+  SiteSelection,
+  TrashItem,
+  UndoSummary,
+  VaultDiff,
+} from '../types';
+
+/**
+ * Indique si une erreur provient d'une commande Tauri ({ code, message, details })
+ */
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+/**
+ * Message affichable pour une erreur quelconque
+ */
+export function errorMessage(err: unknown): string {
+  if (isAppError(err)) return err.message;
+  return err instanceof Error ? err.message : String(err);
+}
+
+/**
+ * Messages de validation par champ (code 'VALIDATION'), ex: { 'urls.frontend': '…' }
+ */
+export function fieldErrors(err: unknown): Record<string, string> {
+  if (!isAppError(err) || !err.fields) return {};
+  return Object.fromEntries(err.fields.map((f) => [f.field, f.message]));
+}
 
 /**
  * Initialise le gestionnaire de stockage
@@ -18,9 +63,34 @@ export async function createInitialData(password: string): Promise<void> {
 
 /**
  * Déverrouille l'application
+ * Le fichier clé n'est requis que si le coffre en exige un.
+ * Échoue avec VAULT_IN_USE si une autre instance a ouvert le coffre :
+ * `readOnly` permet alors de le consulter sans pouvoir l'écraser.
+ */
+export async function unlock(
+  password: string,
+  keyfilePath?: string,
+  readOnly = false
+): Promise<AppData> {
+  return await invoke<AppData>('unlock', {
+    password,
+    keyfilePath: keyfilePath ?? null,
+    readOnly,
+  });
+}
+
+/**
+ * Secondes à attendre avant la prochaine tentative de déverrouillage
+ */
+export async function getUnlockWait(): Promise<number> {
+  return await invoke<number>('get_unlock_wait');
+}
+
+/**
+ * Indique si le coffre exige un fichier clé en plus du mot de passe
  */
-export async function unlock(password: string): Promise<AppData> {
-  return await invoke<AppData>('unlock', { password });
+export async function vaultRequiresKeyfile(): Promise<boolean> {
+  return await invoke<boolean>('vault_requires_keyfile');
 }
 
 /**
@@ -30,6 +100,21 @@ export async function lock(): Promise<void> {
   return await invoke('lock');
 }
 
+/**
+ * Appelé quand le backend verrouille l'application après inactivité
+ * (données et clé déjà effacées côté backend)
+ */
+export async function onAutoLock(callback: () => void): Promise<UnlistenFn> {
+  return await listen('auto-locked', () => callback());
+}
+
+/**
+ * Écoute le passage en lecture seule (verrou repris par une autre instance)
+ */
+export async function onReadOnly(callback: () => void): Promise<UnlistenFn> {
+  return await listen('read-only', () => callback());
+}
+
 /**
  * Vérifie si l'application est verrouillée
  */
@@ -38,10 +123,20 @@ export async function isLocked(): Promise<boolean> {
 }
 
 /**
- * Sauvegarde les données
+ * Indique si le coffre est ouvert en lecture seule
  */
-export async function saveData(password: string, data: AppData): Promise<void> {
-  return await invoke('save_data', { password, data });
+export async function isReadOnly(): Promise<boolean> {
+  return await invoke<boolean>('is_read_only');
+}
+
+/**
+ * Sauvegarde les données (avec la clé de session détenue par le backend)
+ * Si le coffre a été modifié ailleurs depuis le chargement, le backend
+ * renvoie la fusion écrite ('merged') ou les conflits à arbitrer ('conflicts',
+ * rien n'est écrit)
+ */
+export async function saveData(data: AppData): Promise<SaveOutcome> {
+  return await invoke<SaveOutcome>('save_data', { data });
 }
 
 /**
@@ -52,24 +147,279 @@ export async function getData(): Promise<AppData> {
 }
 
 /**
- * Liste les backups disponibles
+ * Ajoute un site (champs validés par le backend : erreur 'VALIDATION' avec
+ * la liste des champs refusés) ; renvoie les données enregistrées
+ */
+export async function addSite(site: Site): Promise<AppData> {
+  return await invoke<AppData>('add_site', { site });
+}
+
+/**
+ * Remplace un site (son identifiant peut changer s'il reste unique)
+ */
+export async function updateSite(siteId: string, site: Site): Promise<AppData> {
+  return await invoke<AppData>('update_site', { siteId, site });
+}
+
+/**
+ * Met un site à la corbeille
+ */
+export async function deleteSite(siteId: string): Promise<AppData> {
+  return await invoke<AppData>('delete_site', { siteId });
+}
+
+/**
+ * Ajoute une intervention au journal d'un site
+ */
+export async function addIntervention(
+  siteId: string,
+  intervention: Intervention
+): Promise<AppData> {
+  return await invoke<AppData>('add_intervention', { siteId, intervention });
+}
+
+/**
+ * Met une intervention à la corbeille
+ */
+export async function deleteIntervention(siteId: string, index: number): Promise<AppData> {
+  return await invoke<AppData>('delete_intervention', { siteId, index });
+}
+
+/**
+ * Met un contact à la corbeille
+ */
+export async function deleteContact(siteId: string, index: number): Promise<AppData> {
+  return await invoke<AppData>('delete_contact', { siteId, index });
+}
+
+/**
+ * Éléments de la corbeille (sites, interventions, contacts supprimés)
+ */
+export async function listTrash(): Promise<TrashItem[]> {
+  return await invoke<TrashItem[]>('list_trash');
+}
+
+/**
+ * Remet un élément de la corbeille à sa place (le site d'une intervention
+ * ou d'un contact doit exister)
+ */
+export async function restoreFromTrash(itemId: string): Promise<AppData> {
+  return await invoke<AppData>('restore_from_trash', { itemId });
+}
+
+/**
+ * Supprime définitivement un élément de la corbeille
+ */
+export async function purgeTrashItem(itemId: string): Promise<AppData> {
+  return await invoke<AppData>('purge_trash_item', { itemId });
+}
+
+/**
+ * Vide la corbeille
+ */
+export async function emptyTrash(): Promise<AppData> {
+  return await invoke<AppData>('empty_trash');
+}
+
+/**
+ * Coche ou décoche une tâche de la checklist d'un site
+ */
+export async function toggleChecklistItem(siteId: string, index: number): Promise<AppData> {
+  return await invoke<AppData>('toggle_checklist_item', { siteId, index });
+}
+
+/**
+ * Annule la dernière modification de la session (erreur si un champ concerné
+ * a été modifié depuis)
  */
-export async function listBackups(): Promise<string[]> {
-  return await invoke<string[]>('list_backups');
+export async function undo(): Promise<AppData> {
+  return await invoke<AppData>('undo');
+}
+
+/**
+ * Rétablit la dernière modification annulée
+ */
+export async function redo(): Promise<AppData> {
+  return await invoke<AppData>('redo');
+}
+
+/**
+ * Descriptions des modifications annulables et rétablissables
+ */
+export async function getUndoHistory(): Promise<UndoSummary> {
+  return await invoke<UndoSummary>('get_undo_history');
+}
+
+/**
+ * Liste les backups disponibles, du plus récent au plus ancien
+ */
+export async function listBackups(): Promise<BackupEntry[]> {
+  return await invoke<BackupEntry[]>('list_backups');
+}
+
+/**
+ * Crée un backup manuel (jamais supprimé par la rétention)
+ */
+export async function createBackup(): Promise<BackupEntry> {
+  return await invoke<BackupEntry>('create_backup');
 }
 
 /**
  * Restaure depuis un backup
+ * Le mot de passe n'est requis que si le backup date d'avant un changement de mot de passe
+ */
+export async function restoreBackup(backupId: string, backupPassword?: string): Promise<AppData> {
+  return await invoke<AppData>('restore_backup', { backupId, backupPassword: backupPassword ?? null });
+}
+
+/**
+ * Sites d'un backup, pour choisir ceux à restaurer
+ */
+export async function listBackupSites(backupId: string, backupPassword?: string): Promise<BackupSiteSummary[]> {
+  return await invoke<BackupSiteSummary[]>('list_backup_sites', {
+    backupId,
+    backupPassword: backupPassword ?? null,
+  });
+}
+
+/**
+ * Restaure des sites d'un backup (ou certaines de leurs listes) sans toucher aux autres
+ * onConflict : conduite à tenir si le site existe déjà (remplacer, ignorer, renommer)
+ */
+export async function restoreSites(
+  backupId: string,
+  selections: SiteSelection[],
+  onConflict: ConflictStrategy,
+  backupPassword?: string
+): Promise<RestoreReport> {
+  return await invoke<RestoreReport>('restore_sites', {
+    backupId,
+    selections,
+    onConflict,
+    backupPassword: backupPassword ?? null,
+  });
+}
+
+/**
+ * Compare deux backups (identifiants), ou un backup et le coffre actuel (to absent)
+ * Les mots de passe ne sont requis que pour un backup antérieur à un changement de mot de passe
+ */
+export async function diffBackups(
+  from: string,
+  to?: string,
+  fromPassword?: string,
+  toPassword?: string
+): Promise<VaultDiff> {
+  return await invoke<VaultDiff>('diff_backups', {
+    from,
+    to: to ?? null,
+    fromPassword: fromPassword ?? null,
+    toPassword: toPassword ?? null,
+  });
+}
+
+/**
+ * Historique des modifications (qui, quoi, quand), par site et/ou par période
+ */
+export async function queryJournal(query: JournalQuery = {}): Promise<JournalPage> {
+  return await invoke<JournalPage>('query_journal', { query });
+}
+
+/**
+ * Coffre tel qu'il était à une date (ms), reconstitué depuis le journal
+ */
+export async function getVaultAt(atMs: number): Promise<PointInTime<AppData>> {
+  return await invoke<PointInTime<AppData>>('get_vault_at', { atMs });
+}
+
+/**
+ * Site tel qu'il était à une date (ms) ; data null s'il n'existait pas
+ */
+export async function getSiteAt(siteId: string, atMs: number): Promise<PointInTime<Site | null>> {
+  return await invoke<PointInTime<Site | null>>('get_site_at', { siteId, atMs });
+}
+
+/**
+ * Aperçu de la rétention des backups (réglage backup_keep_days) : rien n'est supprimé
+ */
+export async function previewBackupRetention(): Promise<RetentionPlan> {
+  return await invoke<RetentionPlan>('preview_backup_retention');
+}
+
+/**
+ * Supprime les backups au-delà de la politique de rétention
  */
-export async function restoreBackup(backupName: string): Promise<void> {
-  return await invoke('restore_backup', { backupName });
+export async function applyBackupRetention(): Promise<RetentionPlan> {
+  return await invoke<RetentionPlan>('apply_backup_retention');
 }
 
 /**
  * Change le mot de passe maître
  */
-export async function changePassword(oldPassword: string, newPassword: string): Promise<void> {
-  return await invoke('change_password', { oldPassword, newPassword });
+export async function changePassword(
+  oldPassword: string,
+  newPassword: string,
+  keyfilePath?: string
+): Promise<void> {
+  return await invoke('change_password', { oldPassword, newPassword, keyfilePath: keyfilePath ?? null });
+}
+
+/**
+ * Génère un nouveau fichier clé aléatoire
+ */
+export async function generateKeyfile(path: string): Promise<void> {
+  return await invoke('generate_keyfile', { path });
+}
+
+/**
+ * Exige un fichier clé en plus du mot de passe
+ * Le fichier clé actuel n'est requis que pour en remplacer un autre
+ */
+export async function attachKeyfile(
+  password: string,
+  keyfilePath: string,
+  currentKeyfilePath?: string
+): Promise<void> {
+  return await invoke('attach_keyfile', {
+    password,
+    keyfilePath,
+    currentKeyfilePath: currentKeyfilePath ?? null,
+  });
+}
+
+/**
+ * Retire l'exigence du fichier clé
+ */
+export async function removeKeyfile(password: string, keyfilePath: string): Promise<void> {
+  return await invoke('remove_keyfile', { password, keyfilePath });
+}
+
+/**
+ * Liste les emplacements de clé du coffre (mots de passe, clés de secours)
+ */
+export async function listKeySlots(): Promise<KeySlotInfo[]> {
+  return await invoke<KeySlotInfo[]>('list_key_slots');
+}
+
+/**
+ * Ajoute un mot de passe supplémentaire (ex: collègue)
+ */
+export async function addPasswordSlot(label: string, password: string): Promise<KeySlotInfo> {
+  return await invoke<KeySlotInfo>('add_password_slot', { label, password });
+}
+
+/**
+ * Génère une clé de secours imprimable (affichée une seule fois)
+ */
+export async function addRecoveryKey(label: string): Promise<RecoveryKey> {
+  return await invoke<RecoveryKey>('add_recovery_key', { label });
+}
+
+/**
+ * Révoque un emplacement de clé
+ */
+export async function revokeKeySlot(slotId: string): Promise<void> {
+  return await invoke('revoke_key_slot', { slotId });
 }
 
 /**
@@ -80,10 +430,14 @@ export async function getDataLocation(): Promise<string> {
 }
 
 /**
- * Change l'emplacement des données
+ * Déplace les données vers un nouveau dossier (copies vérifiées, annulé en
+ * cas d'échec) ; removeSource efface ensuite l'ancien emplacement
  */
-export async function setDataLocation(newPath: string): Promise<void> {
-  return await invoke('set_data_location', { newPath });
+export async function setDataLocation(
+  newPath: string,
+  removeSource = false
+): Promise<RelocationReport> {
+  return await invoke<RelocationReport>('set_data_location', { newPath, removeSource });
 }
 
 /**